@@ -4,16 +4,60 @@ import android.util.Log
 import uniffi.touch_core.AccessibilityService
 
 class AccessibilityImpl: AccessibilityService {
-    override fun dispatchClick(x: Int, y: Int) {
+    override fun dispatchClick(x: Int, y: Int): Boolean {
         val service = MacroAccessibilityService.instance
         if (service != null) {
             // Rust 传过来的是 Int 坐标，转换成 Float 传给 Service
-            service.performClick(x.toFloat(), y.toFloat())
+            return service.performClick(x.toFloat(), y.toFloat())
         } else {
             Log.e("AccessibilityImpl", "❌ 无法执行点击：无障碍服务未连接！")
+            return false
         }
     }
 
-    // 如果未来 Rust 定义了 swipe，也在这里实现
-    // override fun dispatchSwipe(...) { ... }
+    override fun setText(text: String): Boolean {
+        val service = MacroAccessibilityService.instance
+        if (service != null) {
+            return service.performSetText(text)
+        } else {
+            Log.e("AccessibilityImpl", "❌ 无法输入文字：无障碍服务未连接！")
+            return false
+        }
+    }
+
+    override fun performGlobalAction(action: Int): Boolean {
+        val service = MacroAccessibilityService.instance
+        if (service != null) {
+            return service.performGlobalAction(action)
+        } else {
+            Log.e("AccessibilityImpl", "❌ 无法执行全局动作：无障碍服务未连接！")
+            return false
+        }
+    }
+
+    override fun currentPackage(): String? = MacroAccessibilityService.instance?.currentPackage
+
+    override fun currentActivity(): String? = MacroAccessibilityService.instance?.currentActivity
+
+    override fun launchApp(packageName: String): Boolean {
+        val service = MacroAccessibilityService.instance
+        if (service != null) {
+            return service.launchApp(packageName)
+        } else {
+            Log.e("AccessibilityImpl", "❌ 无法启动应用：无障碍服务未连接！")
+            return false
+        }
+    }
+
+    override fun dumpNodeTree(): String? = MacroAccessibilityService.instance?.dumpNodeTree()
+
+    override fun dispatchSwipe(points: List<List<Int>>, durationMs: ULong): Boolean {
+        val service = MacroAccessibilityService.instance
+        if (service != null) {
+            return service.performSwipe(points, durationMs.toLong())
+        } else {
+            Log.e("AccessibilityImpl", "❌ 无法执行滑动：无障碍服务未连接！")
+            return false
+        }
+    }
 }
\ No newline at end of file
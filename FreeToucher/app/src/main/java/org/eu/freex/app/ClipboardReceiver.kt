@@ -0,0 +1,27 @@
+package org.eu.freex.app
+
+import android.app.Activity
+import android.content.BroadcastReceiver
+import android.content.ClipData
+import android.content.ClipboardManager
+import android.content.Context
+import android.content.Intent
+import android.util.Log
+
+/**
+ * Root 模式下由 Rust 通过 am broadcast 调用，把文字写入剪贴板 (随后 Rust 发送粘贴键)
+ * 命令: am broadcast -a org.eu.freex.SET_CLIPBOARD -n org.eu.freex.touchhelper/org.eu.freex.app.ClipboardReceiver --es text "..."
+ * 不导出 (root 的 am broadcast 不受限制)，避免其它应用借它改写剪贴板
+ * 处理完设置 RESULT_OK，Rust 据此确认送达后才发送粘贴键
+ */
+class ClipboardReceiver : BroadcastReceiver() {
+    override fun onReceive(context: Context, intent: Intent) {
+        if (intent.action == "org.eu.freex.SET_CLIPBOARD") {
+            val text = intent.getStringExtra("text") ?: return
+            val clipboard = context.getSystemService(Context.CLIPBOARD_SERVICE) as ClipboardManager
+            clipboard.setPrimaryClip(ClipData.newPlainText("TouchHelper", text))
+            resultCode = Activity.RESULT_OK
+            Log.i("TouchHelper", "ClipboardReceiver set ${text.length} chars")
+        }
+    }
+}
@@ -0,0 +1,68 @@
+package org.eu.freex.app
+
+import android.content.Context
+import android.content.pm.ApplicationInfo
+import android.util.Log
+import java.io.File
+import java.security.SecureRandom
+
+/**
+ * 开发者模式: 脚本调试服务 (9229) + 热重载推送 (9230)
+ * 只在可调试的包里、并且用户在设置页手动打开后才启动
+ *
+ * 端口虽然只监听 127.0.0.1，本机任何应用都能连上，所以连接必须出示令牌:
+ * 每次 App 进程启动生成一个新令牌，写到私有目录 files/dev_token，
+ * 电脑端工具用 `adb shell run-as <包名> cat files/dev_token` 读取 (run-as 只对可调试的包有效)
+ */
+object DevMode {
+    const val PREF_KEY = "dev_mode"
+    const val DEBUG_PORT: UShort = 9229u
+    const val HOT_RELOAD_PORT: UShort = 9230u
+    private const val TOKEN_FILE = "dev_token"
+
+    @Volatile
+    private var token: String? = null
+
+    fun isAvailable(context: Context): Boolean =
+        context.applicationInfo.flags and ApplicationInfo.FLAG_DEBUGGABLE != 0
+
+    fun isEnabled(context: Context): Boolean =
+        isAvailable(context) && context.getSharedPreferences("app_config", Context.MODE_PRIVATE)
+            .getBoolean(PREF_KEY, false)
+
+    /** 本次进程的令牌 (第一次使用时生成并写入私有目录) */
+    fun token(context: Context): String = token ?: synchronized(this) {
+        token ?: generateToken().also {
+            File(context.filesDir, TOKEN_FILE).writeText(it)
+            token = it
+        }
+    }
+
+    /** 开发者模式打开时启动开发服务 (已经在运行的不重复启动) */
+    fun startServers(context: Context) {
+        if (!isEnabled(context)) return
+        val token = token(context)
+        try {
+            if (uniffi.touch_core.debugServerPort() == null) {
+                uniffi.touch_core.startDebugServer(DEBUG_PORT, token)
+            }
+            // npm run android-dev 改完脚本自动推送并重新运行
+            if (!uniffi.touch_core.isHotReloadEnabled()) {
+                uniffi.touch_core.startHotReload(pushPort = HOT_RELOAD_PORT, token = token)
+            }
+        } catch (e: Exception) {
+            Log.e("TouchHelper", "Start dev servers failed", e)
+        }
+    }
+
+    fun stopServers() {
+        uniffi.touch_core.stopDebugServer()
+        uniffi.touch_core.stopHotReload()
+    }
+
+    private fun generateToken(): String {
+        val bytes = ByteArray(16)
+        SecureRandom().nextBytes(bytes)
+        return bytes.joinToString("") { "%02x".format(it) }
+    }
+}
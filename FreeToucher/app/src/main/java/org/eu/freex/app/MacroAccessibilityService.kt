@@ -2,10 +2,18 @@ package org.eu.freex.app
 
 import android.accessibilityservice.AccessibilityService
 import android.accessibilityservice.GestureDescription
+import android.content.ComponentName
 import android.content.Intent
+import android.content.pm.PackageManager
+import android.graphics.Rect
 import android.graphics.Path
+import android.os.Bundle
 import android.util.Log
 import android.view.accessibility.AccessibilityEvent
+import android.view.accessibility.AccessibilityNodeInfo
+import org.json.JSONArray
+import org.json.JSONObject
+import uniffi.touch_core.reportForegroundApp
 
 class MacroAccessibilityService : AccessibilityService() {
 
@@ -28,10 +36,49 @@ class MacroAccessibilityService : AccessibilityService() {
         return super.onUnbind(intent)
     }
 
-    override fun onAccessibilityEvent(event: AccessibilityEvent?) {}
+    // 前台应用 / Activity (来自 TYPE_WINDOW_STATE_CHANGED)，供 Device.currentPackage() 查询
+    @Volatile
+    var currentPackage: String? = null
+        private set
+
+    @Volatile
+    var currentActivity: String? = null
+        private set
+
+    override fun onAccessibilityEvent(event: AccessibilityEvent?) {
+        if (event?.eventType != AccessibilityEvent.TYPE_WINDOW_STATE_CHANGED) return
+        val pkg = event.packageName?.toString() ?: return
+        val cls = event.className?.toString()
+        // 对话框 / 悬浮窗的 className 不是 Activity，只更新包名
+        val activity = cls?.takeIf { isActivity(pkg, it) }
+        currentPackage = pkg
+        currentActivity = activity
+        // 通知脚本的 "foreground" 事件 (相同的会在 Rust 端去重)
+        reportForegroundApp(pkg, activity)
+    }
+
+    private fun isActivity(pkg: String, cls: String): Boolean = try {
+        packageManager.getActivityInfo(ComponentName(pkg, cls), 0)
+        true
+    } catch (e: PackageManager.NameNotFoundException) {
+        false
+    }
+
+    fun launchApp(packageName: String): Boolean {
+        val intent = packageManager.getLaunchIntentForPackage(packageName) ?: return false
+        intent.addFlags(Intent.FLAG_ACTIVITY_NEW_TASK)
+        return try {
+            startActivity(intent)
+            true
+        } catch (e: Exception) {
+            Log.e("MacroService", "❌ 启动 $packageName 失败", e)
+            false
+        }
+    }
+
     override fun onInterrupt() {}
 
-    fun performClick(x: Float, y: Float) {
+    fun performClick(x: Float, y: Float): Boolean {
         Log.d("MacroService", "⚡ 执行点击: ($x, $y)")
         val path = Path()
         path.moveTo(x, y)
@@ -39,6 +86,66 @@ class MacroAccessibilityService : AccessibilityService() {
         val gesture = builder
             .addStroke(GestureDescription.StrokeDescription(path, 0, 100))
             .build()
-        dispatchGesture(gesture, null, null)
+        return dispatchGesture(gesture, null, null)
+    }
+
+    // 沿多点轨迹滑动 (单指一笔)，时长限制在系统允许的手势时长内
+    fun performSwipe(points: List<List<Int>>, durationMs: Long): Boolean {
+        if (points.size < 2) return false
+        Log.d("MacroService", "⚡ 执行滑动: ${points.size} 个点, ${durationMs}ms")
+        val path = Path()
+        path.moveTo(points[0][0].toFloat(), points[0][1].toFloat())
+        for (p in points.drop(1)) {
+            path.lineTo(p[0].toFloat(), p[1].toFloat())
+        }
+        val duration = durationMs.coerceIn(1L, GestureDescription.getMaxGestureDuration())
+        val gesture = GestureDescription.Builder()
+            .addStroke(GestureDescription.StrokeDescription(path, 0, duration))
+            .build()
+        return dispatchGesture(gesture, null, null)
+    }
+
+    // 对当前焦点输入框设置文字 (支持中文等 Unicode)
+    fun performSetText(text: String): Boolean {
+        val node = rootInActiveWindow?.findFocus(AccessibilityNodeInfo.FOCUS_INPUT)
+        if (node == null || !node.isEditable) {
+            Log.w("MacroService", "⚠️ 没有可编辑的焦点输入框")
+            return false
+        }
+        val args = Bundle()
+        args.putCharSequence(AccessibilityNodeInfo.ACTION_ARGUMENT_SET_TEXT_CHARSEQUENCE, text)
+        return node.performAction(AccessibilityNodeInfo.ACTION_SET_TEXT, args)
+    }
+
+    // 当前窗口的节点树 (JSON 快照，格式见 core/src/ui.rs)，供 UiSelector 查找控件
+    fun dumpNodeTree(): String? {
+        val root = rootInActiveWindow ?: return null
+        return nodeToJson(root).toString()
+    }
+
+    private fun nodeToJson(node: AccessibilityNodeInfo): JSONObject {
+        val bounds = Rect()
+        node.getBoundsInScreen(bounds)
+        val json = JSONObject()
+            .put("className", node.className?.toString() ?: "")
+            .put("packageName", node.packageName?.toString() ?: "")
+            .put("bounds", JSONArray(listOf(bounds.left, bounds.top, bounds.right, bounds.bottom)))
+            .put("clickable", node.isClickable)
+            .put("enabled", node.isEnabled)
+            .put("checked", node.isChecked)
+            .put("selected", node.isSelected)
+            .put("focused", node.isFocused)
+            .put("scrollable", node.isScrollable)
+        node.text?.let { json.put("text", it.toString()) }
+        node.contentDescription?.let { json.put("desc", it.toString()) }
+        node.viewIdResourceName?.let { json.put("id", it) }
+        val children = JSONArray()
+        for (i in 0 until node.childCount) {
+            val child = node.getChild(i) ?: continue
+            // 看不见的控件点不到，不放进快照
+            if (child.isVisibleToUser) children.put(nodeToJson(child))
+        }
+        json.put("children", children)
+        return json
     }
 }
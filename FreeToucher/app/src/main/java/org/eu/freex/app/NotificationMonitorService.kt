@@ -0,0 +1,36 @@
+package org.eu.freex.app
+
+import android.app.Notification
+import android.content.Context
+import android.service.notification.NotificationListenerService
+import android.service.notification.StatusBarNotification
+import androidx.core.app.NotificationManagerCompat
+import uniffi.touch_core.reportNotification
+
+/**
+ * 通知监听: 其它应用发出的新通知转给 Rust (reportNotification)，脚本通过 Events.on("notification") 收到
+ * 需要用户在系统设置的 "通知使用权" 里授权 (设置页有入口)
+ */
+class NotificationMonitorService : NotificationListenerService() {
+
+    companion object {
+        fun isEnabled(context: Context): Boolean =
+            NotificationManagerCompat.getEnabledListenerPackages(context).contains(context.packageName)
+    }
+
+    override fun onNotificationPosted(sbn: StatusBarNotification?) {
+        sbn ?: return
+        // 自己的通知 (截屏前台服务) 不上报
+        if (sbn.packageName == packageName) return
+        val notification = sbn.notification ?: return
+        // 分组摘要和组内通知内容重复
+        if (notification.flags and Notification.FLAG_GROUP_SUMMARY != 0) return
+        val extras = notification.extras
+        val title = extras.getCharSequence(Notification.EXTRA_TITLE)?.toString().orEmpty()
+        val text = (extras.getCharSequence(Notification.EXTRA_BIG_TEXT)
+            ?: extras.getCharSequence(Notification.EXTRA_TEXT))?.toString().orEmpty()
+        // 只有进度条 / 图标的通知没有可匹配的内容
+        if (title.isEmpty() && text.isEmpty()) return
+        reportNotification(sbn.packageName, title, text)
+    }
+}
@@ -1,7 +1,9 @@
 package org.eu.freex.app
 
 import android.content.Context
+import android.content.Intent
 import android.os.Bundle
+import android.provider.Settings
 import android.widget.Toast
 import androidx.activity.ComponentActivity
 import androidx.activity.compose.setContent
@@ -44,6 +46,9 @@ fun SettingsScreen() {
     var useRoot by remember {
         mutableStateOf(prefs.getBoolean("use_root", false))
     }
+    var devMode by remember {
+        mutableStateOf(prefs.getBoolean(DevMode.PREF_KEY, false))
+    }
 
     Column(
         modifier = Modifier
@@ -96,6 +101,74 @@ fun SettingsScreen() {
 
         HorizontalDivider(modifier = Modifier.padding(vertical = 8.dp))
 
+        // 开发者模式 (只在可调试的包里显示)
+        if (DevMode.isAvailable(context)) {
+            val toggleDevMode = { newState: Boolean ->
+                devMode = newState
+                prefs.edit { putBoolean(DevMode.PREF_KEY, newState) }
+                if (!newState) DevMode.stopServers()
+                Toast.makeText(context, if (newState) "下次开始运行时开启调试服务" else "调试服务已关闭", Toast.LENGTH_SHORT).show()
+            }
+            Row(
+                modifier = Modifier
+                    .fillMaxWidth()
+                    .clickable { toggleDevMode(!devMode) }
+                    .padding(vertical = 12.dp),
+                verticalAlignment = Alignment.CenterVertically
+            ) {
+                Column(modifier = Modifier.weight(1f)) {
+                    Text(
+                        text = "开发者模式",
+                        fontSize = 18.sp,
+                        fontWeight = FontWeight.Medium
+                    )
+                    Text(
+                        text = if (devMode) {
+                            "令牌: ${DevMode.token(context)}\n电脑端工具通过 adb run-as 自动读取"
+                        } else {
+                            "开启脚本调试服务和热重载推送 (本机端口，需令牌连接)"
+                        },
+                        fontSize = 14.sp,
+                        color = MaterialTheme.colorScheme.onSurfaceVariant
+                    )
+                }
+
+                Switch(
+                    checked = devMode,
+                    onCheckedChange = { toggleDevMode(it) }
+                )
+            }
+
+            HorizontalDivider(modifier = Modifier.padding(vertical = 8.dp))
+        }
+
+        // 通知使用权 (脚本的 "notification" 事件需要)，在系统设置里授权
+        val notificationEnabled = remember { NotificationMonitorService.isEnabled(context) }
+        Row(
+            modifier = Modifier
+                .fillMaxWidth()
+                .clickable {
+                    context.startActivity(Intent(Settings.ACTION_NOTIFICATION_LISTENER_SETTINGS))
+                }
+                .padding(vertical = 12.dp),
+            verticalAlignment = Alignment.CenterVertically
+        ) {
+            Column(modifier = Modifier.weight(1f)) {
+                Text(
+                    text = "通知监听",
+                    fontSize = 18.sp,
+                    fontWeight = FontWeight.Medium
+                )
+                Text(
+                    text = if (notificationEnabled) "已授权，脚本可以收到新通知" else "未授权，点击前往系统设置开启",
+                    fontSize = 14.sp,
+                    color = MaterialTheme.colorScheme.onSurfaceVariant
+                )
+            }
+        }
+
+        HorizontalDivider(modifier = Modifier.padding(vertical = 8.dp))
+
         // 这里可以继续添加其他设置项...
     }
 }
\ No newline at end of file
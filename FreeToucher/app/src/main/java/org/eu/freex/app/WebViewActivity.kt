@@ -44,6 +44,10 @@ import kotlinx.coroutines.CoroutineScope
 import kotlinx.coroutines.Dispatchers
 import kotlinx.coroutines.launch
 import kotlinx.coroutines.withContext
+import uniffi.touch_core.ScriptErrorKind
+import uniffi.touch_core.ScriptEvent
+import uniffi.touch_core.ScriptListener
+import uniffi.touch_core.ScriptStatus
 import uniffi.touch_core.runJsScript
 import java.io.BufferedReader
 import java.io.File
@@ -62,6 +66,7 @@ class WebViewActivity : ComponentActivity() {
     private var isScriptPaused = mutableStateOf(false)
 
     private val SCRIPT_FILENAME = "current_script.js"
+    private val CONFIG_FILENAME = "script_config.json"
 
     // 动态接收器，用于处理 Activity 运行时的热重载
     private val devReceiver = object : BroadcastReceiver() {
@@ -76,10 +81,42 @@ class WebViewActivity : ComponentActivity() {
         }
     }
 
+    /**
+     * 脚本生命周期回调：运行状态以 Rust 为准 (回调在后台线程，切回主线程更新 UI)
+     * 可能同时跑着辅助脚本，按钮状态只跟随主脚本
+     */
+    private val scriptListener = object : ScriptListener {
+        override fun onEvent(scriptId: ULong, event: ScriptEvent) {
+            runOnUiThread {
+                syncScriptStatus()
+                if (event is ScriptEvent.Failed) {
+                    Log.e("TouchHelper", "Script #$scriptId failed: ${event.message}\n${event.stack ?: ""}")
+                    val reason = when (event.kind) {
+                        ScriptErrorKind.OUT_OF_MEMORY -> "内存超限"
+                        ScriptErrorKind.STACK_OVERFLOW -> "调用栈溢出"
+                        ScriptErrorKind.TIMEOUT -> "运行超时"
+                        ScriptErrorKind.EXCEPTION -> "脚本出错"
+                    }
+                    Toast.makeText(this@WebViewActivity, "$reason: ${event.message}", Toast.LENGTH_LONG).show()
+                }
+            }
+        }
+    }
+
+    private fun syncScriptStatus() {
+        val status = uniffi.touch_core.scriptStatus()
+        isScriptRunning.value = status is ScriptStatus.Running || status is ScriptStatus.Paused
+        isScriptPaused.value = status is ScriptStatus.Paused
+    }
+
     @SuppressLint("SetJavaScriptEnabled")
     override fun onCreate(savedInstanceState: Bundle?) {
         super.onCreate(savedInstanceState)
 
+        // 0. 同步脚本状态 (Activity 重建时脚本可能还在跑)
+        uniffi.touch_core.setScriptListener(scriptListener)
+        syncScriptStatus()
+
         // 1. 初始化 WebView 实例
         webView = WebView(this).apply {
             layoutParams = ViewGroup.LayoutParams(
@@ -184,6 +221,8 @@ class WebViewActivity : ComponentActivity() {
                     // 2. 兜底：如果没保存过，读取 Assets 里的默认模板
                     readAssetFile("script.js")
                 }
+                // source map 只和 Assets 里的 script.js 对应 (调试器用它映射回 TS 源码)
+                val sourceMap = if (file.exists()) null else readAssetFile("script.js.map").ifEmpty { null }
 
                 if (scriptContent.isEmpty()) {
                     withContext(Dispatchers.Main) {
@@ -192,12 +231,18 @@ class WebViewActivity : ComponentActivity() {
                     return@launch
                 }
 
-                // 3. 调用 Rust 执行
-                uniffi.touch_core.runJsScript(scriptContent)
+                // 3. 下发配置 (脚本里通过 Config.get 读取)
+                val configFile = File(filesDir, CONFIG_FILENAME)
+                if (configFile.exists()) {
+                    uniffi.touch_core.setConfigJson(configFile.readText())
+                }
+
+                // 4. 设置里打开了开发者模式时开启调试服务 (adb forward tcp:9229 tcp:9229 后用 tools/debug.js 连接)
+                //    和热重载推送 (npm run android-dev 改完脚本自动推送并重新运行)
+                DevMode.startServers(this@WebViewActivity)
 
-                // 更新 UI 状态
-                isScriptRunning.value = true
-                isScriptPaused.value = false
+                // 5. 调用 Rust 执行 (UI 状态由 scriptListener 更新)
+                uniffi.touch_core.runJsScript(scriptContent, sourceMap = sourceMap)
 
             } catch (e: Exception) {
                 Log.e("TouchHelper", "Run failed", e)
@@ -211,8 +256,10 @@ class WebViewActivity : ComponentActivity() {
 
     private fun initRust(isRoot: Boolean) {
         try {
+            // 脚本的 Storage 等持久化数据存在 App 私有目录下
+            uniffi.touch_core.setDataDir(filesDir.absolutePath)
             if (isRoot) {
-                uniffi.touch_core.initService(true, AndroidLogger(), null)
+                uniffi.touch_core.initService(true, AndroidLogger(), null, packageName)
             } else {
                 val adapter = AccessibilityImpl()
                 uniffi.touch_core.initService(false, AndroidLogger(), adapter)
@@ -224,15 +271,12 @@ class WebViewActivity : ComponentActivity() {
     private fun stopScript() {
         CoroutineScope(Dispatchers.IO).launch {
             uniffi.touch_core.stopScript()
-            isScriptRunning.value = false
-            isScriptPaused.value = false
         }
     }
 
     private fun pauseScript(paused: Boolean) {
         CoroutineScope(Dispatchers.IO).launch {
             uniffi.touch_core.setPaused(paused)
-            isScriptPaused.value = paused
         }
     }
 
@@ -265,6 +309,12 @@ class WebViewActivity : ComponentActivity() {
         unregisterReceiver(devReceiver)
     }
 
+    override fun onDestroy() {
+        super.onDestroy()
+        // 避免 Rust 持有已销毁的 Activity
+        uniffi.touch_core.setScriptListener(null)
+    }
+
     // 🔥 核心修改：JS 交互接口适配 UniFFI
     inner class JSBridge {
         @JavascriptInterface
@@ -282,6 +332,24 @@ class WebViewActivity : ComponentActivity() {
             }
         }
 
+        /**
+         * 保存配置并立即下发给 Rust，脚本运行中也会收到 Config.onChange 通知
+         */
+        @JavascriptInterface
+        fun saveConfig(json: String) {
+            CoroutineScope(Dispatchers.IO).launch {
+                try {
+                    openFileOutput(CONFIG_FILENAME, MODE_PRIVATE).use {
+                        it.write(json.toByteArray())
+                    }
+                    uniffi.touch_core.setConfigJson(json)
+                    Log.i("TouchHelper", "Config saved to $CONFIG_FILENAME")
+                } catch (e: Exception) {
+                    Log.e("TouchHelper", "Save config failed", e)
+                }
+            }
+        }
+
         @JavascriptInterface
         fun log(msg: String) {
             Log.i("TouchHelper-Web", msg)
@@ -1,15 +1,42 @@
+use std::sync::Arc;
+
+use crate::api::assets::Assets;
 use crate::api::colors::Colors;
+use crate::api::config::Config;
 use crate::api::device::Device;
+use crate::api::events::Events;
+use crate::api::files::{Files, ScriptFiles};
+use crate::api::image::Image;
+use crate::api::screen::Screen;
+use crate::api::storage::Storage;
 use crate::api::thread::Thread;
+use crate::api::ui::UiSelector;
+use crate::bundle::ScriptBundle;
+use crate::input::{arbiter, InputController, InputError};
+use crate::js_engine::ScriptControl;
+use crate::manifest::NetworkConfig;
+use crate::sandbox::Sandbox;
 use crate::uniffi_binding::CONTROLLER;
-use log::{error, info};
+use log::info;
 use rquickjs::prelude::Func;
-use rquickjs::{Class, Ctx, Object, Result};
+use rquickjs::{Class, Ctx, Exception, Object, Result};
 
+pub mod assets;
 pub mod colors;
+pub mod config;
 pub mod device;
+pub mod events;
+pub mod fetch;
+pub mod files;
 pub mod image;
+pub mod screen;
+pub mod storage;
 pub mod thread;
+pub mod timer;
+pub mod ui;
+
+/// 等待其它脚本释放设备时的轮询间隔
+const INPUT_WAIT_POLL: std::time::Duration = std::time::Duration::from_millis(10);
 
 /// 全局函数：日志 (Log 是最常用的，保持全局)
 #[rquickjs::function]
@@ -18,36 +45,115 @@ pub fn log(msg: String) {
 }
 
 // 供子模块使用的辅助函数
-pub(crate) fn with_controller<F>(f: F)
+pub(crate) fn with_controller<F, R>(f: F) -> std::result::Result<R, InputError>
 where
-    F: FnOnce(&dyn crate::input::InputController),
+    F: FnOnce(&dyn InputController) -> std::result::Result<R, InputError>,
 {
-    if let Ok(guard) = CONTROLLER.lock() {
-        if let Some(ctrl) = guard.as_ref() {
-            // 🔥 核心修复：添加 .as_ref()
-            // ctrl 是 &Box<dyn InputController>
-            // ctrl.as_ref() 变成了 &dyn InputController
-            f(ctrl.as_ref());
-        } else {
-            error!("[JS] Controller not initialized");
+    let guard = CONTROLLER
+        .lock()
+        .map_err(|_| InputError::CommandFailed("controller lock poisoned".into()))?;
+    match guard.as_ref() {
+        // ctrl 是 &Box<dyn InputController>，.as_ref() 变成 &dyn InputController
+        Some(ctrl) => f(ctrl.as_ref()),
+        None => Err(InputError::NotInitialized),
+    }
+}
+
+/// 取消点: 每个原生 API 入口调用
+/// 暂停中则阻塞等待恢复；脚本已被停止则抛异常 (中断回调随后会终止剩余的 JS)
+pub(crate) fn checkpoint(ctx: &Ctx<'_>) -> Result<()> {
+    let Some(control) = ctx.userdata::<ScriptControl>() else {
+        return Ok(());
+    };
+    if control.wait_while_paused() {
+        return Err(Exception::throw_internal(ctx, "Script aborted"));
+    }
+    Ok(())
+}
+
+/// 等待输入仲裁放行 (其它脚本持有设备时阻塞)，等待期间仍响应暂停 / 停止
+pub(crate) fn wait_for_input(ctx: &Ctx<'_>) -> Result<()> {
+    let Some(control) = ctx.userdata::<ScriptControl>().map(|c| c.clone()) else {
+        return Ok(());
+    };
+    while !arbiter::try_enter(control.id(), control.input_priority()) {
+        if control.wait_while_paused() {
+            return Err(Exception::throw_internal(ctx, "Script aborted"));
         }
+        std::thread::sleep(INPUT_WAIT_POLL);
     }
+    Ok(())
+}
+
+/// 设备动作的统一入口: 取消点 + 输入仲裁 + 执行 + InputError 转 JS 异常
+pub(crate) fn device_action<'js, F, R>(ctx: &Ctx<'js>, f: F) -> Result<R>
+where
+    F: FnOnce(&dyn InputController) -> std::result::Result<R, InputError>,
+{
+    checkpoint(ctx)?;
+    wait_for_input(ctx)?;
+    throw_input_error(ctx, with_controller(f))
+}
+
+/// 把 InputError 转成 JS 异常抛出 (附带 code 字段，方便脚本 catch 后判断)
+pub(crate) fn throw_input_error<'js, T>(
+    ctx: &Ctx<'js>,
+    result: std::result::Result<T, InputError>,
+) -> Result<T> {
+    result.or_else(|e| {
+        let ex = Exception::from_message(ctx.clone(), &e.to_string())?;
+        ex.as_object().set("name", "InputError")?;
+        ex.as_object().set("code", e.code())?;
+        Err(ex.throw())
+    })
 }
 
 /// 注册所有类和全局函数
-pub fn register_globals<'js>(globals: &Object<'js>, ctx: &Ctx<'js>) -> Result<()> {
+/// bundle: 以脚本包方式运行时传入，用于注册 Assets 和 Files 的 bundle://
+/// namespace: Storage / Files 的命名空间 (ScriptSource::namespace)
+pub fn register_globals<'js>(
+    globals: &Object<'js>,
+    ctx: &Ctx<'js>,
+    bundle: Option<Arc<ScriptBundle>>,
+    network: NetworkConfig,
+    namespace: &str,
+) -> Result<()> {
     // 1. 注册全局函数
     globals.set("log", Func::new(log))?;
+    timer::register(globals, ctx)?;
+    fetch::register(globals, ctx, network)?;
 
     // 2. 注册类 (Class Definition)
     Class::<Colors>::define(globals)?;
+    Class::<Config>::define(globals)?;
     Class::<Device>::define(globals)?;
+    Class::<Events>::define(globals)?;
+    Class::<Files>::define(globals)?;
+    Class::<Image>::define(globals)?;
+    Class::<Screen>::define(globals)?;
+    Class::<Storage>::define(globals)?;
     Class::<Thread>::define(globals)?;
+    Class::<UiSelector>::define(globals)?;
 
     // 将实例绑定到全局变量
     globals.set("Colors", Class::instance(ctx.clone(), Colors::new()))?;
+    globals.set("Config", Class::instance(ctx.clone(), Config::new()))?;
     globals.set("Device", Class::instance(ctx.clone(), Device::new()))?;
+    globals.set("Events", Class::instance(ctx.clone(), Events::new()))?;
+    globals.set("Screen", Class::instance(ctx.clone(), Screen::new()))?;
     globals.set("Thread", Class::instance(ctx.clone(), Thread::new()))?;
+    let sandbox = Arc::new(Sandbox::new(bundle.clone(), namespace));
+    let _ = ctx.store_userdata(ScriptFiles(sandbox.clone()));
+    globals.set("Files", Class::instance(ctx.clone(), Files::new(sandbox)))?;
+    globals.set(
+        "Storage",
+        Class::instance(ctx.clone(), Storage::new(namespace.to_string())),
+    )?;
+
+    if let Some(bundle) = bundle {
+        Class::<Assets>::define(globals)?;
+        globals.set("Assets", Class::instance(ctx.clone(), Assets::new(bundle)))?;
+    }
 
     Ok(())
 }
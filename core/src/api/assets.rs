@@ -0,0 +1,52 @@
+// ==========================================================
+// Assets 类 (只读访问脚本包内的资源)
+// JS 使用: Assets.readBytes("assets/btn_start.png")
+// ==========================================================
+
+use std::sync::Arc;
+
+use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
+
+use crate::bundle::ScriptBundle;
+
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct Assets {
+    #[qjs(skip_trace)]
+    bundle: Arc<ScriptBundle>,
+}
+
+impl Assets {
+    pub fn new(bundle: Arc<ScriptBundle>) -> Self {
+        Self { bundle }
+    }
+}
+
+#[rquickjs::methods]
+impl Assets {
+    /// 读取文本 (UTF-8)，路径相对包根目录
+    #[qjs(rename = "readText")]
+    pub fn read_text<'js>(&self, ctx: Ctx<'js>, path: String) -> rquickjs::Result<String> {
+        self.bundle
+            .read_text(&path)
+            .map_err(|e| Exception::throw_message(&ctx, &e.to_string()))
+    }
+
+    /// 读取二进制
+    #[qjs(rename = "readBytes")]
+    pub fn read_bytes<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        path: String,
+    ) -> rquickjs::Result<ArrayBuffer<'js>> {
+        let data = self
+            .bundle
+            .read(&path)
+            .map_err(|e| Exception::throw_message(&ctx, &e.to_string()))?;
+        ArrayBuffer::new(ctx, data)
+    }
+
+    pub fn exists(&self, path: String) -> bool {
+        self.bundle.exists(&path)
+    }
+}
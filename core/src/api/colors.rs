@@ -1,6 +1,7 @@
+use crate::api::checkpoint;
 use crate::core;
 use rquickjs::class::Trace;
-use rquickjs::JsLifetime;
+use rquickjs::{Ctx, JsLifetime};
 
 // 1. 定义结构体 (保留 class 宏以注册元数据)
 #[derive(Trace, JsLifetime)]
@@ -25,18 +26,24 @@ impl Colors {
 
     // 🔥 核心修复：使用 #[qjs(rename = "...")]
     #[qjs(rename = "findColor")]
-    pub fn find_color(&self, color: String) -> bool {
+    pub fn find_color<'js>(&self, ctx: Ctx<'js>, color: String) -> rquickjs::Result<bool> {
+        checkpoint(&ctx)?;
         let target = core::parse_hex_color(&color);
-        core::find_color_helper(target, 10, None).is_some()
+        Ok(core::find_color_helper(target, 10, None).is_some())
     }
 
     #[qjs(rename = "findColorPoint")]
-    pub fn find_color_point(&self, color: String) -> Option<Vec<i32>> {
+    pub fn find_color_point<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        color: String,
+    ) -> rquickjs::Result<Option<Vec<i32>>> {
+        checkpoint(&ctx)?;
         let target = core::parse_hex_color(&color);
         if let Some((x, y)) = core::find_color_helper(target, 10, None) {
-            Some(vec![x, y])
+            Ok(Some(vec![x, y]))
         } else {
-            None
+            Ok(None)
         }
     }
 }
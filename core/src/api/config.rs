@@ -0,0 +1,197 @@
+// ==========================================================
+// Config 类 (读取宿主下发的脚本配置)
+// JS 使用: Config.getInt("loops", 3)
+//         Config.onChange((key, value, old) => log(key + " -> " + value))
+// ==========================================================
+
+use rquickjs::{
+    class::Trace, prelude::Opt, CatchResultExt, Class, Ctx, Exception, Function, JsLifetime, Value,
+};
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+use crate::config::{ConfigChange, CONFIG};
+
+#[derive(Trace, JsLifetime, Default)]
+#[rquickjs::class]
+pub struct Config<'js> {
+    listeners: Vec<Function<'js>>,
+}
+
+impl<'js> Config<'js> {
+    pub fn new() -> Self {
+        Self { listeners: vec![] }
+    }
+}
+
+/// serde_json::Value -> JS 值
+fn to_js<'js>(ctx: &Ctx<'js>, value: &serde_json::Value) -> rquickjs::Result<Value<'js>> {
+    ctx.json_parse(value.to_string())
+}
+
+/// 按类型读取: 不存在时返回 default，都没有就抛异常
+fn lookup<'js, T>(
+    ctx: &Ctx<'js>,
+    key: &str,
+    default: Opt<T>,
+    expected: &str,
+    convert: impl FnOnce(&serde_json::Value) -> Option<T>,
+) -> rquickjs::Result<T> {
+    let value = CONFIG.lock().unwrap().get(key).cloned();
+    match (value, default.0) {
+        (Some(v), _) => convert(&v).ok_or_else(|| {
+            Exception::throw_type(ctx, &format!("Config `{}` is not a {}", key, expected))
+        }),
+        (None, Some(d)) => Ok(d),
+        (None, None) => Err(Exception::throw_reference(
+            ctx,
+            &format!("Config `{}` is not set", key),
+        )),
+    }
+}
+
+#[rquickjs::methods]
+impl<'js> Config<'js> {
+    /// 读取任意类型的值，不存在时返回 default (默认 undefined)
+    pub fn get(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        default: Opt<Value<'js>>,
+    ) -> rquickjs::Result<Value<'js>> {
+        let value = CONFIG.lock().unwrap().get(&key).cloned();
+        match (value, default.0) {
+            (Some(v), _) => to_js(&ctx, &v),
+            (None, Some(d)) => Ok(d),
+            (None, None) => Ok(Value::new_undefined(ctx)),
+        }
+    }
+
+    #[qjs(rename = "getString")]
+    pub fn get_string(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        default: Opt<String>,
+    ) -> rquickjs::Result<String> {
+        lookup(&ctx, &key, default, "string", |v| {
+            v.as_str().map(str::to_string)
+        })
+    }
+
+    #[qjs(rename = "getInt")]
+    pub fn get_int(&self, ctx: Ctx<'js>, key: String, default: Opt<i64>) -> rquickjs::Result<i64> {
+        lookup(&ctx, &key, default, "int", |v| {
+            // 3.0 这种整数值的浮点也接受
+            v.as_i64()
+                .or_else(|| v.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+        })
+    }
+
+    #[qjs(rename = "getFloat")]
+    pub fn get_float(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        default: Opt<f64>,
+    ) -> rquickjs::Result<f64> {
+        lookup(&ctx, &key, default, "float", |v| v.as_f64())
+    }
+
+    #[qjs(rename = "getBool")]
+    pub fn get_bool(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        default: Opt<bool>,
+    ) -> rquickjs::Result<bool> {
+        lookup(&ctx, &key, default, "bool", |v| v.as_bool())
+    }
+
+    #[qjs(rename = "getList")]
+    pub fn get_list(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        default: Opt<Value<'js>>,
+    ) -> rquickjs::Result<Value<'js>> {
+        let value = CONFIG.lock().unwrap().get(&key).cloned();
+        match (value, default.0) {
+            (Some(v), _) if v.is_array() => to_js(&ctx, &v),
+            (Some(_), _) => Err(Exception::throw_type(
+                &ctx,
+                &format!("Config `{}` is not a list", key),
+            )),
+            (None, Some(d)) => Ok(d),
+            (None, None) => Err(Exception::throw_reference(
+                &ctx,
+                &format!("Config `{}` is not set", key),
+            )),
+        }
+    }
+
+    pub fn has(&self, key: String) -> bool {
+        CONFIG.lock().unwrap().get(&key).is_some()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        CONFIG.lock().unwrap().keys()
+    }
+
+    /// 全部配置 (普通对象，修改它不会写回)
+    pub fn all(&self, ctx: Ctx<'js>) -> rquickjs::Result<Value<'js>> {
+        let snapshot = CONFIG.lock().unwrap().snapshot();
+        to_js(&ctx, &snapshot)
+    }
+
+    /// 监听宿主在脚本运行中下发的修改: (key, value, oldValue) => void
+    #[qjs(rename = "onChange")]
+    pub fn on_change(&mut self, listener: Function<'js>) {
+        self.listeners.push(listener);
+    }
+}
+
+/// 启动配置变更分发: 把宿主的修改转给 JS 监听器
+/// finished 变为 true 后退出 (否则 rt.idle() 永远等不到结束)
+pub fn spawn_change_pump(ctx: &Ctx<'_>, mut finished: watch::Receiver<bool>) {
+    let mut changes = CONFIG.lock().unwrap().subscribe();
+    let pump_ctx = ctx.clone();
+    ctx.spawn(async move {
+        loop {
+            tokio::select! {
+                _ = finished.changed() => break,
+                change = changes.recv() => match change {
+                    Ok(change) => dispatch(&pump_ctx, change),
+                    Err(RecvError::Lagged(n)) => log::warn!("Config: dropped {} changes", n),
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    });
+}
+
+fn dispatch(ctx: &Ctx<'_>, change: ConfigChange) {
+    let Ok(config) = ctx.globals().get::<_, Class<Config>>("Config") else {
+        return;
+    };
+    // 先拷贝一份，监听器里再调 onChange 也不会冲突
+    let listeners = config.borrow().listeners.clone();
+    if listeners.is_empty() {
+        return;
+    }
+
+    let call = || -> rquickjs::Result<()> {
+        let optional = |value: &Option<serde_json::Value>| match value {
+            Some(value) => to_js(ctx, value),
+            None => Ok(Value::new_undefined(ctx.clone())),
+        };
+        let value = optional(&change.value)?;
+        let old = optional(&change.old)?;
+        for listener in &listeners {
+            listener.call::<_, ()>((change.key.as_str(), value.clone(), old.clone()))?;
+        }
+        Ok(())
+    };
+    if let Err(e) = call().catch(ctx) {
+        log::error!("Config listener error: {}", e);
+    }
+}
@@ -3,9 +3,21 @@
 // JS 使用: Device.click(100, 100)
 // ==========================================================
 
-use rquickjs::{class::Trace, JsLifetime};
+use rquickjs::{class::Trace, prelude::Opt, Ctx, Exception, JsLifetime, Object, Value};
 
-use crate::{api::with_controller, core::map_coordinates};
+use crate::{
+    api::{checkpoint, device_action, throw_input_error, with_controller},
+    core::map_coordinates,
+    input::{
+        apps::is_valid_package,
+        arbiter,
+        keys::{key_code_from_name, KeyEvent, MAX_KEY_REPEAT},
+        TextInputMethod,
+    },
+    js_engine::ScriptControl,
+};
+
+const ACQUIRE_POLL: std::time::Duration = std::time::Duration::from_millis(10);
 
 #[derive(Trace, JsLifetime)]
 #[rquickjs::class]
@@ -25,27 +37,202 @@ impl Device {
     }
 
     #[qjs(rename = "click")]
-    pub fn click(&self, x: i32, y: i32) {
-        with_controller(|ctrl| {
+    pub fn click<'js>(&self, ctx: Ctx<'js>, x: i32, y: i32) -> rquickjs::Result<()> {
+        device_action(&ctx, |ctrl| {
             let (rx, ry) = map_coordinates(x, y);
-            ctrl.click(rx, ry);
-        });
+            ctrl.click(rx, ry)
+        })
+    }
+
+    /// 在矩形区域内点击 (开启拟人化时区域内随机取点)
+    #[qjs(rename = "clickRect")]
+    pub fn click_rect<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+    ) -> rquickjs::Result<()> {
+        let (Some(x2), Some(y2)) = (x.checked_add(w), y.checked_add(h)) else {
+            return Err(Exception::throw_range(
+                &ctx,
+                &format!("Rect [{}, {}, {}, {}] is out of range", x, y, w, h),
+            ));
+        };
+        device_action(&ctx, |ctrl| {
+            let (rx, ry) = map_coordinates(x, y);
+            let (rx2, ry2) = map_coordinates(x2, y2);
+            ctrl.click_in_rect(rx, ry, rx2.saturating_sub(rx), ry2.saturating_sub(ry))
+        })
     }
 
-    pub fn swipe(&self, x1: i32, y1: i32, x2: i32, y2: i32, duration: u64) {
-        with_controller(|ctrl| {
+    /// 长按
+    pub fn press<'js>(&self, ctx: Ctx<'js>, x: i32, y: i32, duration: u64) -> rquickjs::Result<()> {
+        device_action(&ctx, |ctrl| {
+            let (rx, ry) = map_coordinates(x, y);
+            ctrl.press(rx, ry, duration)
+        })
+    }
+
+    pub fn swipe<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        duration: u64,
+    ) -> rquickjs::Result<()> {
+        device_action(&ctx, |ctrl| {
             let (rx1, ry1) = map_coordinates(x1, y1);
             let (rx2, ry2) = map_coordinates(x2, y2);
-            let mut points = Vec::new();
-            points.push(vec![rx1, ry1]);
-            points.push(vec![rx2, ry2]);
-            ctrl.swipe(&points, duration);
-        });
+            let points = vec![vec![rx1, ry1], vec![rx2, ry2]];
+            ctrl.swipe(&points, duration)
+        })
+    }
+
+    /// 输入文字，method: "auto" (默认) / "keyboard" / "clipboard" / "ime"
+    #[qjs(rename = "inputText")]
+    pub fn input_text<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        text: String,
+        method: Opt<String>,
+    ) -> rquickjs::Result<()> {
+        let method = match method.0 {
+            Some(name) => TextInputMethod::parse(&name).ok_or_else(|| {
+                Exception::throw_type(&ctx, &format!("Unknown text input method: {}", name))
+            })?,
+            None => TextInputMethod::Auto,
+        };
+        device_action(&ctx, |ctrl| ctrl.input_text(&text, method))
+    }
+
+    /// 按键: Device.key("BACK") / Device.key(4, { longPress: true, repeat: 2, meta: ["CTRL"] })
+    pub fn key<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        key: Value<'js>,
+        options: Opt<Object<'js>>,
+    ) -> rquickjs::Result<()> {
+        let mut event = KeyEvent::press(resolve_key(&ctx, &key)?);
+        if let Some(opts) = options.0 {
+            event.long_press = opts.get::<_, Option<bool>>("longPress")?.unwrap_or(false);
+            let repeat = opts.get::<_, Option<f64>>("repeat")?.unwrap_or(1.0);
+            if !(1.0..=MAX_KEY_REPEAT as f64).contains(&repeat) {
+                return Err(Exception::throw_range(
+                    &ctx,
+                    &format!(
+                        "repeat must be within 1..={}, got {}",
+                        MAX_KEY_REPEAT, repeat
+                    ),
+                ));
+            }
+            event.repeat = repeat as u32;
+            if let Some(meta) = opts.get::<_, Option<Vec<Value<'js>>>>("meta")? {
+                event.meta = meta
+                    .iter()
+                    .map(|m| resolve_key(&ctx, m))
+                    .collect::<rquickjs::Result<_>>()?;
+            }
+        }
+        device_action(&ctx, |ctrl| ctrl.key_event(&event))
+    }
+
+    /// 只按下，需配合 keyUp
+    #[qjs(rename = "keyDown")]
+    pub fn key_down<'js>(&self, ctx: Ctx<'js>, key: Value<'js>) -> rquickjs::Result<()> {
+        let event = KeyEvent::down(resolve_key(&ctx, &key)?);
+        device_action(&ctx, |ctrl| ctrl.key_event(&event))
+    }
+
+    #[qjs(rename = "keyUp")]
+    pub fn key_up<'js>(&self, ctx: Ctx<'js>, key: Value<'js>) -> rquickjs::Result<()> {
+        let event = KeyEvent::up(resolve_key(&ctx, &key)?);
+        device_action(&ctx, |ctrl| ctrl.key_event(&event))
+    }
+
+    /// 显式持有设备，期间其它低优先级脚本的动作会等待 (ms 不传则直到 release)
+    /// 用于一段不能被打断的连续操作
+    pub fn acquire<'js>(&self, ctx: Ctx<'js>, ms: Opt<u64>) -> rquickjs::Result<()> {
+        let Some(control) = ctx.userdata::<ScriptControl>().map(|c| c.clone()) else {
+            return Ok(());
+        };
+        while !arbiter::try_acquire(control.id(), control.input_priority(), ms.0) {
+            checkpoint(&ctx)?;
+            std::thread::sleep(ACQUIRE_POLL);
+        }
+        Ok(())
     }
 
-    pub fn shell(&self, cmd: String) {
-        with_controller(|ctrl| {
-            ctrl.shell(cmd.as_str());
-        });
+    pub fn release<'js>(&self, ctx: Ctx<'js>) {
+        if let Some(control) = ctx.userdata::<ScriptControl>() {
+            arbiter::release_all(control.id());
+        }
+    }
+
+    /// shell 不经过输入仲裁 (不操作屏幕)
+    pub fn shell<'js>(&self, ctx: Ctx<'js>, cmd: String) -> rquickjs::Result<String> {
+        checkpoint(&ctx)?;
+        throw_input_error(&ctx, with_controller(|ctrl| ctrl.shell(cmd.as_str())))
+    }
+
+    /// 前台应用的包名，识别不出 (锁屏等) 返回 undefined
+    #[qjs(rename = "currentPackage")]
+    pub fn current_package<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<Option<String>> {
+        checkpoint(&ctx)?;
+        let app = throw_input_error(&ctx, with_controller(|ctrl| ctrl.foreground_app()))?;
+        Ok(app.map(|app| app.package))
+    }
+
+    /// 前台 Activity 的完整类名 (如 "com.game.MainActivity")
+    #[qjs(rename = "currentActivity")]
+    pub fn current_activity<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<Option<String>> {
+        checkpoint(&ctx)?;
+        let app = throw_input_error(&ctx, with_controller(|ctrl| ctrl.foreground_app()))?;
+        Ok(app.and_then(|app| app.activity))
+    }
+
+    /// 启动应用 (已在运行则切回前台)，会改变画面所以参与输入仲裁
+    #[qjs(rename = "launchApp")]
+    pub fn launch_app<'js>(&self, ctx: Ctx<'js>, package: String) -> rquickjs::Result<()> {
+        check_package(&ctx, &package)?;
+        device_action(&ctx, |ctrl| ctrl.launch_app(&package))
+    }
+
+    /// 强制停止应用 (需要 Root)
+    #[qjs(rename = "forceStop")]
+    pub fn force_stop<'js>(&self, ctx: Ctx<'js>, package: String) -> rquickjs::Result<()> {
+        check_package(&ctx, &package)?;
+        device_action(&ctx, |ctrl| ctrl.force_stop(&package))
+    }
+}
+
+fn check_package(ctx: &Ctx<'_>, package: &str) -> rquickjs::Result<()> {
+    if is_valid_package(package) {
+        Ok(())
+    } else {
+        Err(Exception::throw_type(
+            ctx,
+            &format!("Invalid package name: {}", package),
+        ))
+    }
+}
+
+/// 键名 ("BACK" / "KEYCODE_ENTER" / "a") 或键值 (4) -> 键值
+fn resolve_key<'js>(ctx: &Ctx<'js>, key: &Value<'js>) -> rquickjs::Result<i32> {
+    if let Some(code) = key.as_int() {
+        return Ok(code);
+    }
+    if let Some(code) = key.as_float() {
+        return Ok(code as i32);
     }
+    let name = key
+        .as_string()
+        .map(|s| s.to_string())
+        .transpose()?
+        .ok_or_else(|| Exception::throw_type(ctx, "Key must be a name or a key code"))?;
+    key_code_from_name(&name)
+        .ok_or_else(|| Exception::throw_type(ctx, &format!("Unknown key name: {}", name)))
 }
@@ -0,0 +1,171 @@
+// ==========================================================
+// Events 类 (订阅宿主事件，不用再轮询)
+// JS 使用: Events.on("frame", (f) => { if (Colors.findColor("#FF0000")) ... })
+//         Events.on("foreground", (app) => { if (app.package !== PKG) log("游戏被切走了") })
+//         Events.on("notification", (n) => log(n.package + ": " + n.title))
+//         Events.off("frame")   // 不传回调则移除该事件的所有回调
+// - 回调在脚本线程上调用；暂停期间的事件丢弃
+// - frame 只保留最新一帧，回调处理慢时中间的帧会被合并
+// - 脚本结束 (入口函数返回且定时器都结束，或被停止) 后自动取消所有订阅；订阅本身不会让脚本继续运行
+// ==========================================================
+
+use rquickjs::{
+    class::Trace, prelude::Opt, CatchResultExt, Class, Ctx, Exception, Function, JsLifetime,
+    Object, Result,
+};
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+use crate::events::{self, FrameInfo, HostEvent};
+use crate::js_engine::ScriptControl;
+
+#[derive(Trace, JsLifetime, Default)]
+#[rquickjs::class]
+pub struct Events<'js> {
+    frame: Vec<Function<'js>>,
+    foreground: Vec<Function<'js>>,
+    notification: Vec<Function<'js>>,
+}
+
+impl<'js> Events<'js> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn listeners(&mut self, ctx: &Ctx<'js>, event: &str) -> Result<&mut Vec<Function<'js>>> {
+        match event {
+            "frame" => Ok(&mut self.frame),
+            "foreground" => Ok(&mut self.foreground),
+            "notification" => Ok(&mut self.notification),
+            _ => Err(Exception::throw_type(
+                ctx,
+                &format!(
+                    "Unknown event `{}` (frame / foreground / notification)",
+                    event
+                ),
+            )),
+        }
+    }
+}
+
+#[rquickjs::methods]
+impl<'js> Events<'js> {
+    pub fn on(&mut self, ctx: Ctx<'js>, event: String, callback: Function<'js>) -> Result<()> {
+        self.listeners(&ctx, &event)?.push(callback);
+        Ok(())
+    }
+
+    /// 移除回调 (不传则移除该事件的所有回调)，返回是否移除了
+    pub fn off(
+        &mut self,
+        ctx: Ctx<'js>,
+        event: String,
+        callback: Opt<Function<'js>>,
+    ) -> Result<bool> {
+        let listeners = self.listeners(&ctx, &event)?;
+        let before = listeners.len();
+        match callback.0 {
+            Some(callback) => listeners.retain(|l| *l != callback),
+            None => listeners.clear(),
+        }
+        Ok(listeners.len() != before)
+    }
+}
+
+/// 启动事件分发: 把新画面 / 宿主事件转给 JS 回调
+/// finished 变为 true 后退出 (否则 rt.idle() 永远等不到结束)
+pub fn spawn_event_pump(ctx: &Ctx<'_>, mut finished: watch::Receiver<bool>) {
+    let mut frames = events::subscribe_frames();
+    let mut host_events = events::subscribe();
+    let control = ctx.userdata::<ScriptControl>().map(|c| c.clone());
+    let pump_ctx = ctx.clone();
+    ctx.spawn(async move {
+        loop {
+            let event = tokio::select! {
+                _ = finished.changed() => break,
+                changed = frames.changed() => match changed {
+                    Ok(()) => Event::Frame(*frames.borrow_and_update()),
+                    Err(_) => break,
+                },
+                event = host_events.recv() => match event {
+                    Ok(event) => Event::Host(event),
+                    Err(RecvError::Lagged(n)) => {
+                        log::warn!("Events: dropped {} host events", n);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            if let Some(control) = &control {
+                if control.should_stop() {
+                    break;
+                }
+                if control.is_paused() {
+                    continue;
+                }
+            }
+            dispatch(&pump_ctx, event);
+        }
+        // 脚本已结束，释放回调
+        if let Ok(events) = pump_ctx.globals().get::<_, Class<Events>>("Events") {
+            let mut events = events.borrow_mut();
+            events.frame.clear();
+            events.foreground.clear();
+            events.notification.clear();
+        }
+    });
+}
+
+enum Event {
+    Frame(FrameInfo),
+    Host(HostEvent),
+}
+
+fn dispatch(ctx: &Ctx<'_>, event: Event) {
+    let Ok(events) = ctx.globals().get::<_, Class<Events>>("Events") else {
+        return;
+    };
+    // 先拷贝一份，回调里再 on / off 也不会冲突
+    let listeners = {
+        let events = events.borrow();
+        match &event {
+            Event::Frame(_) => events.frame.clone(),
+            Event::Host(HostEvent::Foreground(_)) => events.foreground.clone(),
+            Event::Host(HostEvent::Notification { .. }) => events.notification.clone(),
+        }
+    };
+    if listeners.is_empty() {
+        return;
+    }
+
+    let call = || -> Result<()> {
+        let payload = Object::new(ctx.clone())?;
+        match event {
+            Event::Frame(frame) => {
+                payload.set("seq", frame.seq)?;
+                payload.set("width", frame.width)?;
+                payload.set("height", frame.height)?;
+                payload.set("scale", frame.scale)?;
+            }
+            Event::Host(HostEvent::Foreground(app)) => {
+                payload.set("package", app.package)?;
+                payload.set("activity", app.activity)?;
+            }
+            Event::Host(HostEvent::Notification {
+                package,
+                title,
+                text,
+            }) => {
+                payload.set("package", package)?;
+                payload.set("title", title)?;
+                payload.set("text", text)?;
+            }
+        }
+        for listener in &listeners {
+            listener.call::<_, ()>((payload.clone(),))?;
+        }
+        Ok(())
+    };
+    if let Err(e) = call().catch(ctx) {
+        log::error!("Event listener error: {}", e);
+    }
+}
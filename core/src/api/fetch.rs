@@ -0,0 +1,206 @@
+// ==========================================================
+// 🌐 fetch (精简版，只支持 http://，见 crate::net)
+// JS 使用: const res = await fetch("http://192.168.1.10:8080/progress", {
+//              method: "POST",
+//              headers: { "Content-Type": "application/json" },
+//              body: JSON.stringify({ done: 3 }),
+//          });
+//          if (res.ok) log(await res.text());
+// 主机必须在清单 network.allowedHosts 里；脚本被停止时进行中的请求立即中断
+// ==========================================================
+
+use std::time::Duration;
+
+use rquickjs::{
+    class::Trace,
+    prelude::{Async, Coerced, Func, Opt},
+    ArrayBuffer, Class, Ctx, Error, Exception, JsLifetime, Object, Promise, Result, Value,
+};
+
+use crate::{
+    api::{checkpoint, files::bytes_from_js},
+    js_engine::ScriptControl,
+    manifest::NetworkConfig,
+    net::{self, HttpRequest, HttpResponse},
+};
+
+/// 等待请求时检查脚本停止的间隔
+const STOP_POLL: Duration = Duration::from_millis(10);
+
+/// 当前脚本的网络声明 (存在 ctx userdata 里)
+#[derive(JsLifetime)]
+pub struct NetworkPolicy(pub NetworkConfig);
+
+/// config: 清单里的 network (没有清单的旧版脚本为空，不能访问任何主机)
+pub fn register<'js>(globals: &Object<'js>, ctx: &Ctx<'js>, config: NetworkConfig) -> Result<()> {
+    let _ = ctx.store_userdata(NetworkPolicy(config));
+    Class::<FetchResponse>::define(globals)?;
+    globals.set("fetch", Func::from(Async(fetch)))?;
+    Ok(())
+}
+
+async fn fetch<'js>(
+    ctx: Ctx<'js>,
+    url: String,
+    options: Opt<Object<'js>>,
+) -> Result<Class<'js, FetchResponse>> {
+    checkpoint(&ctx)?;
+    let (request, timeout_ms) = build_request(&ctx, url, options.0)?;
+    let config = ctx
+        .userdata::<NetworkPolicy>()
+        .map(|p| p.0.clone())
+        .unwrap_or_default();
+    let control = ctx.userdata::<ScriptControl>().map(|c| c.clone());
+    let result = tokio::select! {
+        result = net::send(&request, &config, timeout_ms) => result,
+        _ = wait_for_stop(control) => {
+            return Err(Exception::throw_internal(&ctx, "Script aborted"));
+        }
+    };
+    let response = result.map_err(|e| Exception::throw_message(&ctx, &e.to_string()))?;
+    Class::instance(ctx, FetchResponse::new(request.url, response))
+}
+
+/// 脚本被停止时返回 (没有控制器则永不返回)
+async fn wait_for_stop(control: Option<ScriptControl>) {
+    let Some(control) = control else {
+        return std::future::pending().await;
+    };
+    while !control.should_stop() {
+        tokio::time::sleep(STOP_POLL).await;
+    }
+}
+
+/// options: { method, headers, body, timeoutMs }
+fn build_request<'js>(
+    ctx: &Ctx<'js>,
+    url: String,
+    options: Option<Object<'js>>,
+) -> Result<(HttpRequest, Option<u64>)> {
+    let mut request = HttpRequest {
+        method: "GET".to_string(),
+        url,
+        headers: Vec::new(),
+        body: Vec::new(),
+    };
+    let Some(options) = options else {
+        return Ok((request, None));
+    };
+    if let Some(method) = options.get::<_, Option<String>>("method")? {
+        request.method = method.to_ascii_uppercase();
+    }
+    if let Some(headers) = options.get::<_, Option<Object<'js>>>("headers")? {
+        for prop in headers.props::<String, Coerced<String>>() {
+            let (name, value) = prop?;
+            request.headers.push((name, value.0));
+        }
+    }
+    let body: Value<'js> = options.get("body")?;
+    if let Some(text) = body.as_string() {
+        request.body = text.to_string()?.into_bytes();
+    } else if !body.is_undefined() && !body.is_null() {
+        request.body = bytes_from_js(ctx, &body)?;
+    }
+    let timeout_ms = options
+        .get::<_, Option<f64>>("timeoutMs")?
+        .filter(|t| t.is_finite() && *t > 0.0)
+        .map(|t| t as u64);
+    Ok((request, timeout_ms))
+}
+
+/// 立即完成的 Promise (和标准 fetch 一样，text() / json() 返回 Promise)
+fn settled<'js>(ctx: &Ctx<'js>, value: Result<Value<'js>>) -> Result<Promise<'js>> {
+    let (promise, resolve, reject) = ctx.promise()?;
+    match value {
+        Ok(value) => resolve.call::<_, ()>((value,))?,
+        Err(Error::Exception) => reject.call::<_, ()>((ctx.catch(),))?,
+        Err(e) => return Err(e),
+    }
+    Ok(promise)
+}
+
+/// fetch 的结果 (响应体已完整读入)
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct FetchResponse {
+    #[qjs(skip_trace)]
+    url: String,
+    #[qjs(skip_trace)]
+    response: HttpResponse,
+}
+
+impl FetchResponse {
+    fn new(url: String, response: HttpResponse) -> Self {
+        Self { url, response }
+    }
+}
+
+#[rquickjs::methods]
+impl FetchResponse {
+    #[qjs(get)]
+    pub fn status(&self) -> u16 {
+        self.response.status
+    }
+
+    #[qjs(get, rename = "statusText")]
+    pub fn status_text(&self) -> String {
+        self.response.status_text.clone()
+    }
+
+    /// 状态码 200-299
+    #[qjs(get)]
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.response.status)
+    }
+
+    #[qjs(get)]
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// 响应头 (名字小写，同名的用 ", " 合并)
+    #[qjs(get)]
+    pub fn headers<'js>(&self, ctx: Ctx<'js>) -> Result<Object<'js>> {
+        let headers = Object::new(ctx)?;
+        for (name, value) in &self.response.headers {
+            let value = match headers.get::<_, Option<String>>(name.as_str())? {
+                Some(prev) => format!("{}, {}", prev, value),
+                None => value.clone(),
+            };
+            headers.set(name.as_str(), value)?;
+        }
+        Ok(headers)
+    }
+
+    /// 读取单个响应头 (不区分大小写)
+    pub fn header(&self, name: String) -> Option<String> {
+        let name = name.to_ascii_lowercase();
+        let values: Vec<&str> = self
+            .response
+            .headers
+            .iter()
+            .filter(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+            .collect();
+        (!values.is_empty()).then(|| values.join(", "))
+    }
+
+    pub fn text<'js>(&self, ctx: Ctx<'js>) -> Result<Promise<'js>> {
+        let text = String::from_utf8_lossy(&self.response.body).into_owned();
+        let value = rquickjs::String::from_str(ctx.clone(), &text).map(|s| s.into_value());
+        settled(&ctx, value)
+    }
+
+    /// 不是合法 JSON 时 Promise 被拒绝
+    pub fn json<'js>(&self, ctx: Ctx<'js>) -> Result<Promise<'js>> {
+        let value = ctx.json_parse(self.response.body.clone());
+        settled(&ctx, value)
+    }
+
+    #[qjs(rename = "arrayBuffer")]
+    pub fn array_buffer<'js>(&self, ctx: Ctx<'js>) -> Result<Promise<'js>> {
+        let value =
+            ArrayBuffer::new(ctx.clone(), self.response.body.clone()).map(|b| b.into_value());
+        settled(&ctx, value)
+    }
+}
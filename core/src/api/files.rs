@@ -0,0 +1,125 @@
+// ==========================================================
+// Files 类 (脚本的文件沙箱，见 crate::sandbox)
+// JS 使用: Files.writeText("logs/today.txt", "done")
+//         Files.readText("bundle://assets/words.txt")
+// 不带前缀 / data:// 是脚本自己的数据目录，bundle:// 是脚本包 (只读)
+// ==========================================================
+
+use std::sync::Arc;
+
+use rquickjs::{
+    class::Trace, prelude::Opt, ArrayBuffer, Ctx, Exception, JsLifetime, Object, TypedArray, Value,
+};
+
+use crate::sandbox::{FileError, Sandbox};
+
+/// 当前脚本的沙箱 (存在 ctx userdata 里，视觉 API 读模板图 / 字库时也用它)
+#[derive(JsLifetime)]
+pub struct ScriptFiles(pub Arc<Sandbox>);
+
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct Files {
+    #[qjs(skip_trace)]
+    sandbox: Arc<Sandbox>,
+}
+
+impl Files {
+    pub fn new(sandbox: Arc<Sandbox>) -> Self {
+        Self { sandbox }
+    }
+}
+
+fn throw_file_error<T>(ctx: &Ctx<'_>, result: Result<T, FileError>) -> rquickjs::Result<T> {
+    result.map_err(|e| Exception::throw_message(ctx, &e.to_string()))
+}
+
+/// 按沙箱规则读取文件 (供其它 API 使用，如 Image.load 的模板路径)
+pub fn read_file(ctx: &Ctx<'_>, path: &str) -> rquickjs::Result<Vec<u8>> {
+    let Some(files) = ctx.userdata::<ScriptFiles>() else {
+        return Err(Exception::throw_internal(
+            ctx,
+            "Files sandbox is not available",
+        ));
+    };
+    throw_file_error(ctx, files.0.read(path))
+}
+
+/// 按沙箱规则写入文件 (供其它 API 使用，如 Screen.save)
+pub fn write_file(ctx: &Ctx<'_>, path: &str, data: &[u8]) -> rquickjs::Result<()> {
+    let Some(files) = ctx.userdata::<ScriptFiles>() else {
+        return Err(Exception::throw_internal(
+            ctx,
+            "Files sandbox is not available",
+        ));
+    };
+    throw_file_error(ctx, files.0.write(path, data, false))
+}
+
+/// 接受 ArrayBuffer 或 Uint8Array
+pub(crate) fn bytes_from_js<'js>(ctx: &Ctx<'js>, data: &Value<'js>) -> rquickjs::Result<Vec<u8>> {
+    let bytes = data.as_object().and_then(|obj: &Object<'js>| {
+        if let Some(buffer) = ArrayBuffer::from_object(obj.clone()) {
+            return buffer.as_bytes().map(|b| b.to_vec());
+        }
+        TypedArray::<u8>::from_object(obj.clone())
+            .ok()
+            .and_then(|array| array.as_bytes().map(|b| b.to_vec()))
+    });
+    bytes.ok_or_else(|| Exception::throw_type(ctx, "Expected an ArrayBuffer or Uint8Array"))
+}
+
+#[rquickjs::methods]
+impl Files {
+    #[qjs(rename = "readText")]
+    pub fn read_text(&self, ctx: Ctx<'_>, path: String) -> rquickjs::Result<String> {
+        throw_file_error(&ctx, self.sandbox.read_text(&path))
+    }
+
+    #[qjs(rename = "readBytes")]
+    pub fn read_bytes<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        path: String,
+    ) -> rquickjs::Result<ArrayBuffer<'js>> {
+        let data = throw_file_error(&ctx, self.sandbox.read(&path))?;
+        ArrayBuffer::new(ctx, data)
+    }
+
+    /// 覆盖写入文本，自动创建父目录
+    #[qjs(rename = "writeText")]
+    pub fn write_text(&self, ctx: Ctx<'_>, path: String, text: String) -> rquickjs::Result<()> {
+        throw_file_error(&ctx, self.sandbox.write(&path, text.as_bytes(), false))
+    }
+
+    #[qjs(rename = "writeBytes")]
+    pub fn write_bytes<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        path: String,
+        data: Value<'js>,
+    ) -> rquickjs::Result<()> {
+        let bytes = bytes_from_js(&ctx, &data)?;
+        throw_file_error(&ctx, self.sandbox.write(&path, &bytes, false))
+    }
+
+    /// 追加文本 (文件不存在时创建)
+    #[qjs(rename = "appendText")]
+    pub fn append_text(&self, ctx: Ctx<'_>, path: String, text: String) -> rquickjs::Result<()> {
+        throw_file_error(&ctx, self.sandbox.write(&path, text.as_bytes(), true))
+    }
+
+    pub fn exists(&self, path: String) -> bool {
+        self.sandbox.exists(&path)
+    }
+
+    /// 列出目录 (默认数据目录根)，子目录以 "/" 结尾
+    pub fn list(&self, ctx: Ctx<'_>, dir: Opt<String>) -> rquickjs::Result<Vec<String>> {
+        throw_file_error(&ctx, self.sandbox.list(dir.0.as_deref().unwrap_or("")))
+    }
+
+    /// 删除文件或目录，返回是否存在
+    pub fn remove(&self, ctx: Ctx<'_>, path: String) -> rquickjs::Result<bool> {
+        throw_file_error(&ctx, self.sandbox.remove(&path))
+    }
+}
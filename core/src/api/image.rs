@@ -1,11 +1,19 @@
 use std::io::Cursor;
 
-use image::ImageFormat;
-
-use crate::vision::types::{
-    BlackWhiteFilterType, ColorFilterType, ColorRule, ImageFilter, Rect, VisionError,
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use rquickjs::{
+    class::Trace,
+    prelude::{Coerced, Opt, Rest},
+    ArrayBuffer, Class, Ctx, Exception, FromJs, JsLifetime, Object, Result as JsResult, Value,
 };
-use crate::vision::{analysis, filters};
+
+use crate::api::{checkpoint, files};
+use crate::core::{self, capture_frame};
+use crate::vision::encode::{self, SaveFormat};
+use crate::vision::filters::NamedFilter;
+use crate::vision::ocr::{self, Font};
+use crate::vision::types::{ColorRule, ImageFilter, Rect, VisionError};
+use crate::vision::{analysis, filters, search};
 
 #[uniffi::export]
 pub fn apply_filter(
@@ -24,29 +32,8 @@ pub fn apply_filter(
     let img =
         image::load_from_memory(&image_data).map_err(|e| VisionError::LoadError(e.to_string()))?;
 
-    let processed_img = match filter {
-        ImageFilter::Color(cf) => match cf {
-            ColorFilterType::Binarization => {
-                let min = param1.unwrap_or(0) as u8;
-                let max = param2.unwrap_or(255) as u8;
-                filters::binarize_rgb_avg(&img, min, max)
-            }
-            ColorFilterType::Grayscale => filters::grayscale(&img),
-            // ... 其他彩色滤镜
-            _ => img, // 暂未实现
-        },
-        ImageFilter::BlackWhite(bw) => match bw {
-            BlackWhiteFilterType::Denoise => filters::denoise(&img, 1),
-            BlackWhiteFilterType::Invert => filters::invert(&img),
-            // ... 其他黑白滤镜
-            _ => img,
-        },
-        ImageFilter::Common(cf) => match cf {
-            // ... 通用滤镜
-            _ => img,
-        },
-        ImageFilter::View => img, // 浏览模式，不做处理
-    };
+    // 2. 处理 (和脚本的 Image.applyFilter 共用)
+    let processed_img = filters::apply(img, filter, param1, param2)?;
 
     // 3. 编码回字节数组
     let mut result_data = Vec::new();
@@ -71,3 +58,518 @@ pub fn scan_components(
 
     Ok(rects)
 }
+
+// ==========================================================
+// Image 类 (脚本持有的图片: 截图 / 文件 / 处理结果)
+// JS 使用: const img = Screen.capture([0, 0, 200, 100]);
+//         const bw = img.pipeline([["keepColor", "#FFFFFF", "#303030"], "denoise"]);
+//         const r = bw.ocr("fonts/digits.json");
+//         const btn = img.findImage(Image.load("bundle://tpl/ok.png"));
+//         img.save("shots/button.png");
+//         img.release();
+// 和 FreeTools 的 apply_filter 共用同一套滤镜，离线调好的参数脚本里直接用。
+// 结果坐标都是图片坐标；scale = 真实屏幕 / 图片，crop 不变，resize 按比例换算
+// 处理类方法都返回新图片，原图不变
+// ==========================================================
+
+/// 解析区域参数 [x, y, w, h]，undefined / null 表示整个画面
+pub(crate) fn parse_region<'js>(
+    ctx: &Ctx<'js>,
+    value: Option<Value<'js>>,
+) -> JsResult<Option<Rect>> {
+    let Some(value) = value.filter(|v| !v.is_undefined() && !v.is_null()) else {
+        return Ok(None);
+    };
+    let region: Vec<f64> = Vec::from_js(ctx, value)?;
+    let [x, y, w, h] = region[..] else {
+        return Err(Exception::throw_type(
+            ctx,
+            "Region must be [x, y, width, height]",
+        ));
+    };
+    if !(w > 0.0 && h > 0.0) {
+        return Err(Exception::throw_range(
+            ctx,
+            "Region width and height must be positive",
+        ));
+    }
+    Ok(Some(Rect {
+        left: x as i32,
+        top: y as i32,
+        width: w as u32,
+        height: h as u32,
+    }))
+}
+
+/// 保存格式: 显式指定 > 文件扩展名 > 没有扩展名时 PNG
+fn save_format(ctx: &Ctx<'_>, path: &str, format: Option<String>) -> JsResult<SaveFormat> {
+    match format {
+        Some(name) => SaveFormat::parse(&name).ok_or_else(|| {
+            Exception::throw_type(
+                ctx,
+                &format!("Unsupported image format `{}` (png / jpeg)", name),
+            )
+        }),
+        None => {
+            let file = path.rsplit('/').next().unwrap_or(path);
+            match file.rsplit_once('.') {
+                Some((_, ext)) => SaveFormat::parse(ext).ok_or_else(|| {
+                    Exception::throw_type(
+                        ctx,
+                        &format!("Unsupported image extension `.{}` (png / jpg)", ext),
+                    )
+                }),
+                None => Ok(SaveFormat::Png),
+            }
+        }
+    }
+}
+
+/// 编码并写入脚本的文件沙箱 (路径规则同 Files)
+pub(crate) fn save_image(
+    ctx: &Ctx<'_>,
+    img: &DynamicImage,
+    scale: f32,
+    path: &str,
+    format: Option<String>,
+) -> JsResult<()> {
+    let format = save_format(ctx, path, format)?;
+    let data = encode::encode(img, format, scale).map_err(|e| throw_vision(ctx, e))?;
+    files::write_file(ctx, path, &data)
+}
+
+fn throw_vision(ctx: &Ctx<'_>, e: VisionError) -> rquickjs::Error {
+    match e {
+        VisionError::LoadError(_) | VisionError::InvalidRegion(_) => {
+            Exception::throw_type(ctx, &e.to_string())
+        }
+        _ => Exception::throw_internal(ctx, &e.to_string()),
+    }
+}
+
+/// 从文件沙箱读图片 (路径规则同 Files)，缩放比例取文件元数据，没有则为 1
+fn load_image(ctx: &Ctx<'_>, path: &str) -> JsResult<(DynamicImage, f32)> {
+    let data = files::read_file(ctx, path)?;
+    // 先只读文件头里的尺寸，超限的图片不解码
+    if let Ok((width, height)) = image::ImageReader::new(Cursor::new(&data))
+        .with_guessed_format()
+        .map_err(image::ImageError::IoError)
+        .and_then(|reader| reader.into_dimensions())
+    {
+        check_pixels(ctx, f64::from(width), f64::from(height))?;
+    }
+    let img = image::load_from_memory(&data)
+        .map_err(|e| throw_vision(ctx, VisionError::LoadError(format!("{}: {}", path, e))))?;
+    Ok((img, encode::read_scale(&data).unwrap_or(1.0)))
+}
+
+/// 图片尺寸超过 MAX_IMAGE_PIXELS 时抛 RangeError
+fn check_pixels(ctx: &Ctx<'_>, width: f64, height: f64) -> JsResult<()> {
+    if width * height > MAX_IMAGE_PIXELS as f64 {
+        return Err(Exception::throw_range(
+            ctx,
+            &format!(
+                "{}x{} exceeds the {} pixel image limit",
+                width, height, MAX_IMAGE_PIXELS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// 滤镜步骤: "invert" 或 ["binarize", 100, 255]
+fn parse_filter_step<'js>(ctx: &Ctx<'js>, step: Value<'js>) -> JsResult<NamedFilter> {
+    let (name, params) = if let Some(name) = step.as_string() {
+        (name.to_string()?, Vec::new())
+    } else if step.is_array() {
+        let mut parts: Vec<Coerced<String>> = Vec::from_js(ctx, step)?.into_iter().collect();
+        if parts.is_empty() {
+            return Err(Exception::throw_type(ctx, "Empty filter step"));
+        }
+        let name = parts.remove(0).0;
+        (name, parts.into_iter().map(|p| p.0).collect())
+    } else {
+        return Err(Exception::throw_type(
+            ctx,
+            "Filter step must be a name or [name, ...params]",
+        ));
+    };
+    NamedFilter::parse(&name, &params).map_err(|e| Exception::throw_type(ctx, &e.to_string()))
+}
+
+/// 字库: 文件路径 (规则同 Files) 或字库对象本身
+fn parse_font<'js>(ctx: &Ctx<'js>, font: Value<'js>) -> JsResult<Font> {
+    let json = match font.as_string() {
+        Some(path) => files::read_file(ctx, &path.to_string()?)?,
+        None => ctx
+            .json_stringify(font)?
+            .ok_or_else(|| Exception::throw_type(ctx, "Font must be a path or an object"))?
+            .to_string()?
+            .into_bytes(),
+    };
+    Font::parse(&json).map_err(|e| throw_vision(ctx, e))
+}
+
+fn match_object<'js>(
+    ctx: &Ctx<'js>,
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+    score: f32,
+) -> JsResult<Object<'js>> {
+    let obj = Object::new(ctx.clone())?;
+    obj.set("x", left)?;
+    obj.set("y", top)?;
+    obj.set("width", width)?;
+    obj.set("height", height)?;
+    obj.set("score", score)?;
+    Ok(obj)
+}
+
+/// 找图默认阈值 (ZNCC 得分)
+const DEFAULT_FIND_THRESHOLD: f64 = 0.9;
+/// OCR 默认阈值 (笔画交并比)
+const DEFAULT_OCR_THRESHOLD: f64 = 0.85;
+/// 找色默认容差 (和 Colors.findColor 一致)
+const DEFAULT_TOLERANCE: f64 = 10.0;
+/// 单张图片的像素上限: 4K 屏幕面积的 4 倍 (RGBA 约 130 MB)
+/// 只有加载和 resize 会产生比原图大的图片，crop / 滤镜的结果不会超过原图
+const MAX_IMAGE_PIXELS: u64 = 3840 * 2160 * 4;
+
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct Image {
+    /// release 之后为 None
+    #[qjs(skip_trace)]
+    pixels: Option<DynamicImage>,
+    #[qjs(skip_trace)]
+    scale: f32,
+}
+
+impl Image {
+    pub fn new(pixels: impl Into<DynamicImage>, scale: f32) -> Self {
+        Self {
+            pixels: Some(pixels.into()),
+            scale,
+        }
+    }
+
+    fn pixels(&self, ctx: &Ctx<'_>) -> JsResult<&DynamicImage> {
+        self.pixels
+            .as_ref()
+            .ok_or_else(|| Exception::throw_reference(ctx, "Image has been released"))
+    }
+
+    /// 处理结果作为新图片返回 (缩放比例不变)
+    fn derive<'js>(&self, ctx: Ctx<'js>, pixels: DynamicImage) -> JsResult<Class<'js, Image>> {
+        Class::instance(ctx, Image::new(pixels, self.scale))
+    }
+}
+
+#[rquickjs::methods]
+impl Image {
+    /// 不支持 new Image()，由 Image.load / Image.fromScreen / Screen.capture 创建
+    #[qjs(constructor)]
+    pub fn ctor(ctx: Ctx<'_>) -> JsResult<Self> {
+        Err(Exception::throw_type(
+            &ctx,
+            "Use Image.load, Image.fromScreen or Screen.capture to create an Image",
+        ))
+    }
+
+    /// 从文件沙箱加载 PNG / JPEG (路径规则同 Files)
+    #[qjs(static)]
+    pub fn load<'js>(ctx: Ctx<'js>, path: String) -> JsResult<Class<'js, Image>> {
+        let (pixels, scale) = load_image(&ctx, &path)?;
+        Class::instance(ctx, Image::new(pixels, scale))
+    }
+
+    /// 截取当前画面，同 Screen.capture
+    #[qjs(static, rename = "fromScreen")]
+    pub fn from_screen<'js>(ctx: Ctx<'js>, region: Opt<Value<'js>>) -> JsResult<Class<'js, Image>> {
+        checkpoint(&ctx)?;
+        let region = parse_region(&ctx, region.0)?;
+        let (pixels, scale) = capture_frame(region).map_err(|e| throw_vision(&ctx, e))?;
+        Class::instance(ctx, Image::new(pixels, scale))
+    }
+
+    #[qjs(get)]
+    pub fn width(&self) -> u32 {
+        self.pixels.as_ref().map_or(0, |p| p.width())
+    }
+
+    #[qjs(get)]
+    pub fn height(&self) -> u32 {
+        self.pixels.as_ref().map_or(0, |p| p.height())
+    }
+
+    /// 真实屏幕坐标 = 图片坐标 * scale
+    #[qjs(get)]
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// 裁出一块区域 (超出图片的部分裁掉)，返回新图片
+    pub fn crop<'js>(&self, ctx: Ctx<'js>, region: Value<'js>) -> JsResult<Class<'js, Image>> {
+        let img = self.pixels(&ctx)?;
+        let rect = parse_region(&ctx, Some(region))?
+            .ok_or_else(|| Exception::throw_type(&ctx, "crop needs a region"))?;
+        let (left, top, right, bottom) = search::clip_rect(Some(rect), img.width(), img.height())
+            .ok_or_else(|| {
+            Exception::throw_range(
+                &ctx,
+                &format!(
+                    "[{}, {}, {}, {}] is outside the {}x{} image",
+                    rect.left,
+                    rect.top,
+                    rect.width,
+                    rect.height,
+                    img.width(),
+                    img.height()
+                ),
+            )
+        })?;
+        let cropped = img.crop_imm(left, top, right - left, bottom - top);
+        self.derive(ctx, cropped)
+    }
+
+    /// 缩放到 width x height，返回新图片；scale 按宽度比例换算，保证仍能换回真实坐标
+    pub fn resize<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        width: f64,
+        height: f64,
+    ) -> JsResult<Class<'js, Image>> {
+        let img = self.pixels(&ctx)?;
+        if !(width >= 1.0 && height >= 1.0) {
+            return Err(Exception::throw_range(
+                &ctx,
+                "Width and height must be at least 1",
+            ));
+        }
+        check_pixels(&ctx, width.trunc(), height.trunc())?;
+        let (width, height) = (width as u32, height as u32);
+        let scale = self.scale * img.width() as f32 / width as f32;
+        let resized = filters::resize(img, width, height);
+        Class::instance(ctx, Image::new(resized, scale))
+    }
+
+    /// 像素颜色 "#RRGGBB"
+    #[qjs(rename = "getPixel")]
+    pub fn get_pixel(&self, ctx: Ctx<'_>, x: f64, y: f64) -> JsResult<String> {
+        let img = self.pixels(&ctx)?;
+        if !(x >= 0.0 && y >= 0.0 && x < img.width() as f64 && y < img.height() as f64) {
+            return Err(Exception::throw_range(
+                &ctx,
+                &format!(
+                    "({}, {}) is outside the {}x{} image",
+                    x,
+                    y,
+                    img.width(),
+                    img.height()
+                ),
+            ));
+        }
+        let [r, g, b, _] = img.get_pixel(x as u32, y as u32).0;
+        Ok(format!("#{:02X}{:02X}{:02X}", r, g, b))
+    }
+
+    /// 按名字套用滤镜，返回新图片: img.applyFilter("binarize", 100, 255)
+    #[qjs(rename = "applyFilter")]
+    pub fn apply_filter<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        name: String,
+        params: Rest<Coerced<String>>,
+    ) -> JsResult<Class<'js, Image>> {
+        let img = self.pixels(&ctx)?;
+        let params: Vec<String> = params.0.into_iter().map(|p| p.0).collect();
+        let filter = NamedFilter::parse(&name, &params)
+            .map_err(|e| Exception::throw_type(&ctx, &e.to_string()))?;
+        let processed = filter
+            .apply(img.clone())
+            .map_err(|e| throw_vision(&ctx, e))?;
+        self.derive(ctx, processed)
+    }
+
+    /// 依次套用多个滤镜: img.pipeline([["keepColor", "#FFFFFF", "#202020"], "denoise"])
+    pub fn pipeline<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        steps: Vec<Value<'js>>,
+    ) -> JsResult<Class<'js, Image>> {
+        let img = self.pixels(&ctx)?;
+        // 先全部解析，写错的步骤不用等前面的滤镜跑完才报错
+        let steps = steps
+            .into_iter()
+            .map(|step| parse_filter_step(&ctx, step))
+            .collect::<JsResult<Vec<_>>>()?;
+        let processed = steps
+            .iter()
+            .try_fold(img.clone(), |img, filter| filter.apply(img))
+            .map_err(|e| throw_vision(&ctx, e))?;
+        self.derive(ctx, processed)
+    }
+
+    /// 在图中找色，返回第一个匹配的 [x, y] (图片坐标)
+    #[qjs(rename = "findColor")]
+    pub fn find_color<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        color: String,
+        tolerance: Opt<f64>,
+        region: Opt<Value<'js>>,
+    ) -> JsResult<Option<Vec<u32>>> {
+        let img = self.pixels(&ctx)?;
+        let target = core::parse_hex_color(&color);
+        let tolerance = tolerance.0.unwrap_or(DEFAULT_TOLERANCE).clamp(0.0, 255.0) as u8;
+        let rect = parse_region(&ctx, region.0)?;
+        Ok(search::find_color(img, target, tolerance, rect).map(|(x, y)| vec![x, y]))
+    }
+
+    /// 在图中找模板 (Image 或图片路径)，返回 { x, y, width, height, score }
+    #[qjs(rename = "findImage")]
+    pub fn find_image<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        template: Value<'js>,
+        threshold: Opt<f64>,
+        region: Opt<Value<'js>>,
+    ) -> JsResult<Option<Object<'js>>> {
+        let img = self.pixels(&ctx)?;
+        let threshold = threshold.0.unwrap_or(DEFAULT_FIND_THRESHOLD) as f32;
+        let rect = parse_region(&ctx, region.0)?;
+        let found = if let Ok(tpl) = Class::<Image>::from_value(&template) {
+            let tpl = tpl.borrow();
+            search::find_image(img, tpl.pixels(&ctx)?, threshold, rect)
+        } else if let Some(path) = template.as_string() {
+            let (tpl, _) = load_image(&ctx, &path.to_string()?)?;
+            search::find_image(img, &tpl, threshold, rect)
+        } else {
+            return Err(Exception::throw_type(
+                &ctx,
+                "Template must be an Image or an image path",
+            ));
+        };
+        found
+            .map(|m| match_object(&ctx, m.left, m.top, m.width, m.height, m.score))
+            .transpose()
+    }
+
+    /// 点阵字库识别，返回 { text, chars: [{ char, x, y, width, height, score }] }
+    /// 图片需先处理成白字黑底；options: { threshold, region }
+    pub fn ocr<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        font: Value<'js>,
+        options: Opt<Object<'js>>,
+    ) -> JsResult<Object<'js>> {
+        let img = self.pixels(&ctx)?;
+        let font = parse_font(&ctx, font)?;
+        let (threshold, region) = match options.0 {
+            Some(opts) => (
+                opts.get::<_, Option<f64>>("threshold")?,
+                opts.get::<_, Option<Value>>("region")?,
+            ),
+            None => (None, None),
+        };
+        let threshold = threshold.unwrap_or(DEFAULT_OCR_THRESHOLD) as f32;
+        let (left, top) = match parse_region(&ctx, region)? {
+            Some(rect) => {
+                let (left, top, right, bottom) =
+                    search::clip_rect(Some(rect), img.width(), img.height()).ok_or_else(|| {
+                        Exception::throw_range(&ctx, "Region is outside the image")
+                    })?;
+                let area = img.crop_imm(left, top, right - left, bottom - top);
+                let lines = ocr::recognize(&area, &font, threshold);
+                return ocr_result(&ctx, lines, left, top);
+            }
+            None => (0, 0),
+        };
+        ocr_result(&ctx, ocr::recognize(img, &font, threshold), left, top)
+    }
+
+    /// 编码为 PNG / JPEG 字节 (默认 PNG)
+    pub fn encode<'js>(&self, ctx: Ctx<'js>, format: Opt<String>) -> JsResult<ArrayBuffer<'js>> {
+        let img = self.pixels(&ctx)?;
+        let format = save_format(&ctx, "", format.0)?;
+        let data = encode::encode(img, format, self.scale).map_err(|e| throw_vision(&ctx, e))?;
+        ArrayBuffer::new(ctx, data)
+    }
+
+    /// format: "png" / "jpeg"，不传按扩展名判断
+    pub fn save(&self, ctx: Ctx<'_>, path: String, format: Opt<String>) -> JsResult<()> {
+        save_image(&ctx, self.pixels(&ctx)?, self.scale, &path, format.0)
+    }
+
+    /// 立即释放像素内存 (不等 GC)，之后再使用会抛错
+    pub fn release(&mut self) {
+        self.pixels = None;
+    }
+}
+
+/// 识别结果转 JS 对象，坐标加上区域偏移；每行拼成一行文本
+fn ocr_result<'js>(
+    ctx: &Ctx<'js>,
+    lines: Vec<Vec<ocr::OcrMatch>>,
+    left: u32,
+    top: u32,
+) -> JsResult<Object<'js>> {
+    let text = lines
+        .iter()
+        .map(|line| line.iter().map(|m| m.text.as_str()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let chars = lines
+        .into_iter()
+        .flatten()
+        .map(|m| {
+            let obj = match_object(ctx, left + m.left, top + m.top, m.width, m.height, m.score)?;
+            obj.set("char", m.text)?;
+            Ok(obj)
+        })
+        .collect::<JsResult<Vec<_>>>()?;
+    let result = Object::new(ctx.clone())?;
+    result.set("text", text)?;
+    result.set("chars", chars)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use rquickjs::{Context, Runtime};
+
+    use super::*;
+
+    #[test]
+    fn resize_rejects_huge_sizes() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            Class::<Image>::define(&ctx.globals()).unwrap();
+            let img = Class::instance(ctx.clone(), Image::new(image::RgbaImage::new(2, 2), 1.0));
+            ctx.globals().set("img", img).unwrap();
+
+            let resized: Object = ctx.eval("img.resize(4, 3)").unwrap();
+            assert_eq!(resized.get::<_, u32>("width").unwrap(), 4);
+            assert_eq!(resized.get::<_, u32>("height").unwrap(), 3);
+
+            for size in ["1e6, 1e6", "Infinity, 1", "65536, 65536"] {
+                let result: rquickjs::Result<Value> = ctx.eval(format!("img.resize({})", size));
+                assert!(result.is_err(), "{}", size);
+                let message = ctx
+                    .catch()
+                    .into_object()
+                    .and_then(|e| e.get::<_, String>("message").ok())
+                    .unwrap_or_default();
+                assert!(
+                    message.contains("pixel image limit"),
+                    "{}: {}",
+                    size,
+                    message
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,58 @@
+// ==========================================================
+// Screen 类 (读取当前画面)
+// JS 使用: Screen.save("fail/" + Date.now() + ".jpg")       // 出错时留证据
+//         const btn = Screen.capture([100, 200, 80, 40]);  // 截取模板
+// 区域为 [x, y, 宽, 高]，脚本逻辑坐标 (即截图缓冲区坐标)，不传为整个画面
+// ==========================================================
+
+use rquickjs::{class::Trace, prelude::Opt, Class, Ctx, Exception, JsLifetime, Value};
+
+use crate::{
+    api::{
+        checkpoint,
+        image::{parse_region, save_image, Image},
+    },
+    core::capture_frame,
+};
+
+#[derive(Default, Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct Screen {}
+
+impl Screen {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[rquickjs::methods]
+impl Screen {
+    /// 截取当前帧，返回 Image (用完可 release)
+    pub fn capture<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        region: Opt<Value<'js>>,
+    ) -> rquickjs::Result<Class<'js, Image>> {
+        checkpoint(&ctx)?;
+        let region = parse_region(&ctx, region.0)?;
+        let (pixels, scale) =
+            capture_frame(region).map_err(|e| Exception::throw_internal(&ctx, &e.to_string()))?;
+        Class::instance(ctx, Image::new(pixels, scale))
+    }
+
+    /// 截图并保存到脚本的文件沙箱 (路径规则同 Files)
+    /// format: "png" / "jpeg"，不传按扩展名判断
+    pub fn save<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        path: String,
+        region: Opt<Value<'js>>,
+        format: Opt<String>,
+    ) -> rquickjs::Result<()> {
+        checkpoint(&ctx)?;
+        let region = parse_region(&ctx, region.0)?;
+        let (pixels, scale) =
+            capture_frame(region).map_err(|e| Exception::throw_internal(&ctx, &e.to_string()))?;
+        save_image(&ctx, &pixels.into(), scale, &path, format.0)
+    }
+}
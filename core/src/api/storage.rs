@@ -0,0 +1,90 @@
+// ==========================================================
+// Storage 类 (跨运行保存的键值数据，按脚本的命名空间隔离)
+// JS 使用: Storage.set("dailyDone", true, 3600 * 1000)
+//         if (!Storage.get("dailyDone", false)) { ... }
+// 值可以是任意能 JSON 序列化的数据
+// ==========================================================
+
+use rquickjs::{class::Trace, prelude::Opt, Ctx, Exception, JsLifetime, Value};
+
+use crate::storage::{self, StorageError};
+
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct Storage {
+    #[qjs(skip_trace)]
+    namespace: String,
+}
+
+impl Storage {
+    pub fn new(namespace: String) -> Self {
+        Self { namespace }
+    }
+}
+
+fn throw_storage_error<T>(ctx: &Ctx<'_>, result: Result<T, StorageError>) -> rquickjs::Result<T> {
+    result.map_err(|e| Exception::throw_internal(ctx, &e.to_string()))
+}
+
+#[rquickjs::methods]
+impl Storage {
+    /// 不存在 (或已过期) 时返回 default (默认 undefined)
+    pub fn get<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        default: Opt<Value<'js>>,
+    ) -> rquickjs::Result<Value<'js>> {
+        match (storage::get(&self.namespace, &key), default.0) {
+            (Some(v), _) => ctx.json_parse(v.to_string()),
+            (None, Some(d)) => Ok(d),
+            (None, None) => Ok(Value::new_undefined(ctx)),
+        }
+    }
+
+    /// ttlMs: 多少毫秒后过期，不传则一直保存
+    pub fn set<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        key: String,
+        value: Value<'js>,
+        ttl_ms: Opt<f64>,
+    ) -> rquickjs::Result<()> {
+        let json = ctx
+            .json_stringify(value)?
+            .map(|s| s.to_string())
+            .transpose()?
+            .ok_or_else(|| {
+                Exception::throw_type(
+                    &ctx,
+                    &format!("Storage `{}`: value is not JSON serializable", key),
+                )
+            })?;
+        let value =
+            serde_json::from_str(&json).map_err(|e| Exception::throw_type(&ctx, &e.to_string()))?;
+        let ttl = ttl_ms.0.map(|ms| ms.max(0.0) as u64);
+        throw_storage_error(&ctx, storage::set(&self.namespace, &key, value, ttl))
+    }
+
+    /// 返回是否删除了存在的键
+    pub fn remove(&self, ctx: Ctx<'_>, key: String) -> rquickjs::Result<bool> {
+        throw_storage_error(&ctx, storage::remove(&self.namespace, &key))
+    }
+
+    pub fn has(&self, key: String) -> bool {
+        storage::get(&self.namespace, &key).is_some()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        storage::keys(&self.namespace)
+    }
+
+    /// 剩余有效毫秒数，不存在或不过期返回 undefined
+    pub fn ttl(&self, key: String) -> Option<f64> {
+        storage::ttl(&self.namespace, &key).map(|ms| ms as f64)
+    }
+
+    pub fn clear(&self, ctx: Ctx<'_>) -> rquickjs::Result<()> {
+        throw_storage_error(&ctx, storage::clear(&self.namespace))
+    }
+}
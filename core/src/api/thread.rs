@@ -3,11 +3,11 @@
 // JS 使用: Thread.sleep(1000)
 // ==========================================================
 
-use std::{sync::atomic::Ordering, time::Duration};
+use std::time::Duration;
 
-use rquickjs::{class::Trace, JsLifetime};
+use rquickjs::{class::Trace, Ctx, Exception, JsLifetime};
 
-use crate::uniffi_binding::IS_PAUSED;
+use crate::js_engine::ScriptControl;
 
 #[derive(Trace, JsLifetime)]
 #[rquickjs::class]
@@ -28,13 +28,21 @@ impl Thread {
     }
     /// Sleep (异步操作)
     #[qjs(rename = "sleep")]
-    pub async fn sleep(ms: u64) {
+    pub async fn sleep<'js>(ctx: Ctx<'js>, ms: u64) -> rquickjs::Result<()> {
+        let control = ctx.userdata::<ScriptControl>().map(|c| c.clone());
+        let should_stop = || control.as_ref().is_some_and(|c| c.should_stop());
+        let paused = || control.as_ref().is_some_and(|c| c.is_paused());
         // 1. 执行正常的休眠
         tokio::time::sleep(Duration::from_millis(ms)).await;
         // 2. 暂停检查 (原子读取)
         // 🔥 这里直接读取原子变量，性能极高
-        while IS_PAUSED.load(Ordering::Relaxed) {
-            tokio::time::sleep(Duration::from_millis(200)).await;
+        // 异步等待，暂停期间配置变更等后台任务照常运行
+        while paused() && !should_stop() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        if should_stop() {
+            return Err(Exception::throw_internal(&ctx, "Script aborted"));
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,235 @@
+// ==========================================================
+// ⏰ 定时器 (setTimeout / setInterval / clearTimeout / clearInterval)
+// JS 使用: const id = setInterval(() => log("tick"), 1000); clearInterval(id)
+//
+// - 每个定时器是运行时里的一个 tokio 任务，到点后在脚本线程上调用回调
+// - 暂停期间不触发，恢复后补一次 (interval 不会补发错过的多次)
+// - 入口函数返回后脚本继续运行，直到定时器全部结束 (wait_idle)，和浏览器 / Node 一样
+// - 出错 / 被停止 / 超时时清除所有定时器 (clear_all)
+// ==========================================================
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rquickjs::{
+    function::Args,
+    prelude::{Func, Opt, Rest},
+    CatchResultExt, Ctx, Exception, Function, JsLifetime, Object, Result, Value,
+};
+use tokio::sync::Notify;
+
+use crate::js_engine::ScriptControl;
+
+/// 暂停时检查恢复 / 停止的间隔
+const PAUSE_POLL: Duration = Duration::from_millis(10);
+/// setInterval 的最小间隔，避免 0 间隔空转
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+/// 延迟上限 (和浏览器一样是 2^31-1 毫秒，约 24.8 天)
+const MAX_DELAY_MS: f64 = i32::MAX as f64;
+/// 等待定时器结束时检查停止 / 超时的间隔
+const IDLE_POLL: Duration = Duration::from_millis(50);
+
+/// 活动定时器表 (存在 ctx userdata 里)，id -> 取消通知
+#[derive(Default, JsLifetime)]
+pub struct Timers {
+    inner: Mutex<TimersInner>,
+    /// 有定时器被移除时通知 wait_idle
+    changed: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct TimersInner {
+    next_id: u32,
+    active: HashMap<u32, Arc<Notify>>,
+}
+
+impl Timers {
+    fn add(&self) -> (u32, Arc<Notify>) {
+        let mut inner = self.inner.lock().unwrap();
+        // id 从 1 开始 (0 / undefined 传给 clearTimeout 是空操作)
+        inner.next_id = inner.next_id.wrapping_add(1).max(1);
+        let id = inner.next_id;
+        let cancel = Arc::new(Notify::new());
+        inner.active.insert(id, cancel.clone());
+        (id, cancel)
+    }
+
+    fn is_active(&self, id: u32) -> bool {
+        self.inner.lock().unwrap().active.contains_key(&id)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().active.is_empty()
+    }
+
+    fn remove(&self, id: u32) {
+        if let Some(cancel) = self.inner.lock().unwrap().active.remove(&id) {
+            // notify_one 会留下许可，任务还没开始等待也能收到
+            cancel.notify_one();
+            self.changed.notify_waiters();
+        }
+    }
+
+    fn clear(&self) {
+        for (_, cancel) in self.inner.lock().unwrap().active.drain() {
+            cancel.notify_one();
+        }
+        self.changed.notify_waiters();
+    }
+}
+
+pub fn register<'js>(globals: &Object<'js>, ctx: &Ctx<'js>) -> Result<()> {
+    let _ = ctx.store_userdata(Timers::default());
+    globals.set("setTimeout", Func::new(set_timeout))?;
+    globals.set("setInterval", Func::new(set_interval))?;
+    globals.set("clearTimeout", Func::new(clear_timer))?;
+    globals.set("clearInterval", Func::new(clear_timer))?;
+    Ok(())
+}
+
+/// 入口函数返回后等待所有定时器结束 (timeout 触发完、interval 被 clear)
+/// 期间定时器任务照常运行；被停止或超时时抛出 "Script aborted"
+pub async fn wait_idle(ctx: &Ctx<'_>) -> Result<()> {
+    let control = ctx.userdata::<ScriptControl>().map(|c| c.clone());
+    loop {
+        if control.as_ref().is_some_and(|c| c.should_stop()) {
+            return Err(Exception::throw_internal(ctx, "Script aborted"));
+        }
+        let Some(changed) = ctx
+            .userdata::<Timers>()
+            .filter(|t| !t.is_empty())
+            .map(|t| t.changed.clone())
+        else {
+            return Ok(());
+        };
+        // notify_waiters 不留许可，检查和等待之间错过的通知靠轮询兜底
+        tokio::select! {
+            _ = changed.notified() => {}
+            _ = tokio::time::sleep(IDLE_POLL) => {}
+        }
+    }
+}
+
+/// 清除所有定时器 (脚本结束时调用，之后不会再有回调)
+pub fn clear_all(ctx: &Ctx<'_>) {
+    if let Some(timers) = ctx.userdata::<Timers>() {
+        timers.clear();
+    }
+}
+
+fn set_timeout<'js>(
+    ctx: Ctx<'js>,
+    callback: Function<'js>,
+    delay: Opt<f64>,
+    args: Rest<Value<'js>>,
+) -> Result<u32> {
+    Ok(schedule(ctx, callback, delay, args, false))
+}
+
+fn set_interval<'js>(
+    ctx: Ctx<'js>,
+    callback: Function<'js>,
+    delay: Opt<f64>,
+    args: Rest<Value<'js>>,
+) -> Result<u32> {
+    Ok(schedule(ctx, callback, delay, args, true))
+}
+
+/// 未知 id / 已触发的 timeout 忽略
+fn clear_timer(ctx: Ctx<'_>, id: Opt<Value<'_>>) {
+    let Some(id) = id.0.and_then(|v| v.as_number()) else {
+        return;
+    };
+    if let Some(timers) = ctx.userdata::<Timers>() {
+        timers.remove(id as u32);
+    }
+}
+
+/// 和浏览器一样，非法 / 负数延迟按 0 处理，超过 MAX_DELAY_MS 的按上限
+fn delay_duration(delay: Option<f64>) -> Duration {
+    let ms = delay
+        .filter(|d| d.is_finite() && *d > 0.0)
+        .unwrap_or(0.0)
+        .min(MAX_DELAY_MS);
+    Duration::from_secs_f64(ms / 1000.0)
+}
+
+fn schedule<'js>(
+    ctx: Ctx<'js>,
+    callback: Function<'js>,
+    delay: Opt<f64>,
+    args: Rest<Value<'js>>,
+    repeat: bool,
+) -> u32 {
+    let Some((id, cancel)) = ctx.userdata::<Timers>().map(|t| t.add()) else {
+        return 0;
+    };
+    let mut delay = delay_duration(delay.0);
+    if repeat {
+        delay = delay.max(MIN_INTERVAL);
+    }
+    let control = ctx.userdata::<ScriptControl>().map(|c| c.clone());
+    let task_ctx = ctx.clone();
+    ctx.spawn(async move {
+        loop {
+            tokio::select! {
+                _ = cancel.notified() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            if let Some(control) = &control {
+                while control.is_paused() && !control.should_stop() {
+                    tokio::time::sleep(PAUSE_POLL).await;
+                }
+                if control.should_stop() {
+                    return;
+                }
+            }
+            let Some(timers) = task_ctx.userdata::<Timers>() else {
+                return;
+            };
+            // 暂停期间可能被 clear 了
+            if !timers.is_active(id) {
+                return;
+            }
+            if !repeat {
+                timers.remove(id);
+            }
+            drop(timers);
+            let call = || -> Result<()> {
+                let mut call_args = Args::new(task_ctx.clone(), args.len());
+                for arg in args.iter() {
+                    call_args.push_arg(arg.clone())?;
+                }
+                callback.call_arg::<()>(call_args)
+            };
+            if let Err(e) = call().catch(&task_ctx) {
+                log::error!("Timer callback error: {}", e);
+            }
+            if !repeat {
+                return;
+            }
+        }
+    });
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_are_clamped() {
+        assert_eq!(delay_duration(None), Duration::ZERO);
+        assert_eq!(delay_duration(Some(-5.0)), Duration::ZERO);
+        assert_eq!(delay_duration(Some(f64::NAN)), Duration::ZERO);
+        assert_eq!(delay_duration(Some(f64::INFINITY)), Duration::ZERO);
+        assert_eq!(delay_duration(Some(1500.0)), Duration::from_millis(1500));
+        let max = Duration::from_millis(i32::MAX as u64);
+        assert_eq!(delay_duration(Some(1e300)), max);
+        assert_eq!(delay_duration(Some(f64::MAX)), max);
+        assert_eq!(delay_duration(Some(i32::MAX as f64 + 1.0)), max);
+    }
+}
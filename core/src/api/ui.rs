@@ -0,0 +1,288 @@
+// ==========================================================
+// UiSelector / UiObject 类 (按控件查找，适合非游戏应用)
+// JS 使用: new UiSelector().text("同意").click()
+//         const btn = new UiSelector().id("login").className("Button").findOne(3000);
+//         if (btn) log(btn.text + " @ " + btn.bounds);
+// - 条件可以链式组合，全部满足才算匹配
+// - findOne(timeoutMs) 在超时前反复抓取节点树，不传则只找一次
+// - 坐标都是脚本逻辑坐标 (和截图 / Device.click 一致)
+// ==========================================================
+
+use std::time::{Duration, Instant};
+
+use rquickjs::{
+    class::Trace,
+    prelude::{Opt, This},
+    Class, Ctx, JsLifetime, Result,
+};
+
+use crate::{
+    api::{checkpoint, device_action, throw_input_error, with_controller},
+    core::unmap_coordinates,
+    ui::{
+        selector::{Criterion, Selector},
+        UiNode,
+    },
+};
+
+/// findOne 等待期间重新抓取节点树的间隔
+const FIND_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Default, Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct UiSelector {
+    #[qjs(skip_trace)]
+    selector: Selector,
+}
+
+/// 追加条件，返回自身以便链式调用
+fn push<'js>(this: This<Class<'js, UiSelector>>, criterion: Criterion) -> Class<'js, UiSelector> {
+    this.borrow_mut().selector.criteria.push(criterion);
+    this.0
+}
+
+impl UiSelector {
+    /// 抓取节点树并查找，timeout 内找不到则反复重试
+    fn find<T>(
+        &self,
+        ctx: &Ctx<'_>,
+        timeout_ms: Option<u64>,
+        pick: impl Fn(&Selector, &UiNode) -> Option<T>,
+    ) -> Result<Option<T>> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms.unwrap_or(0));
+        loop {
+            checkpoint(ctx)?;
+            let root = throw_input_error(ctx, with_controller(|ctrl| ctrl.ui_snapshot()))?;
+            if let Some(found) = root.as_ref().and_then(|root| pick(&self.selector, root)) {
+                return Ok(Some(found));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep(FIND_POLL);
+        }
+    }
+}
+
+#[rquickjs::methods]
+impl UiSelector {
+    #[qjs(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text<'js>(this: This<Class<'js, Self>>, text: String) -> Class<'js, Self> {
+        push(this, Criterion::Text(text))
+    }
+
+    #[qjs(rename = "textContains")]
+    pub fn text_contains<'js>(this: This<Class<'js, Self>>, text: String) -> Class<'js, Self> {
+        push(this, Criterion::TextContains(text))
+    }
+
+    pub fn desc<'js>(this: This<Class<'js, Self>>, desc: String) -> Class<'js, Self> {
+        push(this, Criterion::Desc(desc))
+    }
+
+    #[qjs(rename = "descContains")]
+    pub fn desc_contains<'js>(this: This<Class<'js, Self>>, desc: String) -> Class<'js, Self> {
+        push(this, Criterion::DescContains(desc))
+    }
+
+    /// 全名 "com.app:id/ok" 或只写 "ok"
+    pub fn id<'js>(this: This<Class<'js, Self>>, id: String) -> Class<'js, Self> {
+        push(this, Criterion::Id(id))
+    }
+
+    /// 全名 "android.widget.Button" 或只写 "Button"
+    #[qjs(rename = "className")]
+    pub fn class_name<'js>(this: This<Class<'js, Self>>, name: String) -> Class<'js, Self> {
+        push(this, Criterion::ClassName(name))
+    }
+
+    #[qjs(rename = "packageName")]
+    pub fn package_name<'js>(this: This<Class<'js, Self>>, name: String) -> Class<'js, Self> {
+        push(this, Criterion::PackageName(name))
+    }
+
+    pub fn clickable<'js>(this: This<Class<'js, Self>>, value: Opt<bool>) -> Class<'js, Self> {
+        push(this, Criterion::Clickable(value.0.unwrap_or(true)))
+    }
+
+    pub fn enabled<'js>(this: This<Class<'js, Self>>, value: Opt<bool>) -> Class<'js, Self> {
+        push(this, Criterion::Enabled(value.0.unwrap_or(true)))
+    }
+
+    pub fn checked<'js>(this: This<Class<'js, Self>>, value: Opt<bool>) -> Class<'js, Self> {
+        push(this, Criterion::Checked(value.0.unwrap_or(true)))
+    }
+
+    pub fn selected<'js>(this: This<Class<'js, Self>>, value: Opt<bool>) -> Class<'js, Self> {
+        push(this, Criterion::Selected(value.0.unwrap_or(true)))
+    }
+
+    pub fn scrollable<'js>(this: This<Class<'js, Self>>, value: Opt<bool>) -> Class<'js, Self> {
+        push(this, Criterion::Scrollable(value.0.unwrap_or(true)))
+    }
+
+    /// 第一个匹配的控件，找不到返回 undefined
+    #[qjs(rename = "findOne")]
+    pub fn find_one<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        timeout_ms: Opt<u64>,
+    ) -> Result<Option<Class<'js, UiObject>>> {
+        let found = self.find(&ctx, timeout_ms.0, |s, root| s.find_one(root).cloned())?;
+        found
+            .map(|node| Class::instance(ctx.clone(), UiObject { node }))
+            .transpose()
+    }
+
+    /// 所有匹配的控件 (只抓取一次)
+    #[qjs(rename = "findAll")]
+    pub fn find_all<'js>(&self, ctx: Ctx<'js>) -> Result<Vec<Class<'js, UiObject>>> {
+        let found = self.find(&ctx, None, |s, root| {
+            Some(s.find_all(root).into_iter().cloned().collect::<Vec<_>>())
+        })?;
+        found
+            .unwrap_or_default()
+            .into_iter()
+            .map(|node| Class::instance(ctx.clone(), UiObject { node }))
+            .collect()
+    }
+
+    pub fn exists(&self, ctx: Ctx<'_>) -> Result<bool> {
+        Ok(self
+            .find(&ctx, None, |s, root| s.find_one(root).map(|_| ()))?
+            .is_some())
+    }
+
+    /// 找到后点击控件区域，返回是否找到
+    pub fn click(&self, ctx: Ctx<'_>, timeout_ms: Opt<u64>) -> Result<bool> {
+        let Some(node) = self.find(&ctx, timeout_ms.0, |s, root| s.find_one(root).cloned())? else {
+            return Ok(false);
+        };
+        click_node(&ctx, &node)?;
+        Ok(true)
+    }
+}
+
+/// 节点树里的一个控件 (抓取时的快照，界面变化后不会更新)
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class]
+pub struct UiObject {
+    #[qjs(skip_trace)]
+    node: UiNode,
+}
+
+/// 真实坐标 -> [x, y, 宽, 高] (逻辑坐标)，unmap 一般是 core::unmap_coordinates
+pub(crate) fn logical_bounds(node: &UiNode, unmap: impl Fn(i32, i32) -> (i32, i32)) -> Vec<i32> {
+    let (left, top) = unmap(node.bounds.left, node.bounds.top);
+    let (right, bottom) = unmap(node.bounds.right, node.bounds.bottom);
+    vec![left, top, right - left, bottom - top]
+}
+
+/// 点击控件区域 (开启拟人化时区域内随机取点)，节点坐标已经是真实坐标
+fn click_node(ctx: &Ctx<'_>, node: &UiNode) -> Result<()> {
+    let b = node.bounds;
+    device_action(ctx, |ctrl| {
+        ctrl.click_in_rect(b.left, b.top, b.width(), b.height())
+    })
+}
+
+#[rquickjs::methods]
+impl UiObject {
+    #[qjs(get)]
+    pub fn text(&self) -> Option<String> {
+        self.node.text.clone()
+    }
+
+    #[qjs(get)]
+    pub fn desc(&self) -> Option<String> {
+        self.node.desc.clone()
+    }
+
+    #[qjs(get)]
+    pub fn id(&self) -> Option<String> {
+        self.node.id.clone()
+    }
+
+    #[qjs(get, rename = "className")]
+    pub fn class_name(&self) -> String {
+        self.node.class_name.clone()
+    }
+
+    #[qjs(get, rename = "packageName")]
+    pub fn package_name(&self) -> String {
+        self.node.package_name.clone()
+    }
+
+    /// [x, y, 宽, 高]
+    #[qjs(get)]
+    pub fn bounds(&self) -> Vec<i32> {
+        logical_bounds(&self.node, unmap_coordinates)
+    }
+
+    /// [x, y]
+    #[qjs(get)]
+    pub fn center(&self) -> Vec<i32> {
+        let (x, y) = self.node.bounds.center();
+        let (x, y) = unmap_coordinates(x, y);
+        vec![x, y]
+    }
+
+    #[qjs(get)]
+    pub fn clickable(&self) -> bool {
+        self.node.clickable
+    }
+
+    #[qjs(get)]
+    pub fn enabled(&self) -> bool {
+        self.node.enabled
+    }
+
+    #[qjs(get)]
+    pub fn checked(&self) -> bool {
+        self.node.checked
+    }
+
+    #[qjs(get)]
+    pub fn selected(&self) -> bool {
+        self.node.selected
+    }
+
+    #[qjs(get)]
+    pub fn focused(&self) -> bool {
+        self.node.focused
+    }
+
+    #[qjs(get)]
+    pub fn scrollable(&self) -> bool {
+        self.node.scrollable
+    }
+
+    pub fn children<'js>(&self, ctx: Ctx<'js>) -> Result<Vec<Class<'js, UiObject>>> {
+        self.node
+            .children
+            .iter()
+            .map(|node| Class::instance(ctx.clone(), UiObject { node: node.clone() }))
+            .collect()
+    }
+
+    /// 在这个控件的子树里查找 (不重新抓取)
+    #[qjs(rename = "findOne")]
+    pub fn find_one<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        selector: Class<'js, UiSelector>,
+    ) -> Result<Option<Class<'js, UiObject>>> {
+        let found = selector.borrow().selector.find_one(&self.node).cloned();
+        found
+            .map(|node| Class::instance(ctx.clone(), UiObject { node }))
+            .transpose()
+    }
+
+    pub fn click(&self, ctx: Ctx<'_>) -> Result<()> {
+        click_node(&ctx, &self.node)
+    }
+}
@@ -0,0 +1,363 @@
+// ==========================================================
+// 📦 脚本包 (Script Bundle)
+// 一个目录或 zip，包含 manifest.json + ES 模块 + 资源 (模板图、字库...)
+//
+// my_script/
+// ├── manifest.json   (见 manifest.rs)
+// ├── index.js
+// ├── lib/utils.js
+// └── assets/btn_start.png
+// ==========================================================
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::manifest::ScriptManifest;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// zip 包单个文件解压后的大小上限
+const MAX_ZIP_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+/// zip 包解压后的总大小上限 (整包放在内存里)
+const MAX_ZIP_TOTAL_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum BundleError {
+    #[error("Failed to read bundle: {0}")]
+    IoError(String),
+
+    #[error("Invalid zip bundle: {0}")]
+    ZipError(String),
+
+    #[error("Invalid manifest: {0}")]
+    ManifestError(String),
+
+    #[error("Path escapes bundle root: {0}")]
+    InvalidPath(String),
+
+    #[error("File not found in bundle: {0}")]
+    NotFound(String),
+
+    #[error("Script requires engine API {required}, current is {current}")]
+    ApiVersionTooLow { required: u32, current: u32 },
+
+    #[error("Script requires `{permission}` permission")]
+    PermissionMissing { permission: String },
+
+    #[error(
+        "Script targets {expected_width}x{expected_height}, screen is {actual_width}x{actual_height}"
+    )]
+    ResolutionMismatch {
+        expected_width: u32,
+        expected_height: u32,
+        actual_width: u32,
+        actual_height: u32,
+    },
+
+    #[error("Invalid config schema: {0}")]
+    InvalidConfigSchema(String),
+}
+
+enum BundleSource {
+    // 规范化 (解析过符号链接) 的包根目录
+    Dir(PathBuf),
+    // zip 包体积很小，直接整包解压到内存
+    Memory(HashMap<String, Vec<u8>>),
+}
+
+pub struct ScriptBundle {
+    source: BundleSource,
+    manifest: ScriptManifest,
+}
+
+impl ScriptBundle {
+    /// 打开目录或 .zip 脚本包
+    pub fn open(path: &Path) -> Result<Self, BundleError> {
+        let source = if path.is_dir() {
+            let root = path
+                .canonicalize()
+                .map_err(|e| BundleError::IoError(e.to_string()))?;
+            BundleSource::Dir(root)
+        } else {
+            let file = fs::File::open(path).map_err(|e| BundleError::IoError(e.to_string()))?;
+            BundleSource::Memory(read_zip(file, MAX_ZIP_ENTRY_BYTES, MAX_ZIP_TOTAL_BYTES)?)
+        };
+        Self::from_source(source)
+    }
+
+    /// 从内存文件表构建 (测试 / 远程推送)
+    pub fn from_files(files: HashMap<String, Vec<u8>>) -> Result<Self, BundleError> {
+        let files = files
+            .into_iter()
+            .map(|(k, v)| normalize_path("", &k).map(|k| (k, v)))
+            .collect::<Result<_, _>>()?;
+        Self::from_source(BundleSource::Memory(files))
+    }
+
+    fn from_source(source: BundleSource) -> Result<Self, BundleError> {
+        let raw = read_source(&source, MANIFEST_FILE)?;
+        let manifest = ScriptManifest::parse(&raw)?;
+        Ok(Self { source, manifest })
+    }
+
+    pub fn manifest(&self) -> &ScriptManifest {
+        &self.manifest
+    }
+
+    /// 读取包内文件，path 相对包根目录
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, BundleError> {
+        read_source(&self.source, path)
+    }
+
+    pub fn read_text(&self, path: &str) -> Result<String, BundleError> {
+        String::from_utf8(self.read(path)?).map_err(|e| BundleError::IoError(e.to_string()))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        let Ok(key) = normalize_path("", path) else {
+            return false;
+        };
+        match &self.source {
+            BundleSource::Dir(root) => dir_path(root, &key).is_ok_and(|p| p.is_file()),
+            BundleSource::Memory(files) => files.contains_key(&key),
+        }
+    }
+
+    /// 列出包内目录下的条目 (子目录以 "/" 结尾)，dir 为 "" 表示包根目录
+    pub fn list(&self, dir: &str) -> Vec<String> {
+        let Ok(key) = normalize_path("", dir) else {
+            return vec![];
+        };
+        match &self.source {
+            BundleSource::Dir(root) => match dir_path(root, &key)
+                .and_then(|dir| fs::read_dir(dir).map_err(|e| BundleError::IoError(e.to_string())))
+            {
+                Ok(entries) => entries
+                    .flatten()
+                    .map(|entry| {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        if entry.path().is_dir() {
+                            format!("{}/", name)
+                        } else {
+                            name
+                        }
+                    })
+                    .collect(),
+                Err(_) => vec![],
+            },
+            BundleSource::Memory(files) => {
+                let prefix = if key.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}/", key)
+                };
+                let mut names: Vec<String> = files
+                    .keys()
+                    .filter_map(|path| path.strip_prefix(&prefix))
+                    .map(|rest| match rest.split_once('/') {
+                        Some((dir, _)) => format!("{}/", dir),
+                        None => rest.to_string(),
+                    })
+                    .collect();
+                names.sort();
+                names.dedup();
+                names
+            }
+        }
+    }
+
+    /// 包所在目录 (zip 包返回 None)
+    pub fn root_dir(&self) -> Option<&Path> {
+        match &self.source {
+            BundleSource::Dir(root) => Some(root),
+            BundleSource::Memory(_) => None,
+        }
+    }
+}
+
+fn read_source(source: &BundleSource, path: &str) -> Result<Vec<u8>, BundleError> {
+    let key = normalize_path("", path)?;
+    match source {
+        BundleSource::Dir(root) => fs::read(dir_path(root, &key)?).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => BundleError::NotFound(key),
+            _ => BundleError::IoError(e.to_string()),
+        }),
+        BundleSource::Memory(files) => files.get(&key).cloned().ok_or(BundleError::NotFound(key)),
+    }
+}
+
+/// 目录包里的真实路径，并确认没有通过符号链接跑到包根目录外 (同 Sandbox::data_path)
+fn dir_path(root: &Path, key: &str) -> Result<PathBuf, BundleError> {
+    let path = root.join(key);
+    // 找到最深的已存在的祖先 (含自身)，解析链接后必须仍在根目录下
+    let existing = path.ancestors().find(|p| p.exists()).unwrap_or(root);
+    let resolved = existing
+        .canonicalize()
+        .map_err(|e| BundleError::IoError(e.to_string()))?;
+    if !resolved.starts_with(root) {
+        return Err(BundleError::InvalidPath(key.to_string()));
+    }
+    Ok(path)
+}
+
+/// 整包解压到内存。entry.size() 来自 zip 头，不可信: 按上限分配、按上限读
+fn read_zip(
+    file: impl Read + std::io::Seek,
+    max_entry: u64,
+    max_total: u64,
+) -> Result<HashMap<String, Vec<u8>>, BundleError> {
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| BundleError::ZipError(e.to_string()))?;
+    let mut files = HashMap::new();
+    let mut total = 0u64;
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| BundleError::ZipError(e.to_string()))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = normalize_path("", entry.name())?;
+        let limit = max_entry.min(max_total - total);
+        let mut data = Vec::with_capacity(entry.size().min(limit) as usize);
+        (&mut entry)
+            .take(limit + 1)
+            .read_to_end(&mut data)
+            .map_err(|e| BundleError::ZipError(e.to_string()))?;
+        if data.len() as u64 > limit {
+            return Err(BundleError::ZipError(if limit == max_entry {
+                format!("{} exceeds {} bytes", name, max_entry)
+            } else {
+                format!("Bundle exceeds {} bytes", max_total)
+            }));
+        }
+        total += data.len() as u64;
+        files.insert(name, data);
+    }
+    Ok(files)
+}
+
+/// 把 base 目录下的相对路径规整为包内路径 ("lib/../a.js" -> "a.js")
+/// 以 "/" 开头表示从包根目录开始；越过根目录的 ".." 会被拒绝
+pub fn normalize_path(base_dir: &str, path: &str) -> Result<String, BundleError> {
+    let mut parts: Vec<&str> = Vec::new();
+    let joined = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("{}/{}", base_dir, path)
+    };
+    for part in joined.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.pop().is_none() {
+                    return Err(BundleError::InvalidPath(path.to_string()));
+                }
+            }
+            p => parts.push(p),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// 模块路径所在目录 ("lib/utils.js" -> "lib")
+pub fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Cursor, Write},
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static NEXT: AtomicU32 = AtomicU32::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "touch_bundle_{}_{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn zip(files: &[(&str, usize)]) -> Cursor<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, size) in files {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(&vec![b'x'; *size]).unwrap();
+        }
+        Cursor::new(writer.finish().unwrap().into_inner())
+    }
+
+    #[test]
+    fn zip_entries_and_total_are_capped() {
+        let files = read_zip(zip(&[("a.js", 10), ("b.js", 10)]), 10, 20).unwrap();
+        assert_eq!(files["a.js"].len(), 10);
+        assert_eq!(files["b.js"].len(), 10);
+
+        let err = read_zip(zip(&[("a.js", 11)]), 10, 100).unwrap_err();
+        assert!(err.to_string().contains("a.js exceeds 10 bytes"), "{}", err);
+        let err = read_zip(zip(&[("a.js", 10), ("b.js", 10), ("c.js", 1)]), 10, 20).unwrap_err();
+        assert!(
+            err.to_string().contains("Bundle exceeds 20 bytes"),
+            "{}",
+            err
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_bundle_rejects_symlinks_pointing_outside() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new();
+        let root = tmp.0.join("bundle");
+        let outside = tmp.0.join("outside");
+        fs::create_dir_all(root.join("lib")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join(MANIFEST_FILE), r#"{"name": "demo"}"#).unwrap();
+        fs::write(root.join("lib/a.js"), "inside").unwrap();
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+        symlink(&outside, root.join("dir_link")).unwrap();
+        symlink(outside.join("secret.txt"), root.join("file_link")).unwrap();
+        symlink(root.join("lib/a.js"), root.join("inner_link")).unwrap();
+
+        let bundle = ScriptBundle::open(&root).unwrap();
+        assert!(matches!(
+            bundle.read("dir_link/secret.txt"),
+            Err(BundleError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            bundle.read("file_link"),
+            Err(BundleError::InvalidPath(_))
+        ));
+        assert!(!bundle.exists("file_link"));
+        assert!(bundle.list("dir_link").is_empty());
+
+        // 指向包内的链接可以用
+        assert_eq!(bundle.read_text("inner_link").unwrap(), "inside");
+        assert!(bundle.exists("lib/a.js"));
+        assert_eq!(bundle.list("lib"), vec!["a.js"]);
+    }
+}
@@ -0,0 +1,260 @@
+// ==========================================================
+// ⚙️ 脚本配置 (Config)
+// 宿主 (Kotlin) 通过 UniFFI 写入，脚本通过 JS 的 Config 读取
+// 类型以 manifest.json 的 config 声明为准，未声明的键按值本身推断
+// 脚本运行中修改会广播给 JS 的 Config.onChange 监听器
+// ==========================================================
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use tokio::sync::broadcast;
+
+use crate::manifest::{ConfigField, ConfigType};
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum ConfigError {
+    #[error("Config `{key}` expects {expected}")]
+    TypeMismatch { key: String, expected: String },
+
+    #[error("Invalid config json: {0}")]
+    InvalidJson(String),
+}
+
+/// 宿主侧的配置值
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum ConfigValue {
+    Text {
+        value: String,
+    },
+    Int {
+        value: i64,
+    },
+    Float {
+        value: f64,
+    },
+    Bool {
+        value: bool,
+    },
+    List {
+        items: Vec<ConfigValue>,
+    },
+    /// 任意 JSON (对象 / 对象数组等复杂结构)
+    Json {
+        json: String,
+    },
+}
+
+/// 一次配置变更 (old = None 表示新增，value = None 表示删除)
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    pub key: String,
+    pub value: Option<Value>,
+    pub old: Option<Value>,
+}
+
+pub struct ConfigStore {
+    values: HashMap<String, Value>,
+    schema: HashMap<String, ConfigType>,
+    changes: broadcast::Sender<ConfigChange>,
+}
+
+lazy_static::lazy_static! {
+    pub static ref CONFIG: std::sync::Mutex<ConfigStore> = std::sync::Mutex::new(ConfigStore::new());
+}
+
+impl ConfigValue {
+    pub fn to_json(&self) -> Result<Value, ConfigError> {
+        Ok(match self {
+            ConfigValue::Text { value } => Value::from(value.clone()),
+            ConfigValue::Int { value } => Value::from(*value),
+            ConfigValue::Float { value } => Value::from(*value),
+            ConfigValue::Bool { value } => Value::from(*value),
+            ConfigValue::List { items } => Value::Array(
+                items
+                    .iter()
+                    .map(|v| v.to_json())
+                    .collect::<Result<_, _>>()?,
+            ),
+            ConfigValue::Json { json } => {
+                serde_json::from_str(json).map_err(|e| ConfigError::InvalidJson(e.to_string()))?
+            }
+        })
+    }
+}
+
+impl ConfigType {
+    fn name(&self) -> &'static str {
+        match self {
+            ConfigType::String => "string",
+            ConfigType::Int => "int",
+            ConfigType::Float => "float",
+            ConfigType::Bool => "bool",
+            ConfigType::List => "list",
+        }
+    }
+}
+
+impl Default for ConfigStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigStore {
+    pub fn new() -> Self {
+        // 容量只影响慢消费者，满了最旧的变更会被丢弃
+        let (changes, _) = broadcast::channel(64);
+        Self {
+            values: HashMap::new(),
+            schema: HashMap::new(),
+            changes,
+        }
+    }
+
+    /// 载入清单声明: 记录类型，宿主还没设置的键填入默认值
+    /// 已有值类型不符时用默认值覆盖
+    pub fn apply_manifest(&mut self, fields: &[ConfigField]) {
+        self.schema = fields.iter().map(|f| (f.key.clone(), f.kind)).collect();
+        for field in fields {
+            let valid = self
+                .values
+                .get(&field.key)
+                .is_some_and(|v| field.kind.accepts(v));
+            if valid {
+                continue;
+            }
+            match &field.default {
+                Some(default) => {
+                    self.values.insert(field.key.clone(), default.clone());
+                }
+                None => {
+                    self.values.remove(&field.key);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.values.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// 全部配置，作为 JSON 对象
+    pub fn snapshot(&self) -> Value {
+        Value::Object(
+            self.values
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Map<_, _>>(),
+        )
+    }
+
+    /// 写入一个值 (按声明校验类型)，值有变化时广播
+    pub fn set(&mut self, key: &str, value: Value) -> Result<(), ConfigError> {
+        if let Some(kind) = self.schema.get(key) {
+            if !kind.accepts(&value) {
+                return Err(ConfigError::TypeMismatch {
+                    key: key.to_string(),
+                    expected: kind.name().to_string(),
+                });
+            }
+        }
+        let old = self.values.insert(key.to_string(), value.clone());
+        if old.as_ref() != Some(&value) {
+            // 没有订阅者时 send 返回 Err，忽略即可
+            let _ = self.changes.send(ConfigChange {
+                key: key.to_string(),
+                value: Some(value),
+                old,
+            });
+        }
+        Ok(())
+    }
+
+    /// 合并一个 JSON 对象 (全部校验通过才写入)
+    pub fn merge_json(&mut self, json: &str) -> Result<(), ConfigError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| ConfigError::InvalidJson(e.to_string()))?;
+        let Value::Object(map) = value else {
+            return Err(ConfigError::InvalidJson("expected an object".into()));
+        };
+        for (key, value) in &map {
+            if let Some(kind) = self.schema.get(key) {
+                if !kind.accepts(value) {
+                    return Err(ConfigError::TypeMismatch {
+                        key: key.clone(),
+                        expected: kind.name().to_string(),
+                    });
+                }
+            }
+        }
+        for (key, value) in map {
+            self.set(&key, value)?;
+        }
+        Ok(())
+    }
+
+    /// 删除一个值，存在时广播 (value 为 None)
+    pub fn remove(&mut self, key: &str) {
+        if let Some(old) = self.values.remove(key) {
+            self.notify_removed(key.to_string(), old);
+        }
+    }
+
+    /// 清空，每个被删除的键各广播一次
+    pub fn clear(&mut self) {
+        let mut removed: Vec<(String, Value)> = self.values.drain().collect();
+        removed.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, old) in removed {
+            self.notify_removed(key, old);
+        }
+    }
+
+    fn notify_removed(&self, key: String, old: Value) {
+        let _ = self.changes.send(ConfigChange {
+            key,
+            value: None,
+            old: Some(old),
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigChange> {
+        self.changes.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn remove_and_clear_notify_listeners() {
+        let mut store = ConfigStore::new();
+        store.set("a", json!(1)).unwrap();
+        store.set("b", json!("x")).unwrap();
+        store.set("c", json!(true)).unwrap();
+        let mut changes = store.subscribe();
+        let mut next = || {
+            let change = changes.try_recv().unwrap();
+            (change.key, change.value, change.old)
+        };
+
+        store.remove("a");
+        assert_eq!(next(), ("a".to_string(), None, Some(json!(1))));
+        // 不存在的键不广播
+        store.remove("a");
+        store.clear();
+        assert_eq!(next(), ("b".to_string(), None, Some(json!("x"))));
+        assert_eq!(next(), ("c".to_string(), None, Some(json!(true))));
+        assert!(changes.try_recv().is_err());
+        assert!(store.keys().is_empty());
+    }
+}
@@ -6,6 +6,17 @@ pub const JAVA_OUTPUT_PATH: &str = "../server/src/main/java/bind/GeneratedConsta
 // 2. Java Server 的完整类名 (Rust 启动命令需要，Java 本身也可以校验)
 pub const SERVER_CLASS_NAME: &str = "org.eu.freex.server.Main";
 
+// 脚本引擎 API 版本 (manifest.json 的 minApiVersion 与之比较)，新增/修改 JS API 时 +1
+pub const ENGINE_API_VERSION: u32 = 1;
+
+// 3. App 广播 (Root 模式下通过 am broadcast 让 App 写剪贴板)
+// 组件名 = 安装包名 (applicationId) / 类名，包名以宿主 init_service 传入的为准
+pub const DEFAULT_HOST_PACKAGE: &str = "org.eu.freex.touchhelper";
+pub const CLIPBOARD_RECEIVER_CLASS: &str = "org.eu.freex.app.ClipboardReceiver";
+pub const ACTION_SET_CLIPBOARD: &str = "org.eu.freex.SET_CLIPBOARD";
+// ADB Keyboard 输入法的广播 (需用户自行安装并切换为当前输入法)
+pub const ACTION_IME_INPUT: &str = "ADB_INPUT_TEXT";
+
 // ==========================================================================
 // 📝 在这里添加共享内存配置
 // ==========================================================================
@@ -7,11 +7,13 @@ use std::{
     thread,
 };
 
+use image::RgbaImage;
 use lazy_static::lazy_static;
 use log::{error, info};
 use memmap2::MmapOptions;
 
 use crate::constants::{SERVER_CLASS_NAME, SHARED_FILE_PATH, SHARED_MEMORY_SIZE};
+use crate::vision::types::{Rect, VisionError};
 //  全局缓冲区
 // 结构: (数据, 宽, 高, 行跨度, 缩放比例)
 // 对应 JNI 里的 guard.0, guard.1 ...
@@ -93,10 +95,7 @@ pub fn find_color_in_buffer(
                 continue;
             }
 
-            // BGR -> RGB 转换
-            let b = pixels[offset];
-            let g = pixels[offset + 1];
-            let r = pixels[offset + 2];
+            let (r, g, b) = pixel_rgb(pixels, offset);
 
             if is_color_match(r, g, b, tr, tg, tb, tolerance) {
                 return Some((x as i32, y as i32));
@@ -106,6 +105,61 @@ pub fn find_color_in_buffer(
     None
 }
 
+/// 缓冲区像素的 RGB
+/// 两个来源都是 R,G,B,A 字节顺序: 无障碍录屏的 ImageReader (RGBA_8888)，
+/// Root Server 的 Bitmap (ARGB_8888 经 copyPixelsToBuffer 也是 RGBA 字节序)
+/// 注意: 早期版本按 B,G,R 读取，红蓝通道对调，找色时要把目标色写反才能找到；
+/// 现在按真实字节序读取，和 Screen.capture / Image 的像素一致，之前写反颜色的脚本需要改回来
+#[inline]
+pub fn pixel_rgb(pixels: &[u8], offset: usize) -> (u8, u8, u8) {
+    (pixels[offset], pixels[offset + 1], pixels[offset + 2])
+}
+
+/// 拷贝当前帧 (或其中一块区域，缓冲区坐标) 为 RGBA 图片，返回 (图片, 缩放比例)
+/// 区域超出画面的部分会被裁掉，完全在画面外报错
+pub fn capture_frame(region: Option<Rect>) -> Result<(RgbaImage, f32), VisionError> {
+    let guard = SCREEN_BUFFER.lock().unwrap();
+    let (pixels, w, h, stride, scale) = &*guard;
+    let (w, h, stride) = (*w as i64, *h as i64, *stride);
+    if pixels.is_empty() || w == 0 || h == 0 {
+        return Err(VisionError::NoFrame);
+    }
+    let (left, top, right, bottom) = match region {
+        Some(r) => (
+            (r.left as i64).max(0),
+            (r.top as i64).max(0),
+            (r.left as i64 + r.width as i64).min(w),
+            (r.top as i64 + r.height as i64).min(h),
+        ),
+        None => (0, 0, w, h),
+    };
+    if left >= right || top >= bottom {
+        let r = region.unwrap_or(Rect {
+            left: 0,
+            top: 0,
+            width: 0,
+            height: 0,
+        });
+        return Err(VisionError::InvalidRegion(format!(
+            "[{}, {}, {}, {}] is outside the {}x{} screen",
+            r.left, r.top, r.width, r.height, w, h
+        )));
+    }
+    let (width, height) = ((right - left) as usize, (bottom - top) as usize);
+    let mut data = Vec::with_capacity(width * height * 4);
+    for y in top as usize..bottom as usize {
+        let start = y * stride + left as usize * 4;
+        let row = pixels
+            .get(start..start + width * 4)
+            .ok_or_else(|| VisionError::ProcessError("screen buffer is truncated".into()))?;
+        // 截图不需要透明度，alpha 统一为不透明
+        data.extend(row.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2], 255]));
+    }
+    let img = RgbaImage::from_raw(width as u32, height as u32, data)
+        .ok_or_else(|| VisionError::ProcessError("invalid frame size".into()))?;
+    Ok((img, *scale))
+}
+
 // 只负责算坐标，不负责点
 pub fn map_coordinates(x: i32, y: i32) -> (i32, i32) {
     let scale = {
@@ -117,6 +171,25 @@ pub fn map_coordinates(x: i32, y: i32) -> (i32, i32) {
     (real_x, real_y)
 }
 
+// 反向映射: 真实屏幕坐标 -> 脚本逻辑坐标
+pub fn unmap_coordinates(x: i32, y: i32) -> (i32, i32) {
+    let scale = {
+        let guard = SCREEN_BUFFER.lock().unwrap();
+        guard.4
+    };
+    unmap_with_scale(x, y, scale)
+}
+
+// 按给定的缩放比例反向映射 (还没有画面时 scale 为 0，原样返回)
+pub fn unmap_with_scale(x: i32, y: i32, scale: f32) -> (i32, i32) {
+    if scale == 0.0 {
+        return (x, y);
+    }
+    let logical_x = (x as f32 / scale) as i32;
+    let logical_y = (y as f32 / scale) as i32;
+    (logical_x, logical_y)
+}
+
 // --- 5. 核心逻辑：Root Server 启动 (保持原样) ---
 
 pub fn start_root_server_internal(jar_path: String) {
@@ -240,6 +313,7 @@ pub fn start_root_server_internal(jar_path: String) {
                             guard.3 = width * 4;
                             guard.4 = scale;
                         }
+                        crate::events::frame_updated(width, height, scale);
                     }
                 }
                 Err(e) => {
@@ -250,3 +324,54 @@ pub fn start_root_server_internal(jar_path: String) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 录屏缓冲区: 每行 stride 字节 (含行尾填充)，每个像素 R,G,B,A
+    fn frame(
+        width: usize,
+        height: usize,
+        stride: usize,
+        pixel: (usize, usize, [u8; 4]),
+    ) -> Vec<u8> {
+        let mut pixels = vec![0u8; stride * height];
+        let (x, y, rgba) = pixel;
+        pixels[y * stride + x * 4..][..4].copy_from_slice(&rgba);
+        assert!(width * 4 <= stride);
+        pixels
+    }
+
+    #[test]
+    fn buffer_is_read_in_rgba_byte_order() {
+        // ImageReader (RGBA_8888) 的 plane 字节: 0xFF, 0x80, 0x10, 0xFF 是 #FF8010
+        let pixels = frame(4, 3, 20, (2, 1, [0xFF, 0x80, 0x10, 0xFF]));
+        assert_eq!(pixel_rgb(&pixels, 20 + 2 * 4), (0xFF, 0x80, 0x10));
+        assert_eq!(
+            find_color_in_buffer(
+                &pixels,
+                4,
+                3,
+                20,
+                parse_hex_color("#FF8010"),
+                0,
+                (0, 0, 4, 3)
+            ),
+            Some((2, 1))
+        );
+        // 按 BGR 读取时才会匹配的颜色不再命中
+        assert_eq!(
+            find_color_in_buffer(
+                &pixels,
+                4,
+                3,
+                20,
+                parse_hex_color("#1080FF"),
+                0,
+                (0, 0, 4, 3)
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,118 @@
+// ==========================================================
+// 📁 App 数据目录
+// 宿主启动时通过 set_data_dir 传入 (Android: Context.filesDir)，
+// 脚本的持久化数据 (Storage 等) 按命名空间 (清单名 / 脚本内容哈希) 分开存放在下面
+// 没设置时这些数据只保存在内存里，App 退出即丢失
+// ==========================================================
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+lazy_static::lazy_static! {
+    static ref DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+pub fn set(path: &Path) {
+    *DATA_DIR.lock().unwrap() = Some(path.to_path_buf());
+}
+
+pub fn get() -> Option<PathBuf> {
+    DATA_DIR.lock().unwrap().clone()
+}
+
+/// 数据目录下的子目录 (如 "storage")，没设置数据目录返回 None
+pub fn subdir(name: &str) -> Option<PathBuf> {
+    get().map(|dir| dir.join(name))
+}
+
+/// 命名空间 -> 可以安全用作文件名的形式 (百分号编码)
+/// 保留字母数字和 - _ .，其它字符 (包括 % 本身) 按 UTF-8 字节编码成 %XX，
+/// 开头的 . 也编码 (不会变成 "." / ".." / 隐藏文件)，空名字编码成 "%"
+/// 不同的名字一定得到不同的文件名，可以用 unsanitize 还原
+pub fn sanitize(name: &str) -> String {
+    if name.is_empty() {
+        return "%".to_string();
+    }
+    let mut out = String::with_capacity(name.len());
+    for (i, c) in name.char_indices() {
+        let keep = c.is_alphanumeric() || matches!(c, '-' | '_') || (c == '.' && i > 0);
+        if keep {
+            out.push(c);
+        } else {
+            for b in c.encode_utf8(&mut [0; 4]).bytes() {
+                out.push_str(&format!("%{:02X}", b));
+            }
+        }
+    }
+    out
+}
+
+/// sanitize 的逆运算，不是 sanitize 产生的名字返回 None
+pub fn unsanitize(file_name: &str) -> Option<String> {
+    if file_name == "%" {
+        return Some(String::new());
+    }
+    let mut bytes = Vec::with_capacity(file_name.len());
+    let mut rest = file_name.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    let name = String::from_utf8(bytes).ok()?;
+    (sanitize(&name) == file_name).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_is_injective() {
+        let names = [
+            "",
+            "%",
+            "_",
+            "a b",
+            "a_b",
+            "a/b",
+            "a%2Fb",
+            "..",
+            ".",
+            "._",
+            "a.b",
+            "游戏脚本",
+            "%25",
+        ];
+        let encoded: Vec<String> = names.iter().map(|n| sanitize(n)).collect();
+        for (i, a) in encoded.iter().enumerate() {
+            for b in &encoded[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        for (name, file) in names.iter().zip(&encoded) {
+            assert!(!file.contains(['/', '\\']) && file != "." && file != "..");
+            assert!(!file.starts_with('.'));
+            assert_eq!(unsanitize(file).as_deref(), Some(*name));
+        }
+        assert_eq!(sanitize("daily-task_v2.1"), "daily-task_v2.1");
+        assert_eq!(sanitize("a/b"), "a%2Fb");
+        assert_eq!(sanitize(".."), "%2E.");
+    }
+
+    #[test]
+    fn unsanitize_rejects_foreign_names() {
+        assert_eq!(unsanitize("a b"), None);
+        assert_eq!(unsanitize("%2"), None);
+        assert_eq!(unsanitize("%zz"), None);
+        // 非规范编码 (字母数字不该被编码)
+        assert_eq!(unsanitize("%41"), None);
+    }
+}
@@ -0,0 +1,393 @@
+// ==========================================================
+// 🐞 脚本调试器
+// 设备上开一个本地 TCP 端口，电脑通过 adb forward 连接:
+//     adb forward tcp:9229 tcp:9229
+//     node tools/debug.js 9229        (或任意能收发 JSON 行的客户端)
+// 协议见 debugger/protocol.rs，实现方式见 debugger/instrument.rs
+// 求值能执行任意脚本 (包括 Root 的 Device.shell)，所以只由宿主显式开启，
+// 客户端连上后第一条请求必须是带令牌的 authenticate (dev_auth.rs)
+//
+// - 同一时间只调试一个主脚本 (服务开启后启动的第一个主脚本)，只接受一个客户端
+// - 断点 / 单步 / 求值都在脚本线程上处理 (debugger/hook.rs)，
+//   网络线程 (debugger/server.rs) 只负责转发
+// - 调试服务没开时脚本不做插桩，没有任何额外开销
+// ==========================================================
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{mpsc, Arc, Mutex},
+};
+
+use log::{info, warn};
+use serde_json::json;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    js_engine::source_map::ScriptOrigin,
+    registry::{self, ScriptId, ScriptRole},
+};
+
+pub mod hook;
+pub mod instrument;
+pub mod protocol;
+mod server;
+
+pub use hook::Debuggee;
+use protocol::{BreakpointInfo, Command, ExceptionMode, Location, PauseReason, Request};
+
+/// 断点请求行上没有语句时，最多向后顺延多少行
+const BREAKPOINT_SEARCH_LINES: u32 = 20;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum DebugError {
+    #[error("Debug server is already listening on port {0}")]
+    AlreadyRunning(u16),
+
+    #[error("Failed to start debug server: {0}")]
+    IoError(String),
+
+    #[error(
+        "Debug token must be at least {} characters",
+        crate::dev_auth::MIN_TOKEN_LEN
+    )]
+    WeakToken,
+}
+
+/// 被调试脚本的静态信息
+pub struct DebugInfo {
+    pub script_id: ScriptId,
+    pub origin: ScriptOrigin,
+    /// 可暂停的行 -> 可见变量名
+    pub stops: BTreeMap<u32, Vec<String>>,
+}
+
+impl DebugInfo {
+    pub fn location(&self, line: u32, column: Option<u32>) -> Location {
+        let original = self
+            .origin
+            .original(line, column.unwrap_or(1))
+            .map(Into::into);
+        Location {
+            line,
+            column,
+            original,
+        }
+    }
+
+    /// 断点行 -> 实际停下的行 (source 为空表示生成代码的行号)
+    fn resolve_breakpoint(&self, source: Option<&str>, line: u32) -> Option<u32> {
+        let generated = match source {
+            None => line,
+            Some(source) => self
+                .origin
+                .source_map
+                .as_ref()?
+                .generated_line(source, line)?,
+        };
+        self.stops
+            .range(generated..=generated + BREAKPOINT_SEARCH_LINES)
+            .next()
+            .map(|(line, _)| *line)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    None,
+    In,
+    /// 调用栈深度不超过 n 时停下
+    Over(usize),
+    /// 调用栈深度小于 n 时停下
+    Out(usize),
+}
+
+struct Session {
+    port: Option<u16>,
+    /// 客户端握手要出示的令牌
+    token: String,
+    server: Option<server::ServerHandle>,
+    client: Option<UnboundedSender<String>>,
+    suspend_on_start: bool,
+    target: Option<Arc<DebugInfo>>,
+    /// 发给暂停中的脚本线程的命令
+    commands: Option<mpsc::Sender<Request>>,
+    /// 客户端设置的断点 (按 source 分组，脚本重新附加时重新解析)
+    breakpoint_requests: BTreeMap<Option<String>, Vec<u32>>,
+    /// 解析后的断点 (生成代码的行号)
+    breakpoints: BTreeSet<u32>,
+    pause_on_exceptions: ExceptionMode,
+    pause_requested: Option<PauseReason>,
+    step: Step,
+    paused: bool,
+    /// 正在为客户端求值，期间不触发断点
+    evaluating: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref SESSION: Mutex<Session> = Mutex::new(Session {
+        port: None,
+        token: String::new(),
+        server: None,
+        client: None,
+        suspend_on_start: false,
+        target: None,
+        commands: None,
+        breakpoint_requests: BTreeMap::new(),
+        breakpoints: BTreeSet::new(),
+        pause_on_exceptions: ExceptionMode::None,
+        pause_requested: None,
+        step: Step::None,
+        paused: false,
+        evaluating: false,
+    });
+}
+
+impl Session {
+    fn send(&self, line: String) {
+        if let Some(client) = &self.client {
+            let _ = client.send(line);
+        }
+    }
+
+    fn resolve_breakpoints(&mut self) {
+        self.breakpoints = match &self.target {
+            Some(target) => self
+                .breakpoint_requests
+                .iter()
+                .flat_map(|(source, lines)| {
+                    lines
+                        .iter()
+                        .filter_map(|line| target.resolve_breakpoint(source.as_deref(), *line))
+                })
+                .collect(),
+            None => BTreeSet::new(),
+        };
+    }
+
+    fn breakpoint_infos(&self, source: Option<&str>, lines: &[u32]) -> Vec<BreakpointInfo> {
+        lines
+            .iter()
+            .map(|&line| {
+                let generated_line = self
+                    .target
+                    .as_ref()
+                    .and_then(|t| t.resolve_breakpoint(source, line));
+                BreakpointInfo {
+                    line,
+                    verified: generated_line.is_some(),
+                    generated_line,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 开启调试服务 (只监听 127.0.0.1)。port = 0 时随机分配，返回实际端口
+/// suspend_on_start: 被调试的脚本在第一条语句前暂停，等客户端设置好断点再 continue
+/// token: 客户端 authenticate 时要出示的令牌
+pub fn start_server(port: u16, suspend_on_start: bool, token: String) -> Result<u16, DebugError> {
+    if !crate::dev_auth::is_strong(&token) {
+        return Err(DebugError::WeakToken);
+    }
+    let mut session = SESSION.lock().unwrap();
+    if let Some(port) = session.port {
+        return Err(DebugError::AlreadyRunning(port));
+    }
+    let handle = server::spawn(port)?;
+    let port = handle.port;
+    info!("🐞 Debug server listening on 127.0.0.1:{}", port);
+    session.port = Some(port);
+    session.token = token;
+    session.server = Some(handle);
+    session.suspend_on_start = suspend_on_start;
+    Ok(port)
+}
+
+/// 关闭调试服务，正在暂停的脚本继续运行
+pub fn stop_server() {
+    let handle = {
+        let mut session = SESSION.lock().unwrap();
+        session.port = None;
+        session.server.take()
+    };
+    if let Some(handle) = handle {
+        handle.shutdown();
+        info!("🐞 Debug server stopped");
+    }
+    disconnect();
+}
+
+pub fn server_port() -> Option<u16> {
+    SESSION.lock().unwrap().port
+}
+
+/// 脚本启动时调用: 调试服务开启、是主脚本、且没有其它脚本正在被调试时附加
+/// 返回插桩后的代码和要注册到运行时里的钩子状态
+pub fn attach(
+    script_id: ScriptId,
+    source: &str,
+    origin: ScriptOrigin,
+) -> Option<(String, Debuggee)> {
+    let mut session = SESSION.lock().unwrap();
+    if session.port.is_none()
+        || session.target.is_some()
+        || registry::role(script_id) != Some(ScriptRole::Main)
+    {
+        return None;
+    }
+
+    let instrumented = instrument::instrument(source);
+    let info = Arc::new(DebugInfo {
+        script_id,
+        origin,
+        stops: instrumented.stops,
+    });
+    let (tx, rx) = mpsc::channel();
+    session.target = Some(info.clone());
+    session.commands = Some(tx);
+    session.step = Step::None;
+    session.paused = false;
+    session.pause_requested = session.suspend_on_start.then_some(PauseReason::Entry);
+    session.resolve_breakpoints();
+    info!(
+        "🐞 Debugger attached to script #{} ({} stops)",
+        script_id,
+        info.stops.len()
+    );
+    session.send(protocol::event(
+        "attached",
+        json!({
+            "scriptId": script_id,
+            "file": info.origin.file,
+            "sources": info.origin.source_map.as_ref().map(|m| m.sources().to_vec()).unwrap_or_default(),
+        }),
+    ));
+    Some((instrumented.code, Debuggee::new(info, rx)))
+}
+
+/// 脚本结束时调用
+pub fn detach(script_id: ScriptId) {
+    let mut session = SESSION.lock().unwrap();
+    if session.target.as_ref().map(|t| t.script_id) != Some(script_id) {
+        return;
+    }
+    session.target = None;
+    session.commands = None;
+    session.breakpoints.clear();
+    session.step = Step::None;
+    session.paused = false;
+    session.pause_requested = None;
+    session.send(protocol::event(
+        "detached",
+        json!({ "scriptId": script_id }),
+    ));
+}
+
+/// 握手: 令牌和开启服务时的一致才允许连接
+fn authenticate(token: &str) -> bool {
+    let session = SESSION.lock().unwrap();
+    session.port.is_some() && crate::dev_auth::matches(&session.token, token)
+}
+
+/// 新客户端连接，已有客户端时拒绝
+fn connect(client: UnboundedSender<String>) -> bool {
+    let mut session = SESSION.lock().unwrap();
+    if session.client.is_some() {
+        return false;
+    }
+    info!("🐞 Debug client connected");
+    session.client = Some(client);
+    if let Some(target) = &session.target {
+        session.send(protocol::event(
+            "attached",
+            json!({ "scriptId": target.script_id, "file": target.origin.file, "paused": session.paused }),
+        ));
+    }
+    true
+}
+
+/// 客户端断开: 清掉断点和单步状态，暂停中的脚本继续运行
+fn disconnect() {
+    let mut session = SESSION.lock().unwrap();
+    if session.client.take().is_some() {
+        info!("🐞 Debug client disconnected");
+    }
+    session.breakpoint_requests.clear();
+    session.breakpoints.clear();
+    session.pause_on_exceptions = ExceptionMode::None;
+    session.pause_requested = None;
+    session.step = Step::None;
+    if session.paused {
+        if let Some(commands) = &session.commands {
+            let _ = commands.send(Request {
+                id: 0,
+                command: Command::Continue,
+            });
+        }
+    }
+}
+
+/// 处理客户端发来的一行
+fn handle_line(line: &str) {
+    // 没有参数的命令可以省略 arguments
+    let request = serde_json::from_str::<serde_json::Value>(line).and_then(|mut value| {
+        if let Some(object) = value.as_object_mut() {
+            object.entry("arguments").or_insert(serde_json::Value::Null);
+        }
+        serde_json::from_value::<Request>(value)
+    });
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => {
+            let session = SESSION.lock().unwrap();
+            session.send(protocol::failure(0, &format!("Invalid request: {}", e)));
+            return;
+        }
+    };
+
+    let mut session = SESSION.lock().unwrap();
+    let id = request.id;
+    match &request.command {
+        Command::SetBreakpoints { source, lines } => {
+            session
+                .breakpoint_requests
+                .insert(source.clone(), lines.clone());
+            session.resolve_breakpoints();
+            let infos = session.breakpoint_infos(source.as_deref(), lines);
+            session.send(protocol::success(id, json!({ "breakpoints": infos })));
+        }
+        Command::SetPauseOnExceptions { mode } => {
+            session.pause_on_exceptions = *mode;
+            session.send(protocol::success(id, json!({})));
+        }
+        Command::Pause => {
+            if session.target.is_none() {
+                session.send(protocol::failure(id, "No script attached"));
+            } else {
+                session.pause_requested = Some(PauseReason::Pause);
+                session.send(protocol::success(id, json!({})));
+            }
+        }
+        Command::Authenticate { .. } => {
+            session.send(protocol::success(id, json!({})));
+        }
+        Command::Status => {
+            let body = json!({
+                "scriptId": session.target.as_ref().map(|t| t.script_id),
+                "paused": session.paused,
+                "breakpoints": session.breakpoints,
+                "pauseOnExceptions": session.pause_on_exceptions,
+            });
+            session.send(protocol::success(id, body));
+        }
+        command if command.needs_paused_script() => match (&session.commands, session.paused) {
+            (Some(commands), true) => {
+                if commands.send(request).is_err() {
+                    session.send(protocol::failure(id, "Script has exited"));
+                }
+            }
+            _ => session.send(protocol::failure(id, "Script is not paused")),
+        },
+        _ => warn!("Unhandled debug command: {:?}", request.command),
+    }
+}
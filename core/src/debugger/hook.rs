@@ -0,0 +1,372 @@
+// ==========================================================
+// 🪝 脚本线程这一侧: 插桩钩子 + 暂停循环
+// 每条语句前调用 __touchDebug(line, scope, exception?)，判断是否要停下；
+// 停下后阻塞脚本线程，处理客户端的 evaluate / variables / stackTrace，
+// 直到 continue / step (或脚本被停止)
+// ==========================================================
+
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
+};
+
+use rquickjs::{
+    prelude::{Func, Opt},
+    CatchResultExt, Coerced, Ctx, Exception, Function, JsLifetime, Object, Result, Value,
+};
+use serde_json::json;
+
+use super::{
+    instrument::HOOK_NAME,
+    protocol::{
+        self, Command, ExceptionMode, Location, PauseReason, Request, StackFrameInfo, VariableInfo,
+    },
+    DebugInfo, Step, SESSION,
+};
+use crate::js_engine::{source_map::parse_frame, ScriptControl, ScriptError};
+
+/// 暂停时检查命令 / 停止标志的间隔
+const PAUSE_POLL: Duration = Duration::from_millis(20);
+/// 变量预览的最大长度
+const PREVIEW_LEN: usize = 200;
+/// variables 最多列出多少个属性
+const MAX_PROPERTIES: usize = 100;
+
+/// 存在 ctx userdata 里的调试状态
+#[derive(JsLifetime)]
+pub struct Debuggee {
+    info: Arc<DebugInfo>,
+    commands: Mutex<mpsc::Receiver<Request>>,
+}
+
+impl Debuggee {
+    pub(super) fn new(info: Arc<DebugInfo>, commands: mpsc::Receiver<Request>) -> Self {
+        Self {
+            info,
+            commands: Mutex::new(commands),
+        }
+    }
+}
+
+/// 求值作用域: 插桩闭包 (能看到局部变量) 或全局 (脚本已经退出到顶层)
+enum Scope<'js> {
+    Local(Function<'js>),
+    Global,
+}
+
+/// 注册钩子函数 (只有附加了调试器的运行时才有)
+pub fn register(ctx: &Ctx<'_>, debuggee: Debuggee) -> Result<()> {
+    let _ = ctx.store_userdata(debuggee);
+    ctx.globals().set(HOOK_NAME, Func::from(debug_hook))
+}
+
+fn debug_hook<'js>(
+    ctx: Ctx<'js>,
+    line: u32,
+    scope: Function<'js>,
+    exception: Opt<Value<'js>>,
+) -> Result<()> {
+    let (reason, step) = {
+        let session = SESSION.lock().unwrap();
+        if session.evaluating || session.paused {
+            return Ok(());
+        }
+        let reason = if exception.0.is_some() && session.pause_on_exceptions == ExceptionMode::All {
+            Some(PauseReason::Exception)
+        } else if let Some(reason) = session.pause_requested {
+            Some(reason)
+        } else if session.breakpoints.contains(&line) {
+            Some(PauseReason::Breakpoint)
+        } else {
+            None
+        };
+        (reason, session.step)
+    };
+
+    let reason = reason.or_else(|| {
+        let hit = match step {
+            Step::None => false,
+            Step::In => true,
+            Step::Over(depth) => stack_depth(&ctx) <= depth,
+            Step::Out(depth) => stack_depth(&ctx) < depth,
+        };
+        hit.then_some(PauseReason::Step)
+    });
+    match reason {
+        Some(reason) => pause(&ctx, Scope::Local(scope), Some(line), reason, exception.0),
+        None => Ok(()),
+    }
+}
+
+/// 脚本因未捕获的异常失败 (在 js_engine 里、运行时销毁前调用)
+pub fn on_uncaught(ctx: &Ctx<'_>, error: &ScriptError) {
+    let Some(control) = ctx.userdata::<ScriptControl>().map(|c| c.clone()) else {
+        return;
+    };
+    if control.should_stop() || ctx.userdata::<Debuggee>().is_none() {
+        return;
+    }
+    let pause_on = SESSION.lock().unwrap().pause_on_exceptions;
+    if pause_on == ExceptionMode::None {
+        return;
+    }
+    let exception = Exception::from_message(ctx.clone(), &error.message)
+        .map(|e| e.into_value())
+        .ok();
+    let _ = pause(ctx, Scope::Global, None, PauseReason::Exception, exception);
+}
+
+fn pause<'js>(
+    ctx: &Ctx<'js>,
+    scope: Scope<'js>,
+    line: Option<u32>,
+    reason: PauseReason,
+    exception: Option<Value<'js>>,
+) -> Result<()> {
+    let Some(debuggee) = ctx.userdata::<Debuggee>() else {
+        return Ok(());
+    };
+    let control = ctx.userdata::<ScriptControl>().map(|c| c.clone());
+    let info = debuggee.info.clone();
+    let location = line.map(|line| info.location(line, None));
+
+    {
+        let mut session = SESSION.lock().unwrap();
+        session.paused = true;
+        session.step = Step::None;
+        session.pause_requested = None;
+        let mut body = json!({
+            "reason": reason,
+            "scriptId": info.script_id,
+            "location": location,
+        });
+        if let Some(exception) = &exception {
+            body["exception"] = json!(describe(ctx, "exception", exception));
+        }
+        session.send(protocol::event("paused", body));
+    }
+
+    let mut next_step = Step::None;
+    loop {
+        if control.as_ref().is_some_and(|c| c.should_stop()) {
+            break;
+        }
+        let request = match debuggee.commands.lock().unwrap().recv_timeout(PAUSE_POLL) {
+            Ok(request) => request,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        let id = request.id;
+        let reply = match request.command {
+            Command::Continue => {
+                respond(id, Ok(json!({})));
+                break;
+            }
+            Command::StepIn | Command::StepOver | Command::StepOut if line.is_none() => {
+                respond(
+                    id,
+                    Err("Script has already exited, only continue is possible".into()),
+                );
+                continue;
+            }
+            Command::StepIn => {
+                next_step = Step::In;
+                respond(id, Ok(json!({})));
+                break;
+            }
+            Command::StepOver => {
+                next_step = Step::Over(stack_depth(ctx));
+                respond(id, Ok(json!({})));
+                break;
+            }
+            Command::StepOut => {
+                next_step = Step::Out(stack_depth(ctx));
+                respond(id, Ok(json!({})));
+                break;
+            }
+            Command::Evaluate { expression } => evaluate(ctx, &scope, &expression)
+                .map(|v| json!({ "result": describe(ctx, &expression, &v) })),
+            Command::Variables => Ok(json!({ "variables": variables(ctx, &scope, &info, line) })),
+            Command::Properties { expression } => evaluate(ctx, &scope, &expression).map(|v| {
+                let properties = v
+                    .as_object()
+                    .map(|o| properties(ctx, o))
+                    .unwrap_or_default();
+                json!({ "properties": properties })
+            }),
+            Command::StackTrace => {
+                Ok(json!({ "frames": stack_trace(ctx, &info, location.as_ref()) }))
+            }
+            other => Err(format!("Unexpected command while paused: {:?}", other)),
+        };
+        respond(id, reply);
+    }
+
+    {
+        let mut session = SESSION.lock().unwrap();
+        session.paused = false;
+        session.step = next_step;
+        session.send(protocol::event(
+            "resumed",
+            json!({ "scriptId": info.script_id }),
+        ));
+    }
+    drop(debuggee);
+
+    if control.is_some_and(|c| c.should_stop()) {
+        return Err(Exception::throw_internal(ctx, "Script aborted"));
+    }
+    Ok(())
+}
+
+fn respond(id: u64, reply: std::result::Result<serde_json::Value, String>) {
+    let session = SESSION.lock().unwrap();
+    session.send(match reply {
+        Ok(body) => protocol::success(id, body),
+        Err(message) => protocol::failure(id, &message),
+    });
+}
+
+/// 求值期间屏蔽断点 (表达式里调用的脚本函数也被插桩了)
+fn evaluate<'js>(
+    ctx: &Ctx<'js>,
+    scope: &Scope<'js>,
+    expression: &str,
+) -> std::result::Result<Value<'js>, String> {
+    SESSION.lock().unwrap().evaluating = true;
+    let result = match scope {
+        Scope::Local(f) => f.call::<_, Value>((expression,)),
+        Scope::Global => ctx.eval::<Value, _>(expression),
+    }
+    .catch(ctx)
+    .map_err(|e| ScriptError::from(e).message);
+    SESSION.lock().unwrap().evaluating = false;
+    result
+}
+
+fn variables<'js>(
+    ctx: &Ctx<'js>,
+    scope: &Scope<'js>,
+    info: &DebugInfo,
+    line: Option<u32>,
+) -> Vec<VariableInfo> {
+    match line.and_then(|line| info.stops.get(&line)) {
+        // 还没初始化的 let / const 会抛 ReferenceError，跳过
+        Some(names) => names
+            .iter()
+            .filter_map(|name| {
+                evaluate(ctx, scope, name)
+                    .ok()
+                    .map(|v| describe(ctx, name, &v))
+            })
+            .collect(),
+        None => properties(ctx, &ctx.globals()),
+    }
+}
+
+fn properties<'js>(ctx: &Ctx<'js>, object: &Object<'js>) -> Vec<VariableInfo> {
+    object
+        .keys::<String>()
+        .filter_map(|key| key.ok())
+        .take(MAX_PROPERTIES)
+        .filter_map(|key| {
+            let value: Value = object.get(key.as_str()).ok()?;
+            Some(describe(ctx, &key, &value))
+        })
+        .collect()
+}
+
+/// 值的类型 + 简短预览
+fn describe<'js>(ctx: &Ctx<'js>, name: &str, value: &Value<'js>) -> VariableInfo {
+    let preview = if let Some(s) = value.as_string() {
+        serde_json::to_string(&s.to_string().unwrap_or_default()).unwrap_or_default()
+    } else if value.is_error() {
+        let ex = value.as_exception();
+        let name: String = value
+            .as_object()
+            .and_then(|o| o.get("name").ok())
+            .unwrap_or_else(|| "Error".into());
+        format!(
+            "{}: {}",
+            name,
+            ex.and_then(|e| e.message()).unwrap_or_default()
+        )
+    } else if let Some(f) = value.as_function() {
+        let name: String = f.get("name").unwrap_or_default();
+        format!("function {}()", name)
+    } else if value.is_object() {
+        match ctx.json_stringify(value.clone()) {
+            Ok(Some(json)) => json.to_string().unwrap_or_default(),
+            _ => "[object]".into(),
+        }
+    } else {
+        value
+            .get::<Coerced<String>>()
+            .map(|c| c.0)
+            .unwrap_or_default()
+    };
+    VariableInfo {
+        name: name.to_string(),
+        kind: value.type_name().to_string(),
+        value: truncate(preview),
+    }
+}
+
+fn truncate(mut s: String) -> String {
+    if s.len() > PREVIEW_LEN {
+        let mut end = PREVIEW_LEN;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+        s.push('…');
+    }
+    s
+}
+
+/// 当前 JS 调用栈 (不含原生帧)
+fn js_frames(ctx: &Ctx<'_>) -> Vec<String> {
+    Exception::from_message(ctx.clone(), "")
+        .ok()
+        .and_then(|e| e.stack())
+        .map(|stack| {
+            stack
+                .lines()
+                .map(str::trim)
+                .filter(|l| l.starts_with("at ") && !l.ends_with("(native)"))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn stack_depth(ctx: &Ctx<'_>) -> usize {
+    js_frames(ctx).len()
+}
+
+fn stack_trace(ctx: &Ctx<'_>, info: &DebugInfo, current: Option<&Location>) -> Vec<StackFrameInfo> {
+    let mut frames: Vec<StackFrameInfo> = js_frames(ctx)
+        .iter()
+        .filter_map(|frame| parse_frame(frame))
+        .map(|frame| {
+            let location = match info.origin.script_line(&frame.file, frame.line) {
+                Some(line) => info.location(line, Some(frame.column)),
+                None => Location {
+                    line: frame.line,
+                    column: Some(frame.column),
+                    original: None,
+                },
+            };
+            StackFrameInfo {
+                function: frame.function.unwrap_or_else(|| "<anonymous>".to_string()),
+                file: frame.file,
+                location,
+            }
+        })
+        .collect();
+    // 钩子可能追加在上一行末尾，栈顶用暂停的行号
+    if let (Some(top), Some(current)) = (frames.first_mut(), current) {
+        top.location = current.clone();
+    }
+    frames
+}
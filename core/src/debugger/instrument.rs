@@ -0,0 +1,485 @@
+// ==========================================================
+// 🪡 调试插桩
+// QuickJS 没有断点 / 单步接口，调试模式下在每条语句前插入一次钩子调用:
+//     __touchDebug(行号, (__expr) => eval(__expr))
+// 闭包里的 eval 是直接 eval，能读到当前作用域的局部变量 (求值 / 查看变量用)
+// catch 块的第一条语句额外传入异常对象 (捕获的异常也能暂停)
+//
+// 只按行处理 esbuild 风格的输出 (一行一条语句)，不做完整语法分析:
+// - 上一行以 ";" / 代码块的 "{" / 代码块的 "}" 结尾，且当前处于代码块内 (不在对象字面量、
+//   类体、括号、字符串、模板、注释中) 才算语句开头
+// - 钩子尽量追加在上一行末尾，保证原有代码的行号和列号不变 (source map 仍然可用)
+// ==========================================================
+
+use std::collections::BTreeMap;
+
+/// 注入的钩子函数名 (见 debugger::hook)
+pub const HOOK_NAME: &str = "__touchDebug";
+
+/// 插桩结果
+pub struct Instrumented {
+    pub code: String,
+    /// 可以暂停的行 (1 开始) -> 该处可见的变量名 (内层在前)
+    pub stops: BTreeMap<u32, Vec<String>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Code,
+    Str(u8),
+    Template,
+    BlockComment,
+    Regex { in_class: bool },
+}
+
+enum Open {
+    Paren,
+    Bracket,
+    /// 模板字符串里的 ${ ... }
+    TemplateExpr,
+    /// 对象字面量 / 类体 / 解构
+    Object,
+    Block(Scope),
+}
+
+#[derive(Default)]
+struct Scope {
+    names: Vec<String>,
+    /// catch (e) 块，第一条语句把 e 传给钩子
+    catch_binding: Option<String>,
+}
+
+/// 行首的关键字 / 符号表明这一行是上一条语句的延续，不能在前面插入钩子
+const CONTINUATION_KEYWORDS: &[&str] = &["case", "default", "else", "catch", "finally"];
+const CONTINUATION_CHARS: &[u8] = b"})].,?:=+-*/%&|^<>";
+
+/// 出现在这些关键字之后的 "/" 是正则字面量
+const REGEX_AFTER_WORDS: &[&str] = &[
+    "return", "typeof", "case", "do", "else", "in", "of", "new", "delete", "void", "throw",
+    "yield", "await",
+];
+
+struct Scanner {
+    mode: Mode,
+    stack: Vec<Open>,
+    /// 顶层作用域
+    top_names: Vec<String>,
+    /// 上一个有效 (非空白、非注释) 字符
+    prev: Option<u8>,
+    prev2: Option<u8>,
+    /// 上一个 token 是标识符 / 关键字时的内容
+    prev_word: String,
+    /// 上一个有效字符是否是关闭代码块的 "}"
+    prev_closed_block: bool,
+    /// 上一个有效字符所在的行，以及它是否是那一行的最后一个非空白字符
+    prev_line: usize,
+    prev_at_line_end: bool,
+}
+
+pub fn instrument(source: &str) -> Instrumented {
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    let mut stops = BTreeMap::new();
+    let mut scanner = Scanner {
+        mode: Mode::Code,
+        stack: Vec::new(),
+        top_names: Vec::new(),
+        prev: None,
+        prev2: None,
+        prev_word: String::new(),
+        prev_closed_block: false,
+        prev_line: 0,
+        prev_at_line_end: false,
+    };
+
+    for index in 0..lines.len() {
+        let line_no = index as u32 + 1;
+        let text = lines[index].clone();
+        if index > 0 && scanner.at_statement_start(&text) {
+            let catch_binding = scanner.take_catch_binding();
+            stops.insert(line_no, scanner.visible_names());
+            let hook = match catch_binding {
+                Some(e) => format!(
+                    "{}({}, (__expr) => eval(__expr), {});",
+                    HOOK_NAME, line_no, e
+                ),
+                None => format!("{}({}, (__expr) => eval(__expr));", HOOK_NAME, line_no),
+            };
+            if scanner.prev.is_some() && scanner.prev_at_line_end {
+                lines[scanner.prev_line].push(' ');
+                lines[scanner.prev_line].push_str(&hook);
+            } else {
+                let indent = text.len() - text.trim_start().len();
+                lines[index] = format!("{}{} {}", &text[..indent], hook, &text[indent..]);
+            }
+            scanner.declare_from_statement(text.trim_start());
+        }
+        scanner.scan_line(index, &text);
+    }
+
+    let mut code = lines.join("\n");
+    if source.ends_with('\n') {
+        code.push('\n');
+    }
+    Instrumented { code, stops }
+}
+
+impl Scanner {
+    /// 当前行是否是一条新语句的开头 (在扫描这一行之前调用)
+    fn at_statement_start(&self, text: &str) -> bool {
+        let trimmed = text.trim_start();
+        if self.mode != Mode::Code || trimmed.is_empty() {
+            return false;
+        }
+        if !matches!(self.stack.last(), None | Some(Open::Block(_))) {
+            return false;
+        }
+        let after_statement = match self.prev {
+            Some(b';') => true,
+            Some(b'{') => matches!(self.stack.last(), Some(Open::Block(_))),
+            Some(b'}') => self.prev_closed_block,
+            _ => false,
+        };
+        if !after_statement || is_continuation(trimmed) {
+            return false;
+        }
+        // do x(); while (c); 的尾部
+        !(starts_with_word(trimmed, "while") && trimmed.trim_end().ends_with(';'))
+    }
+
+    fn take_catch_binding(&mut self) -> Option<String> {
+        match self.stack.last_mut() {
+            Some(Open::Block(scope)) => scope.catch_binding.take(),
+            _ => None,
+        }
+    }
+
+    fn visible_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let scopes = self.stack.iter().rev().filter_map(|o| match o {
+            Open::Block(scope) => Some(&scope.names),
+            _ => None,
+        });
+        for scope_names in scopes.chain(std::iter::once(&self.top_names)) {
+            for name in scope_names.iter().rev() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    fn current_scope(&mut self) -> &mut Vec<String> {
+        for open in self.stack.iter_mut().rev() {
+            if let Open::Block(scope) = open {
+                return &mut scope.names;
+            }
+        }
+        &mut self.top_names
+    }
+
+    /// 记录语句里声明的名字: const / let / var / function / class
+    fn declare_from_statement(&mut self, statement: &str) {
+        let mut rest = statement;
+        for prefix in ["export ", "default ", "async "] {
+            rest = rest.strip_prefix(prefix).unwrap_or(rest);
+        }
+        let name = [
+            "const ",
+            "let ",
+            "var ",
+            "function ",
+            "function* ",
+            "class ",
+        ]
+        .iter()
+        .find_map(|kw| rest.strip_prefix(kw))
+        .and_then(leading_identifier);
+        if let Some(name) = name {
+            let scope = self.current_scope();
+            if !scope.contains(&name) {
+                scope.push(name);
+            }
+        }
+    }
+
+    fn set_prev(&mut self, line: usize, c: u8) {
+        self.prev2 = self.prev;
+        self.prev = Some(c);
+        self.prev_line = line;
+        self.prev_closed_block = false;
+        if !is_ident_byte(c) {
+            self.prev_word.clear();
+        }
+    }
+
+    fn regex_allowed(&self) -> bool {
+        match self.prev {
+            None => true,
+            Some(c) if is_ident_byte(c) => REGEX_AFTER_WORDS.contains(&self.prev_word.as_str()),
+            Some(c) => b"(,=:[!&|?{};+-*%<>~^".contains(&c),
+        }
+    }
+
+    /// 判断 "{" 开启的是代码块还是对象字面量 / 类体
+    fn opens_block(&self) -> bool {
+        match self.prev {
+            None | Some(b';') | Some(b')') => true,
+            Some(b'}') => self.prev_closed_block,
+            Some(b'{') => matches!(self.stack.last(), None | Some(Open::Block(_))),
+            Some(b'>') => self.prev2 == Some(b'='),
+            Some(c) if is_ident_byte(c) => {
+                matches!(self.prev_word.as_str(), "else" | "try" | "finally" | "do")
+            }
+            _ => false,
+        }
+    }
+
+    fn scan_line(&mut self, line: usize, text: &str) {
+        let bytes = text.as_bytes();
+        let mut i = 0;
+        let mut last_significant = None;
+        while i < bytes.len() {
+            let c = bytes[i];
+            match self.mode {
+                Mode::Str(quote) => {
+                    if c == b'\\' {
+                        i += 1;
+                    } else if c == quote {
+                        self.mode = Mode::Code;
+                        self.set_prev(line, c);
+                        last_significant = Some(i);
+                    }
+                }
+                Mode::Template => {
+                    if c == b'\\' {
+                        i += 1;
+                    } else if c == b'`' {
+                        self.mode = Mode::Code;
+                        self.set_prev(line, c);
+                        last_significant = Some(i);
+                    } else if c == b'$' && bytes.get(i + 1) == Some(&b'{') {
+                        self.stack.push(Open::TemplateExpr);
+                        self.mode = Mode::Code;
+                        self.set_prev(line, b'{');
+                        i += 1;
+                    }
+                }
+                Mode::BlockComment => {
+                    if c == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                        self.mode = Mode::Code;
+                        i += 1;
+                    }
+                }
+                Mode::Regex { in_class } => {
+                    if c == b'\\' {
+                        i += 1;
+                    } else if c == b'[' {
+                        self.mode = Mode::Regex { in_class: true };
+                    } else if c == b']' {
+                        self.mode = Mode::Regex { in_class: false };
+                    } else if c == b'/' && !in_class {
+                        self.mode = Mode::Code;
+                        // 正则之后相当于一个值 (flags 会被当作标识符继续扫描)
+                        self.set_prev(line, b'0');
+                        last_significant = Some(i);
+                    }
+                }
+                Mode::Code => {
+                    if c.is_ascii_whitespace() {
+                        i += 1;
+                        continue;
+                    }
+                    match c {
+                        b'/' if bytes.get(i + 1) == Some(&b'/') => break,
+                        b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                            self.mode = Mode::BlockComment;
+                            i += 2;
+                            continue;
+                        }
+                        b'/' if self.regex_allowed() => self.mode = Mode::Regex { in_class: false },
+                        b'\'' | b'"' => self.mode = Mode::Str(c),
+                        b'`' => self.mode = Mode::Template,
+                        _ => {
+                            self.scan_code_byte(line, text, i, c);
+                            last_significant = Some(i);
+                            i += 1;
+                            continue;
+                        }
+                    }
+                    last_significant = Some(i);
+                }
+            }
+            i += 1;
+        }
+        if let Some(pos) = last_significant {
+            if self.prev_line == line {
+                self.prev_at_line_end =
+                    self.mode == Mode::Code && text[pos + 1..].trim().is_empty();
+            }
+        }
+    }
+
+    fn scan_code_byte(&mut self, line: usize, text: &str, i: usize, c: u8) {
+        match c {
+            b'(' => self.stack.push(Open::Paren),
+            b'[' => self.stack.push(Open::Bracket),
+            b')' | b']' => {
+                self.stack.pop();
+            }
+            b'{' => {
+                let open = if self.opens_block() {
+                    let head = &text[..i];
+                    Open::Block(Scope {
+                        names: block_params(head),
+                        catch_binding: catch_binding(head),
+                    })
+                } else {
+                    Open::Object
+                };
+                self.stack.push(open);
+            }
+            b'}' => {
+                let closed = self.stack.pop();
+                if let Some(Open::TemplateExpr) = closed {
+                    self.mode = Mode::Template;
+                }
+                self.set_prev(line, c);
+                self.prev_closed_block = matches!(closed, Some(Open::Block(_)));
+                return;
+            }
+            _ => {}
+        }
+        if is_ident_byte(c) {
+            if i == 0 || !is_ident_byte(text.as_bytes()[i - 1]) {
+                self.prev_word.clear();
+            }
+            self.prev_word.push(c as char);
+        }
+        self.set_prev(line, c);
+    }
+}
+
+fn is_ident_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80
+}
+
+fn starts_with_word(text: &str, word: &str) -> bool {
+    text.strip_prefix(word)
+        .is_some_and(|rest| !rest.bytes().next().is_some_and(is_ident_byte))
+}
+
+fn is_continuation(trimmed: &str) -> bool {
+    let first = trimmed.as_bytes()[0];
+    CONTINUATION_CHARS.contains(&first)
+        || CONTINUATION_KEYWORDS
+            .iter()
+            .any(|kw| starts_with_word(trimmed, kw))
+        || trimmed.starts_with("\"use strict\"")
+        || trimmed.starts_with("'use strict'")
+}
+
+fn leading_identifier(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let end = text
+        .bytes()
+        .position(|c| !is_ident_byte(c))
+        .unwrap_or(text.len());
+    let ident = &text[..end];
+    (!ident.is_empty() && !ident.as_bytes()[0].is_ascii_digit()).then(|| ident.to_string())
+}
+
+/// head 以 ")" 结尾时，返回最后一对括号里的内容和括号前的文本
+fn last_parens(head: &str) -> Option<(&str, &str)> {
+    let head = head.trim_end();
+    let close = head.len().checked_sub(1).filter(|_| head.ends_with(')'))?;
+    let mut depth = 0;
+    for (i, c) in head.bytes().enumerate().rev() {
+        match c {
+            b')' => depth += 1,
+            b'(' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&head[..i], &head[i + 1..close]));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn split_top_level(list: &str, sep: u8) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in list.bytes().enumerate() {
+        match c {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            _ if c == sep && depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts
+}
+
+/// 代码块开头 (函数参数 / for 循环变量 / catch 变量) 引入的名字
+fn block_params(head: &str) -> Vec<String> {
+    let head = head.trim_end();
+    let (head, arrow) = match head.strip_suffix("=>") {
+        Some(h) => (h.trim_end(), true),
+        None => (head, false),
+    };
+    let Some((before, inside)) = last_parens(head) else {
+        // x => { ... }
+        return if arrow {
+            head.rsplit(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+                .next()
+                .and_then(leading_identifier)
+                .into_iter()
+                .collect()
+        } else {
+            vec![]
+        };
+    };
+    let keyword = before
+        .trim_end()
+        .rsplit(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+        .next()
+        .unwrap_or("");
+    match keyword {
+        "if" | "while" | "switch" | "with" if !arrow => vec![],
+        "for" if !arrow => {
+            let init = split_top_level(inside, b';')[0];
+            let init = init.split(" of ").next().unwrap_or(init);
+            let init = init.split(" in ").next().unwrap_or(init);
+            ["const ", "let ", "var "]
+                .iter()
+                .find_map(|kw| init.trim_start().strip_prefix(kw))
+                .map(|decls| {
+                    split_top_level(decls, b',')
+                        .into_iter()
+                        .filter_map(leading_identifier)
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        _ => split_top_level(inside, b',')
+            .into_iter()
+            .filter_map(|p| leading_identifier(p.trim_start().trim_start_matches("...")))
+            .collect(),
+    }
+}
+
+fn catch_binding(head: &str) -> Option<String> {
+    let (before, inside) = last_parens(head)?;
+    before
+        .trim_end()
+        .ends_with("catch")
+        .then(|| leading_identifier(inside))
+        .flatten()
+}
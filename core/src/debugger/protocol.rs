@@ -0,0 +1,166 @@
+// ==========================================================
+// 📡 调试协议 (每行一个 JSON，UTF-8)
+//
+// 请求:  {"id": 1, "command": "setBreakpoints", "arguments": {"source": "index.ts", "lines": [12]}}
+// 响应:  {"id": 1, "success": true, "body": {...}}  /  {"id": 1, "success": false, "message": "..."}
+// 事件:  {"event": "paused", "body": {"reason": "breakpoint", "location": {"line": 120, "original": {...}}}}
+//
+// 命令:
+//   authenticate {token}              连接后的第一条请求，令牌不对直接断开
+//   setBreakpoints {source?, lines}   source 为空表示生成的 script.js 的行号，否则按 source map 映射
+//   setPauseOnExceptions {mode}       none / uncaught / all (all 包括被 catch 的异常)
+//   pause / continue / stepIn / stepOver / stepOut
+//   evaluate {expression}             暂停时在当前作用域求值
+//   variables                         当前作用域的变量 (脚本已退出时为全局变量)
+//   properties {expression}           对象的属性
+//   stackTrace / status
+// 事件: attached / paused / resumed / detached
+// ==========================================================
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+
+use crate::js_engine::source_map::OriginalPosition;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub id: u64,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", content = "arguments", rename_all = "camelCase")]
+pub enum Command {
+    Authenticate {
+        token: String,
+    },
+    SetBreakpoints {
+        #[serde(default)]
+        source: Option<String>,
+        lines: Vec<u32>,
+    },
+    SetPauseOnExceptions {
+        mode: ExceptionMode,
+    },
+    Pause,
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+    Evaluate {
+        expression: String,
+    },
+    Variables,
+    Properties {
+        expression: String,
+    },
+    StackTrace,
+    Status,
+}
+
+impl Command {
+    /// 只能在脚本暂停时、由脚本线程处理的命令
+    pub fn needs_paused_script(&self) -> bool {
+        matches!(
+            self,
+            Command::Continue
+                | Command::StepIn
+                | Command::StepOver
+                | Command::StepOut
+                | Command::Evaluate { .. }
+                | Command::Variables
+                | Command::Properties { .. }
+                | Command::StackTrace
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExceptionMode {
+    #[default]
+    None,
+    /// 没有被 catch、导致脚本失败的异常 (脚本已退出到顶层，只能查看全局变量)
+    Uncaught,
+    /// 另外在每个 catch 块开头暂停
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseReason {
+    Entry,
+    Breakpoint,
+    Step,
+    Pause,
+    Exception,
+}
+
+/// 源码位置 (生成代码的行 + source map 映射后的源位置)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub line: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<OriginalLocation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OriginalLocation {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl From<OriginalPosition> for OriginalLocation {
+    fn from(p: OriginalPosition) -> Self {
+        Self {
+            source: p.source,
+            line: p.line,
+            column: p.column,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointInfo {
+    /// 请求的行号
+    pub line: u32,
+    pub verified: bool,
+    /// 实际停下的生成代码行 (请求行没有语句时顺延到下一条语句)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_line: Option<u32>,
+}
+
+/// 变量 / 求值结果的展示形式
+#[derive(Debug, Clone, Serialize)]
+pub struct VariableInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StackFrameInfo {
+    pub function: String,
+    pub file: String,
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+pub fn success(id: u64, body: Json) -> String {
+    serde_json::json!({ "id": id, "success": true, "body": body }).to_string()
+}
+
+pub fn failure(id: u64, message: &str) -> String {
+    serde_json::json!({ "id": id, "success": false, "message": message }).to_string()
+}
+
+pub fn event(name: &str, body: Json) -> String {
+    serde_json::json!({ "event": name, "body": body }).to_string()
+}
@@ -0,0 +1,137 @@
+// ==========================================================
+// 🔌 调试服务的网络部分: 独立线程 + tokio 运行时
+// 一次只服务一个客户端，后来的连接排队等前一个断开
+// 第一行必须是 authenticate 请求，令牌不对 (或超时没发) 直接断开
+// ==========================================================
+
+use std::{net::TcpListener as StdTcpListener, sync::Arc, thread, time::Duration};
+
+use log::{info, warn};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, Notify},
+};
+
+use super::{
+    protocol::{self, Command, Request},
+    DebugError,
+};
+
+/// 连上后多久内必须完成握手
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(super) struct ServerHandle {
+    pub port: u16,
+    shutdown: Arc<Notify>,
+}
+
+impl ServerHandle {
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+}
+
+pub(super) fn spawn(port: u16) -> Result<ServerHandle, DebugError> {
+    let io_error = |e: std::io::Error| DebugError::IoError(e.to_string());
+    let listener = StdTcpListener::bind(("127.0.0.1", port)).map_err(io_error)?;
+    listener.set_nonblocking(true).map_err(io_error)?;
+    let port = listener.local_addr().map_err(io_error)?.port();
+    let shutdown = Arc::new(Notify::new());
+
+    let stop = shutdown.clone();
+    thread::Builder::new()
+        .name("touch-debugger".into())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            rt.block_on(async move {
+                let listener = match TcpListener::from_std(listener) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        warn!("Debug server failed: {}", e);
+                        return;
+                    }
+                };
+                tokio::select! {
+                    _ = stop.notified() => {}
+                    _ = serve(listener) => {}
+                }
+            });
+        })
+        .map_err(io_error)?;
+
+    Ok(ServerHandle { port, shutdown })
+}
+
+async fn serve(listener: TcpListener) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                info!("🐞 Debug connection from {}", addr);
+                client_session(stream).await;
+            }
+            Err(e) => warn!("Debug server accept failed: {}", e),
+        }
+    }
+}
+
+async fn client_session(stream: TcpStream) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    let handshake = tokio::time::timeout(HANDSHAKE_TIMEOUT, lines.next_line()).await;
+    let id = match handshake {
+        Ok(Ok(Some(line))) => match serde_json::from_str::<Request>(&line) {
+            Ok(Request {
+                id,
+                command: Command::Authenticate { token },
+            }) if super::authenticate(&token) => Some(id),
+            Ok(Request { id, .. }) => {
+                let line = protocol::failure(id, "Authentication failed");
+                let _ = write.write_all(format!("{}\n", line).as_bytes()).await;
+                None
+            }
+            Err(_) => None,
+        },
+        _ => None,
+    };
+    let Some(id) = id else {
+        warn!("🐞 Debug connection rejected: missing or invalid token");
+        return;
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    // 先排队握手的响应，connect 之后才会有 attached 等事件
+    let _ = tx.send(protocol::success(id, serde_json::json!({})));
+    if !super::connect(tx) {
+        let line = protocol::failure(0, "Another debug client is already connected");
+        let _ = write.write_all(format!("{}\n", line).as_bytes()).await;
+        return;
+    }
+
+    let writer = async move {
+        while let Some(line) = rx.recv().await {
+            if write
+                .write_all(format!("{}\n", line).as_bytes())
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    };
+    let reader = async move {
+        while let Ok(Some(line)) = lines.next_line().await {
+            if !line.trim().is_empty() {
+                super::handle_line(&line);
+            }
+        }
+    };
+    tokio::select! {
+        _ = writer => {}
+        _ = reader => {}
+    }
+    super::disconnect();
+}
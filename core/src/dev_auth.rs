@@ -0,0 +1,36 @@
+// ==================================================
+// 🔑 开发服务 (调试器 / 热重载推送) 的会话令牌
+// 端口只监听 127.0.0.1，但同一台设备上的任何应用都能连上来，
+// 所以连接必须先出示宿主开启服务时给的令牌 (每次开启开发模式随机生成)
+// ==================================================
+
+/// 令牌最短长度，太短的令牌可以被本机应用穷举
+pub const MIN_TOKEN_LEN: usize = 16;
+
+pub fn is_strong(token: &str) -> bool {
+    token.len() >= MIN_TOKEN_LEN
+}
+
+/// 逐字节比较完整个令牌，耗时不随第一个不同字节的位置变化
+pub fn matches(expected: &str, given: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), given.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_checks() {
+        assert!(is_strong("0123456789abcdef"));
+        assert!(!is_strong("short"));
+        assert!(matches("0123456789abcdef", "0123456789abcdef"));
+        assert!(!matches("0123456789abcdef", "0123456789abcdeF"));
+        assert!(!matches("0123456789abcdef", "0123456789abcde"));
+        assert!(!matches("0123456789abcdef", ""));
+    }
+}
@@ -0,0 +1,97 @@
+// ==========================================================
+// 📡 宿主事件 (新画面 / 前台应用切换 / 通知)
+// - 画面: 截图来源 (Root Server / 无障碍录屏) 每写入一帧调用 frame_updated
+// - 前台应用、通知: 宿主通过 report_foreground_app / report_notification 上报
+//   (Android 端来自 AccessibilityEvent 和 NotificationListenerService)
+// 每个脚本运行时各自订阅 (api::events)，这里只负责广播
+// 画面用 watch (只保留最新一帧，脚本处理慢时自动合并)，其余用 broadcast (按顺序逐条)
+// ==========================================================
+
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use tokio::sync::{broadcast, watch};
+
+/// broadcast 的容量，慢消费者超出后丢弃最旧的事件
+const EVENT_CAPACITY: usize = 64;
+
+/// 最新一帧的信息 (像素在 core::SCREEN_BUFFER 里)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameInfo {
+    /// 帧序号，从 1 开始递增
+    pub seq: u64,
+    pub width: usize,
+    pub height: usize,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForegroundApp {
+    pub package: String,
+    pub activity: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum HostEvent {
+    Foreground(ForegroundApp),
+    Notification {
+        package: String,
+        title: String,
+        text: String,
+    },
+}
+
+lazy_static! {
+    static ref FRAMES: watch::Sender<FrameInfo> = watch::channel(FrameInfo::default()).0;
+    static ref EVENTS: broadcast::Sender<HostEvent> = broadcast::channel(EVENT_CAPACITY).0;
+    // 最近一次上报的前台应用
+    static ref FOREGROUND: Mutex<Option<ForegroundApp>> = Mutex::new(None);
+}
+
+/// 截图缓冲区写入了新的一帧
+pub fn frame_updated(width: usize, height: usize, scale: f32) {
+    FRAMES.send_modify(|frame| {
+        *frame = FrameInfo {
+            seq: frame.seq + 1,
+            width,
+            height,
+            scale,
+        }
+    });
+}
+
+/// 前台应用 / 页面变化，和上次相同时不广播
+pub fn foreground_changed(package: String, activity: Option<String>) {
+    let app = ForegroundApp { package, activity };
+    {
+        let mut current = FOREGROUND.lock().unwrap();
+        if current.as_ref() == Some(&app) {
+            return;
+        }
+        *current = Some(app.clone());
+    }
+    // 没有订阅者时 send 返回 Err，忽略即可
+    let _ = EVENTS.send(HostEvent::Foreground(app));
+}
+
+pub fn notification_posted(package: String, title: String, text: String) {
+    let _ = EVENTS.send(HostEvent::Notification {
+        package,
+        title,
+        text,
+    });
+}
+
+/// 最近一次上报的前台应用 (宿主没上报过为 None)
+pub fn foreground() -> Option<ForegroundApp> {
+    FOREGROUND.lock().unwrap().clone()
+}
+
+/// 订阅画面 (订阅前的帧视为已读)
+pub fn subscribe_frames() -> watch::Receiver<FrameInfo> {
+    FRAMES.subscribe()
+}
+
+pub fn subscribe() -> broadcast::Receiver<HostEvent> {
+    EVENTS.subscribe()
+}
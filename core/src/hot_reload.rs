@@ -0,0 +1,531 @@
+// ==========================================================
+// 🔥 开发模式: 脚本热重载
+// 两种触发方式 (可以同时开):
+//   1. 监听本地路径: script.js (连同 script.js.map / manifest.json) 或脚本包目录 / .zip，
+//      文件变化后重载
+//   2. 本地端口接收 tools/dev.js 推送的脚本 (电脑端 adb forward tcp:9230 tcp:9230)
+//      每行一个 JSON: {"token": "...", "files": {"script.js": "...", "script.js.map": "..."}}
+//      token 必须和开启时宿主给的一致 (dev_auth.rs)，否则本机任何应用都能推送脚本以 Root 运行
+//      带 manifest.json 时按内存脚本包运行 (只支持文本文件)
+//      回复: {"success": true, "scriptId": 3, "reloadMs": 12} / {"success": false, "message": "..."}
+//
+// 重载 = 停掉当前主脚本并等它的线程退出，再用新内容重新启动；变化的文件和耗时通过日志回调 (PlatformLogger) 报告
+// 配置默认保留 (开发期间在配置页改过的值继续生效)，preserve_config = false 时恢复到开启开发模式时的配置
+// ==========================================================
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    net::TcpListener as StdTcpListener,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{tcp::OwnedReadHalf, TcpListener, TcpStream},
+    sync::{Notify, Semaphore},
+};
+
+use crate::{
+    bundle::{BundleError, ScriptBundle, MANIFEST_FILE},
+    config::CONFIG,
+    js_engine::RuntimeLimits,
+    registry::{self, ScriptId, ScriptRole},
+    uniffi_binding,
+};
+
+/// 监听路径的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 发现变化后再等一会儿，让打包工具把 script.js / .map 都写完
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+const SCRIPT_FILE: &str = "script.js";
+const SOURCE_MAP_FILE: &str = "script.js.map";
+/// 重载时等待旧脚本退出的上限
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+/// 推送连接上每一行 (一次推送) 必须在这个时间内收完，空闲的连接也会被关掉
+const PUSH_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// 一次推送 (一行 JSON) 的大小上限
+const MAX_PUSH_BYTES: u64 = 16 * 1024 * 1024;
+/// 同时处理的推送连接数，占满时新连接排队等待
+const MAX_PUSH_SESSIONS: usize = 2;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum HotReloadError {
+    #[error("Hot reload is already running")]
+    AlreadyRunning,
+
+    #[error("Nothing to watch: set a watch path or a push port")]
+    NothingToWatch,
+
+    #[error("Watch path does not exist: {0}")]
+    PathNotFound(String),
+
+    #[error(
+        "Push port requires a token of at least {} characters",
+        crate::dev_auth::MIN_TOKEN_LEN
+    )]
+    WeakToken,
+
+    #[error("Failed to start hot reload: {0}")]
+    IoError(String),
+}
+
+/// 要重载的脚本
+enum DevSource {
+    /// 旧版单文件脚本 (+ source map / 清单)
+    Script {
+        script: String,
+        source_map: Option<String>,
+        manifest: Option<String>,
+    },
+    /// 磁盘上的脚本包 (目录或 .zip)
+    BundlePath(PathBuf),
+    /// 推送来的内存脚本包
+    BundleFiles(HashMap<String, Vec<u8>>),
+}
+
+struct Settings {
+    /// 推送要出示的令牌
+    token: String,
+    preserve_config: bool,
+    limits: Option<RuntimeLimits>,
+    /// 开启开发模式时的配置 (不保留配置时重载前恢复)
+    initial_config: String,
+}
+
+struct HotReload {
+    shutdown: Arc<Notify>,
+}
+
+lazy_static::lazy_static! {
+    static ref HOT_RELOAD: Mutex<Option<HotReload>> = Mutex::new(None);
+}
+
+/// 开启开发模式，返回推送端口 (push_port = 0 时随机分配；没开推送返回 None)
+/// 监听路径在开启时不会立即运行，第一次变化 (或推送) 时才启动脚本
+/// 开推送时必须给 token
+pub fn start(
+    watch_path: Option<String>,
+    push_port: Option<u16>,
+    token: Option<String>,
+    preserve_config: bool,
+    limits: Option<RuntimeLimits>,
+) -> Result<Option<u16>, HotReloadError> {
+    let mut guard = HOT_RELOAD.lock().unwrap();
+    if guard.is_some() {
+        return Err(HotReloadError::AlreadyRunning);
+    }
+    if watch_path.is_none() && push_port.is_none() {
+        return Err(HotReloadError::NothingToWatch);
+    }
+    let token = token.unwrap_or_default();
+    if push_port.is_some() && !crate::dev_auth::is_strong(&token) {
+        return Err(HotReloadError::WeakToken);
+    }
+    let watch_path = watch_path.map(PathBuf::from);
+    if let Some(path) = &watch_path {
+        if !path.exists() {
+            return Err(HotReloadError::PathNotFound(path.display().to_string()));
+        }
+    }
+
+    let io_error = |e: std::io::Error| HotReloadError::IoError(e.to_string());
+    let listener = match push_port {
+        Some(port) => {
+            let listener = StdTcpListener::bind(("127.0.0.1", port)).map_err(io_error)?;
+            listener.set_nonblocking(true).map_err(io_error)?;
+            Some(listener)
+        }
+        None => None,
+    };
+    let push_port = match &listener {
+        Some(listener) => Some(listener.local_addr().map_err(io_error)?.port()),
+        None => None,
+    };
+
+    let settings = Arc::new(Settings {
+        token,
+        preserve_config,
+        limits,
+        initial_config: CONFIG.lock().unwrap().snapshot().to_string(),
+    });
+    let shutdown = Arc::new(Notify::new());
+    let stop = shutdown.clone();
+    let watched = watch_path.clone();
+    thread::Builder::new()
+        .name("touch-hot-reload".into())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            rt.block_on(async move {
+                let watch = async {
+                    match &watched {
+                        Some(path) => watch(path, &settings).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                let push = async {
+                    match listener.map(TcpListener::from_std) {
+                        Some(Ok(listener)) => serve(listener, settings.clone()).await,
+                        Some(Err(e)) => warn!("Hot reload push server failed: {}", e),
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    _ = stop.notified() => {}
+                    _ = async { tokio::join!(watch, push) } => {}
+                }
+            });
+        })
+        .map_err(io_error)?;
+
+    report(format!(
+        "🔥 Hot reload enabled (watch: {}, push port: {})",
+        watch_path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "-".into()),
+        push_port
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "-".into()),
+    ));
+    *guard = Some(HotReload { shutdown });
+    Ok(push_port)
+}
+
+/// 关闭开发模式 (不会停掉正在运行的脚本)
+pub fn stop() {
+    if let Some(hot_reload) = HOT_RELOAD.lock().unwrap().take() {
+        hot_reload.shutdown.notify_one();
+        report("🔥 Hot reload disabled".into());
+    }
+}
+
+pub fn is_enabled() -> bool {
+    HOT_RELOAD.lock().unwrap().is_some()
+}
+
+/// 同时写日志和宿主的日志回调
+fn report(message: String) {
+    info!("{}", message);
+    if let Some(logger) = uniffi_binding::PLATFORM_LOGGER.lock().unwrap().as_ref() {
+        logger.log(message);
+    }
+}
+
+// ==========================================
+// 重载
+// ==========================================
+
+/// 停掉当前主脚本并等它退出，按需恢复配置，再启动新脚本
+fn reload(
+    source: DevSource,
+    changed: &[String],
+    settings: &Settings,
+) -> Result<(ScriptId, f64), String> {
+    let started = Instant::now();
+    // 旧脚本还在执行 (比如正卡在一段同步代码里) 时不能启动新的，否则两个脚本同时操作设备
+    if !registry::stop_all_and_wait(Some(ScriptRole::Main), STOP_TIMEOUT) {
+        let message = format!(
+            "previous script did not stop within {}s",
+            STOP_TIMEOUT.as_secs()
+        );
+        report(format!("❌ Hot reload failed: {}", message));
+        return Err(message);
+    }
+    if !settings.preserve_config {
+        let mut config = CONFIG.lock().unwrap();
+        config.clear();
+        if let Err(e) = config.merge_json(&settings.initial_config) {
+            warn!("Failed to restore config: {}", e);
+        }
+    }
+
+    let limits = settings.limits.clone();
+    let result = match source {
+        DevSource::Script {
+            script,
+            source_map,
+            manifest: None,
+        } => Ok(uniffi_binding::run_js_script(
+            script, limits, None, source_map,
+        )),
+        DevSource::Script {
+            script,
+            source_map,
+            manifest: Some(manifest),
+        } => {
+            uniffi_binding::run_js_script_with_manifest(script, manifest, limits, None, source_map)
+        }
+        DevSource::BundlePath(path) => {
+            uniffi_binding::run_script_bundle(path.display().to_string(), limits, None)
+        }
+        DevSource::BundleFiles(files) => ScriptBundle::from_files(files)
+            .and_then(|bundle| uniffi_binding::run_bundle(bundle, limits, None)),
+    };
+    let elapsed = (started.elapsed().as_secs_f64() * 10_000.0).round() / 10.0;
+    match result {
+        Ok(id) => {
+            report(format!(
+                "🔥 Reloaded script #{} in {:.1} ms, changed: {}",
+                id,
+                elapsed,
+                changed.join(", ")
+            ));
+            Ok((id, elapsed))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            report(format!("❌ Hot reload failed: {}", message));
+            Err(message)
+        }
+    }
+}
+
+// ==========================================
+// 监听本地路径
+// ==========================================
+
+/// 文件 -> (修改时间, 大小)
+type Snapshot = BTreeMap<String, (SystemTime, u64)>;
+
+async fn watch(path: &Path, settings: &Settings) {
+    let mut last = snapshot(path);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if snapshot(path) == last {
+            continue;
+        }
+        tokio::time::sleep(SETTLE_DELAY).await;
+        let current = snapshot(path);
+        let changed = diff(&last, &current);
+        last = current;
+        if changed.is_empty() {
+            continue;
+        }
+        match load_path(path) {
+            Ok(source) => {
+                let _ = reload(source, &changed, settings);
+            }
+            Err(e) => report(format!("❌ Hot reload failed: {}", e)),
+        }
+    }
+}
+
+/// 单文件脚本同时看 .map 和同目录的清单，目录递归扫描
+fn snapshot(path: &Path) -> Snapshot {
+    let mut files = Snapshot::new();
+    if path.is_dir() {
+        scan_dir(path, path, &mut files);
+    } else {
+        let mut candidates = vec![path.to_path_buf(), map_path(path)];
+        if is_script(path) {
+            candidates.extend(path.parent().map(|dir| dir.join(MANIFEST_FILE)));
+        }
+        for file in candidates {
+            if let Some(stamp) = stamp(&file) {
+                files.insert(file_name(&file), stamp);
+            }
+        }
+    }
+    files
+}
+
+fn scan_dir(root: &Path, dir: &Path, files: &mut Snapshot) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_dir(root, &path, files);
+        } else if let Some(stamp) = stamp(&path) {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.insert(relative.to_string_lossy().replace('\\', "/"), stamp);
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// 新增 / 修改 / 删除的文件
+fn diff(old: &Snapshot, new: &Snapshot) -> Vec<String> {
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(name, stamp)| old.get(*name) != Some(stamp))
+        .map(|(name, _)| name.clone())
+        .collect();
+    changed.extend(
+        old.keys()
+            .filter(|name| !new.contains_key(*name))
+            .map(|name| format!("{} (deleted)", name)),
+    );
+    changed
+}
+
+fn load_path(path: &Path) -> Result<DevSource, String> {
+    if !is_script(path) {
+        return Ok(DevSource::BundlePath(path.to_path_buf()));
+    }
+    let script = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let manifest = path
+        .parent()
+        .and_then(|dir| fs::read_to_string(dir.join(MANIFEST_FILE)).ok());
+    Ok(DevSource::Script {
+        script,
+        source_map: fs::read_to_string(map_path(path)).ok(),
+        manifest,
+    })
+}
+
+fn is_script(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "js")
+}
+
+fn map_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".map");
+    PathBuf::from(name)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// ==========================================
+// 接收推送
+// ==========================================
+
+#[derive(Deserialize)]
+struct Push {
+    #[serde(default)]
+    token: String,
+    files: HashMap<String, String>,
+}
+
+/// 每个连接一个任务，连接数有上限 (慢速 / 空闲的连接不会挡住正常的推送)
+async fn serve(listener: TcpListener, settings: Arc<Settings>) {
+    let sessions = Arc::new(Semaphore::new(MAX_PUSH_SESSIONS));
+    loop {
+        let Ok(permit) = sessions.clone().acquire_owned().await else {
+            return;
+        };
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let settings = settings.clone();
+                tokio::spawn(async move {
+                    push_session(stream, &settings).await;
+                    drop(permit);
+                });
+            }
+            Err(e) => warn!("Hot reload accept failed: {}", e),
+        }
+    }
+}
+
+/// 一个推送连接: 每行一次推送，读超时 / 超长 / 令牌不对时回复后关闭连接
+async fn push_session(stream: TcpStream, settings: &Settings) {
+    let (read, mut write) = stream.into_split();
+    let mut reader = BufReader::new(read);
+    loop {
+        let line = match tokio::time::timeout(PUSH_READ_TIMEOUT, read_push_line(&mut reader)).await
+        {
+            Ok(Ok(Some(line))) => line,
+            Ok(Ok(None)) => break,
+            Ok(Err(message)) => {
+                report(format!("❌ Hot reload push rejected: {}", message));
+                let response = json!({ "success": false, "message": message });
+                let _ = write.write_all(format!("{}\n", response).as_bytes()).await;
+                break;
+            }
+            // 超时: 空闲或发得太慢的连接直接关掉
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (result, close) = match parse_push(&line, &settings.token) {
+            Ok((source, changed)) => (reload(source, &changed, settings), false),
+            // 没通过校验 (格式不对 / 令牌不对) 的连接不再继续读
+            Err(message) => {
+                report(format!("❌ Hot reload push rejected: {}", message));
+                (Err(message), true)
+            }
+        };
+        let response = match result {
+            Ok((id, elapsed)) => json!({ "success": true, "scriptId": id, "reloadMs": elapsed }),
+            Err(message) => json!({ "success": false, "message": message }),
+        };
+        if write
+            .write_all(format!("{}\n", response).as_bytes())
+            .await
+            .is_err()
+            || close
+        {
+            break;
+        }
+    }
+}
+
+/// 读一行推送 (不含换行)，超过 MAX_PUSH_BYTES 报错；连接关闭返回 None
+async fn read_push_line(reader: &mut BufReader<OwnedReadHalf>) -> Result<Option<String>, String> {
+    let mut line = Vec::new();
+    (&mut *reader)
+        .take(MAX_PUSH_BYTES + 1)
+        .read_until(b'\n', &mut line)
+        .await
+        .map_err(|e| e.to_string())?;
+    if line.len() as u64 > MAX_PUSH_BYTES {
+        return Err(format!("Push exceeds {} bytes", MAX_PUSH_BYTES));
+    }
+    if line.is_empty() {
+        return Ok(None);
+    }
+    String::from_utf8(line)
+        .map(|line| Some(line.trim_end_matches(['\r', '\n']).to_string()))
+        .map_err(|_| "Push is not valid UTF-8".to_string())
+}
+
+/// 推送内容 -> (脚本, 推送的文件名)
+fn parse_push(line: &str, token: &str) -> Result<(DevSource, Vec<String>), String> {
+    let push: Push = serde_json::from_str(line).map_err(|e| format!("Invalid push: {}", e))?;
+    if !crate::dev_auth::matches(token, &push.token) {
+        return Err("Invalid token".into());
+    }
+    let mut changed: Vec<String> = push.files.keys().cloned().collect();
+    changed.sort();
+    let source = if push.files.contains_key(MANIFEST_FILE) {
+        DevSource::BundleFiles(
+            push.files
+                .into_iter()
+                .map(|(name, content)| (name, content.into_bytes()))
+                .collect(),
+        )
+    } else {
+        let mut files = push.files;
+        let script = files
+            .remove(SCRIPT_FILE)
+            .ok_or_else(|| BundleError::NotFound(SCRIPT_FILE.into()).to_string())?;
+        DevSource::Script {
+            script,
+            source_map: files.remove(SOURCE_MAP_FILE),
+            manifest: None,
+        }
+    };
+    Ok((source, changed))
+}
@@ -1,62 +1,474 @@
+use crate::constants::{
+    ACTION_IME_INPUT, ACTION_SET_CLIPBOARD, CLIPBOARD_RECEIVER_CLASS, DEFAULT_HOST_PACKAGE,
+};
+use crate::events::ForegroundApp;
 use crate::types::AccessibilityService;
-use std::process::Command;
+use crate::ui::{uiautomator, UiNode};
+use keys::{global_action_for, is_modifier, KeyAction, KeyEvent, KEYCODE_PASTE};
+use std::{
+    process::Command,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+pub mod apps;
+pub mod arbiter;
+pub mod humanize;
+pub mod keys;
+pub mod recorder;
+
+/// 输入动作失败的原因 (会以异常形式抛给 JS，code 字段对应变体名)
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum InputError {
+    #[error("Input controller not initialized")]
+    NotInitialized,
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Command failed: {0}")]
+    CommandFailed(String),
+
+    #[error("Unsupported in current mode: {0}")]
+    Unsupported(String),
+}
+
+impl InputError {
+    /// 给 JS 侧判断用的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            InputError::NotInitialized => "NOT_INITIALIZED",
+            InputError::PermissionDenied(_) => "PERMISSION_DENIED",
+            InputError::CommandFailed(_) => "COMMAND_FAILED",
+            InputError::Unsupported(_) => "UNSUPPORTED",
+        }
+    }
+}
+
+/// 当前控制器的运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum InputMode {
+    Root,
+    Accessibility,
+    /// 录制控制器 (桌面 dry run)，不碰设备
+    Recording,
+}
+
+/// 文字输入方式 (每次调用可单独指定)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TextInputMethod {
+    /// 纯 ASCII 走 Keyboard，否则走 Clipboard
+    Auto,
+    /// `input text`，只支持 ASCII
+    Keyboard,
+    /// 写剪贴板后发送粘贴键，支持 Unicode
+    Clipboard,
+    /// ADB Keyboard 输入法广播，支持 Unicode (需安装并启用该输入法)
+    Ime,
+}
+
+impl TextInputMethod {
+    /// 解析 JS 传入的名字 ("auto" / "keyboard" / "clipboard" / "ime")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "keyboard" => Some(Self::Keyboard),
+            "clipboard" => Some(Self::Clipboard),
+            "ime" => Some(Self::Ime),
+            _ => None,
+        }
+    }
+
+    /// Auto 落到具体方式
+    pub fn resolve(self, text: &str) -> Self {
+        match self {
+            Self::Auto if is_keyboard_text(text) => Self::Keyboard,
+            Self::Auto => Self::Clipboard,
+            other => other,
+        }
+    }
+}
+
+/// `input text` 只能打可见 ASCII 和空格
+fn is_keyboard_text(text: &str) -> bool {
+    text.chars().all(|c| c == ' ' || c.is_ascii_graphic())
+}
+
+/// 单引号包裹，内部的单引号转成 '\'' ，交给 sh 后原样还原
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
 
 /// 🎮 输入控制策略接口
 /// 无论是 Root 还是无障碍，都必须实现这些基础操作
 pub trait InputController: Send + Sync {
-    fn click(&self, x: i32, y: i32);
-    fn swipe(&self, points: &Vec<Vec<i32>>, duration_ms: u64);
-    fn input_text(&self, text: &str);
-    fn key_event(&self, key_code: i32);
-    fn shell(&self, cmd: &str); // 只有 Root 能真正执行，无障碍模式只记录日志
+    fn mode(&self) -> InputMode;
+    fn click(&self, x: i32, y: i32) -> Result<(), InputError>;
+    /// 按住指定时长后抬起，默认退化为普通点击
+    fn press(&self, x: i32, y: i32, _duration_ms: u64) -> Result<(), InputError> {
+        self.click(x, y)
+    }
+    /// 在矩形区域内点击，默认点中心 (拟人化装饰器会在区域内随机取点)
+    fn click_in_rect(
+        &self,
+        left: i32,
+        top: i32,
+        width: i32,
+        height: i32,
+    ) -> Result<(), InputError> {
+        self.click(left + width / 2, top + height / 2)
+    }
+    fn swipe(&self, points: &[Vec<i32>], duration_ms: u64) -> Result<(), InputError>;
+    fn input_text(&self, text: &str, method: TextInputMethod) -> Result<(), InputError>;
+    fn key_event(&self, event: &KeyEvent) -> Result<(), InputError>;
+    /// 执行 shell 并返回 stdout，只有 Root 能真正执行
+    fn shell(&self, cmd: &str) -> Result<String, InputError>;
+    /// 当前前台应用，识别不出 (锁屏等) 返回 None
+    fn foreground_app(&self) -> Result<Option<ForegroundApp>, InputError> {
+        Err(InputError::Unsupported("foreground app detection".into()))
+    }
+    /// 启动应用 (已在运行则切回前台)
+    fn launch_app(&self, package: &str) -> Result<(), InputError> {
+        Err(InputError::Unsupported(format!("launch {}", package)))
+    }
+    /// 强制停止应用
+    fn force_stop(&self, package: &str) -> Result<(), InputError> {
+        Err(InputError::Unsupported(format!("force-stop {}", package)))
+    }
+    /// 当前界面的节点树 (真实屏幕坐标)，暂时没有活动窗口返回 None
+    fn ui_snapshot(&self) -> Result<Option<UiNode>, InputError> {
+        Err(InputError::Unsupported("UI node tree".into()))
+    }
 }
 
 // ==================================================
 // 🚀 策略 A: Root 模式 (使用 su 命令)
 // ==================================================
-pub struct RootStrategy;
+// 长按阈值，对应 ViewConfiguration.getLongPressTimeout()
+const LONG_PRESS_MS: u128 = 500;
+
+struct HeldKey {
+    code: i32,
+    since: Instant,
+    // 按住期间是否有别的键被按下 (是则它只是组合键的一部分，抬起时不再单独发送)
+    combined: bool,
+}
+
+pub struct RootStrategy {
+    // `input` 命令不支持单独的按下/抬起，这里记录按住的键，
+    // 按住期间的其它按键组合成 keycombination，抬起时再决定发送普通按键还是长按
+    held_keys: Mutex<Vec<HeldKey>>,
+    // 宿主 App 的安装包名，am broadcast -n 定位剪贴板广播接收器用
+    host_package: String,
+}
+
+impl RootStrategy {
+    pub fn new() -> Self {
+        Self::with_host_package(DEFAULT_HOST_PACKAGE)
+    }
+
+    pub fn with_host_package(host_package: &str) -> Self {
+        Self {
+            held_keys: Mutex::new(Vec::new()),
+            host_package: host_package.to_string(),
+        }
+    }
+
+    /// 通过宿主的广播接收器写剪贴板
+    /// am broadcast 找不到接收器时也返回成功，靠接收器设置的 result 判断是否送达，
+    /// 否则后面的粘贴键会贴出剪贴板里原有的内容
+    fn set_clipboard(&self, text: &str) -> Result<(), InputError> {
+        let output = run_su(&clipboard_broadcast_command(&self.host_package, text))?;
+        if clipboard_delivered(&output) {
+            Ok(())
+        } else {
+            Err(InputError::CommandFailed(format!(
+                "clipboard receiver {}/{} did not respond: {}",
+                self.host_package,
+                CLIPBOARD_RECEIVER_CLASS,
+                output.trim()
+            )))
+        }
+    }
+
+    fn send_key(
+        &self,
+        code: i32,
+        long_press: bool,
+        repeat: u32,
+        meta: &[i32],
+    ) -> Result<(), InputError> {
+        let repeat = repeat.max(1) as usize;
+        let cmd = if meta.is_empty() {
+            let codes = vec![code.to_string(); repeat].join(" ");
+            if long_press {
+                format!("input keyevent --longpress {}", codes)
+            } else {
+                format!("input keyevent {}", codes)
+            }
+        } else {
+            // keycombination 需要 Android 13+
+            let mut keys: Vec<String> = meta.iter().map(|m| m.to_string()).collect();
+            keys.push(code.to_string());
+            let hold = if long_press { "-t 1000 " } else { "" };
+            vec![format!("input keycombination {}{}", hold, keys.join(" ")); repeat].join(" && ")
+        };
+        run_su(&cmd).map(|_| ())
+    }
+}
+
+impl Default for RootStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 通过 su 执行命令，区分 "拿不到 root" 和 "命令本身失败"
+pub fn run_su(cmd: &str) -> Result<String, InputError> {
+    let output = Command::new("su")
+        .arg("-c")
+        .arg(cmd)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => {
+                InputError::PermissionDenied(format!("su unavailable: {}", e))
+            }
+            _ => InputError::CommandFailed(format!("{}: {}", cmd, e)),
+        })?;
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let lower = stderr.to_lowercase();
+    if lower.contains("permission denied") || lower.contains("not allowed") {
+        Err(InputError::PermissionDenied(stderr))
+    } else {
+        Err(InputError::CommandFailed(format!(
+            "`{}` exited with {}: {}",
+            cmd, output.status, stderr
+        )))
+    }
+}
 
 impl InputController for RootStrategy {
-    fn click(&self, x: i32, y: i32) {
+    fn mode(&self) -> InputMode {
+        InputMode::Root
+    }
+
+    fn click(&self, x: i32, y: i32) -> Result<(), InputError> {
         // 建议未来优化为写 /dev/input/event，这里先保持 su 实现
-        let _ = Command::new("su")
-            .arg("-c")
-            .arg(format!("input tap {} {}", x, y))
-            .output();
+        run_su(&format!("input tap {} {}", x, y)).map(|_| ())
     }
 
-    fn swipe(&self, points: &Vec<Vec<i32>>, duration_ms: u64) {
+    fn press(&self, x: i32, y: i32, duration_ms: u64) -> Result<(), InputError> {
+        // 原地 swipe 即长按，时长可控
+        run_su(&format!(
+            "input swipe {} {} {} {} {}",
+            x, y, x, y, duration_ms
+        ))
+        .map(|_| ())
+    }
+
+    fn swipe(&self, points: &[Vec<i32>], duration_ms: u64) -> Result<(), InputError> {
         // Root 滑动命令: input swipe x1 y1 x2 y2 duration
         if points.len() < 2 {
-            return;
+            return Err(InputError::CommandFailed(
+                "swipe requires at least 2 points".into(),
+            ));
         }
-        let start = &points[0];
-        let end = &points[points.len() - 1]; // 简化处理，只取首尾，复杂路径需拆分
-        let _ = Command::new("su")
-            .arg("-c")
-            .arg(format!(
+        if points.len() == 2 {
+            let start = &points[0];
+            let end = &points[1];
+            return run_su(&format!(
                 "input swipe {} {} {} {} {}",
                 start[0], start[1], end[0], end[1], duration_ms
             ))
-            .output();
+            .map(|_| ());
+        }
+        // 多点轨迹: motionevent 拼出一次完整的按下-移动-抬起，保证是同一根手指
+        // 每条 input 命令都要启动 app_process，按启动开销抽样轨迹点，避免把滑动拖慢
+        let overhead = INPUT_OVERHEAD_MS.load(Ordering::Relaxed);
+        let motion = motion_event_script(points, duration_ms, overhead);
+        let started = Instant::now();
+        run_su(&motion.script)?;
+        let elapsed = started.elapsed().as_millis() as u64;
+        // 用实测耗时修正下一次的启动开销估计
+        let measured = elapsed.saturating_sub(motion.sleep_ms) / motion.events as u64;
+        INPUT_OVERHEAD_MS.store((overhead * 3 + measured) / 4, Ordering::Relaxed);
+        log::debug!(
+            "[Root] swipe of {} points: {} events, {}ms (requested {}ms)",
+            points.len(),
+            motion.events,
+            elapsed,
+            duration_ms
+        );
+        Ok(())
+    }
+
+    fn input_text(&self, text: &str, method: TextInputMethod) -> Result<(), InputError> {
+        let cmd = match method.resolve(text) {
+            TextInputMethod::Keyboard => {
+                if !is_keyboard_text(text) {
+                    return Err(InputError::Unsupported(
+                        "`input text` only accepts ASCII, use clipboard or ime".into(),
+                    ));
+                }
+                // input 命令把 %s 当作空格
+                format!("input text {}", shell_quote(&text.replace(' ', "%s")))
+            }
+            TextInputMethod::Clipboard => {
+                self.set_clipboard(text)?;
+                format!("input keyevent {}", KEYCODE_PASTE)
+            }
+            TextInputMethod::Ime => {
+                format!(
+                    "am broadcast -a {} --es msg {}",
+                    ACTION_IME_INPUT,
+                    shell_quote(text)
+                )
+            }
+            TextInputMethod::Auto => unreachable!("resolved above"),
+        };
+        run_su(&cmd).map(|_| ())
+    }
+
+    fn key_event(&self, event: &KeyEvent) -> Result<(), InputError> {
+        let mut held = self.held_keys.lock().unwrap();
+        match event.action {
+            KeyAction::Down => {
+                if !held.iter().any(|k| k.code == event.code) {
+                    held.push(HeldKey {
+                        code: event.code,
+                        since: Instant::now(),
+                        combined: false,
+                    });
+                }
+                Ok(())
+            }
+            KeyAction::Up => {
+                let Some(pos) = held.iter().position(|k| k.code == event.code) else {
+                    return Err(InputError::CommandFailed(format!(
+                        "key {} released without being pressed",
+                        event.code
+                    )));
+                };
+                let key = held.remove(pos);
+                if key.combined {
+                    return Ok(());
+                }
+                // 修饰键单独抬起时不发送，与真实键盘行为一致
+                if is_modifier(key.code) {
+                    return Ok(());
+                }
+                let long_press = key.since.elapsed().as_millis() >= LONG_PRESS_MS;
+                let meta: Vec<i32> = held.iter().map(|k| k.code).collect();
+                held.iter_mut().for_each(|k| k.combined = true);
+                self.send_key(key.code, long_press, 1, &meta)
+            }
+            KeyAction::Press => {
+                // 按住中的键 + 显式 meta 一起作为组合键
+                let mut meta: Vec<i32> = held.iter().map(|k| k.code).collect();
+                for m in &event.meta {
+                    if !meta.contains(m) {
+                        meta.push(*m);
+                    }
+                }
+                held.iter_mut().for_each(|k| k.combined = true);
+                self.send_key(event.code, event.long_press, event.repeat, &meta)
+            }
+        }
+    }
+
+    fn shell(&self, cmd: &str) -> Result<String, InputError> {
+        run_su(cmd)
     }
 
-    fn input_text(&self, text: &str) {
-        let _ = Command::new("su")
-            .arg("-c")
-            .arg(format!("input text \"{}\"", text))
-            .output();
+    fn foreground_app(&self) -> Result<Option<ForegroundApp>, InputError> {
+        run_su(apps::FOREGROUND_COMMAND).map(|out| apps::parse_foreground(&out))
     }
 
-    fn key_event(&self, key_code: i32) {
-        let _ = Command::new("su")
-            .arg("-c")
-            .arg(format!("input keyevent {}", key_code))
-            .output();
+    fn launch_app(&self, package: &str) -> Result<(), InputError> {
+        let output = run_su(&apps::launch_command(package))?;
+        if apps::launch_failed(&output) {
+            return Err(InputError::CommandFailed(format!(
+                "{} has no launcher activity",
+                package
+            )));
+        }
+        Ok(())
+    }
+
+    fn force_stop(&self, package: &str) -> Result<(), InputError> {
+        run_su(&apps::force_stop_command(package)).map(|_| ())
     }
 
-    fn shell(&self, cmd: &str) {
-        let _ = Command::new("su").arg("-c").arg(cmd).output();
+    fn ui_snapshot(&self) -> Result<Option<UiNode>, InputError> {
+        let xml = run_su(uiautomator::DUMP_COMMAND)?;
+        uiautomator::parse_dump(&xml)
+            .map_err(|e| InputError::CommandFailed(format!("uiautomator dump: {}", e)))
+    }
+}
+
+/// 接收器处理完会把 result 设为 RESULT_OK (-1)，没送达时是默认的 0
+const CLIPBOARD_RESULT_OK: &str = "result=-1";
+
+fn clipboard_broadcast_command(host_package: &str, text: &str) -> String {
+    format!(
+        "am broadcast -a {} -n {}/{} --es text {}",
+        ACTION_SET_CLIPBOARD,
+        host_package,
+        CLIPBOARD_RECEIVER_CLASS,
+        shell_quote(text)
+    )
+}
+
+fn clipboard_delivered(am_output: &str) -> bool {
+    am_output.contains(CLIPBOARD_RESULT_OK)
+}
+
+/// 一次 `input` 调用的启动开销估计 (毫秒)，每次多点滑动后按实测值修正
+static INPUT_OVERHEAD_MS: AtomicU64 = AtomicU64::new(150);
+
+/// 多点滑动的 su 脚本
+struct MotionScript {
+    script: String,
+    /// input motionevent 的条数
+    events: usize,
+    /// 脚本里 sleep 的总时长
+    sleep_ms: u64,
+}
+
+/// 把多点轨迹转换为一段 shell 脚本 (DOWN -> MOVE... -> UP)，全程不抬起手指
+/// 相邻两条命令之间至少隔一次启动开销: 点数按 duration_ms / overhead_ms 均匀抽样 (起点终点必留)，
+/// 开销之外剩下的时间均分成 sleep，使整个手势接近 duration_ms
+fn motion_event_script(points: &[Vec<i32>], duration_ms: u64, overhead_ms: u64) -> MotionScript {
+    let steps = points.len() - 1;
+    let max_gaps = duration_ms.checked_div(overhead_ms).unwrap_or(u64::MAX);
+    let gaps = steps.min(max_gaps.max(1) as usize);
+    let step_sleep = duration_ms.saturating_sub(gaps as u64 * overhead_ms) / gaps as u64;
+    let index = |i: usize| (i * steps + gaps / 2) / gaps;
+    let mut cmds = Vec::with_capacity(gaps * 2 + 1);
+    for i in 0..=gaps {
+        let p = &points[index(i)];
+        let action = match i {
+            0 => "DOWN",
+            _ if i == gaps => "UP",
+            _ => "MOVE",
+        };
+        cmds.push(format!("input motionevent {} {} {}", action, p[0], p[1]));
+        if i != gaps && step_sleep > 0 {
+            cmds.push(format!("sleep {:.3}", step_sleep as f64 / 1000.0));
+        }
+    }
+    MotionScript {
+        script: cmds.join("; "),
+        events: gaps + 1,
+        sleep_ms: step_sleep * gaps as u64,
     }
 }
 
@@ -75,41 +487,195 @@ impl AccessibilityStrategy {
 }
 
 impl InputController for AccessibilityStrategy {
-    fn click(&self, x: i32, y: i32) {
-        self.service.dispatch_click(x, y);
+    fn mode(&self) -> InputMode {
+        InputMode::Accessibility
     }
 
-    fn swipe(&self, points: &Vec<Vec<i32>>, duration_ms: u64) {
-        // 暂时只打印日志，需要你在 PlatformCallback 加接口
-        log::warn!(
-            "[Accessibility] Swipe requested: {:?} over {}ms",
-            points,
-            duration_ms
-        );
-        // self.callback.dispatch_swipe(...) // TODO: 需要扩展 servie 接口
+    fn click(&self, x: i32, y: i32) -> Result<(), InputError> {
+        if self.service.dispatch_click(x, y) {
+            Ok(())
+        } else {
+            Err(InputError::CommandFailed(format!(
+                "accessibility click ({}, {}) was not dispatched",
+                x, y
+            )))
+        }
     }
 
-    fn input_text(&self, text: &str) {
-        // 无障碍输入文字比较麻烦（需要粘贴板或AccessibilityNodeInfo），暂时 Log
-        let msg = format!("[Accessibility] Input text not fully implemented: {}", text);
+    fn swipe(&self, points: &[Vec<i32>], duration_ms: u64) -> Result<(), InputError> {
+        if points.len() < 2 {
+            return Err(InputError::CommandFailed(
+                "swipe requires at least 2 points".into(),
+            ));
+        }
+        if self.service.dispatch_swipe(points.to_vec(), duration_ms) {
+            Ok(())
+        } else {
+            Err(InputError::CommandFailed(format!(
+                "accessibility swipe over {} points was not dispatched",
+                points.len()
+            )))
+        }
+    }
 
-        log::warn!("input_text 功能待实现,暂时只打印日志: {}", msg);
+    fn input_text(&self, text: &str, method: TextInputMethod) -> Result<(), InputError> {
+        // 无障碍模式统一对焦点输入框 ACTION_SET_TEXT，天然支持 Unicode
+        if method == TextInputMethod::Ime {
+            return Err(InputError::Unsupported(
+                "ime input in Accessibility mode".into(),
+            ));
+        }
+        if self.service.set_text(text.to_string()) {
+            Ok(())
+        } else {
+            Err(InputError::CommandFailed(
+                "no focused editable node to set text".into(),
+            ))
+        }
     }
 
-    fn key_event(&self, key_code: i32) {
-        let msg = format!(
-            "[Accessibility] Key event {} not supported without Root",
-            key_code
-        );
-        log::warn!("key_event 功能待实现,暂时只打印日志: {}", msg);
+    fn key_event(&self, event: &KeyEvent) -> Result<(), InputError> {
+        // 无障碍只能用全局动作模拟少量按键，没有按下/抬起和组合键
+        if event.action != KeyAction::Press || !event.meta.is_empty() {
+            return Err(InputError::Unsupported(
+                "key down/up and key combos require Root".into(),
+            ));
+        }
+        let Some(action) = global_action_for(event.code, event.long_press) else {
+            return Err(InputError::Unsupported(format!(
+                "key {} has no global action in Accessibility mode",
+                event.code
+            )));
+        };
+        for _ in 0..event.repeat.max(1) {
+            if !self.service.perform_global_action(action) {
+                return Err(InputError::CommandFailed(format!(
+                    "global action {} was not performed",
+                    action
+                )));
+            }
+        }
+        Ok(())
     }
 
-    fn shell(&self, cmd: &str) {
-        let msg = format!(
-            "[Permission Denied] Cannot execute shell in Accessibility mode: {}",
+    fn shell(&self, cmd: &str) -> Result<String, InputError> {
+        Err(InputError::PermissionDenied(format!(
+            "cannot execute shell in Accessibility mode: {}",
             cmd
-        );
+        )))
+    }
+
+    fn foreground_app(&self) -> Result<Option<ForegroundApp>, InputError> {
+        Ok(self.service.current_package().map(|package| ForegroundApp {
+            package,
+            activity: self.service.current_activity(),
+        }))
+    }
+
+    fn launch_app(&self, package: &str) -> Result<(), InputError> {
+        if self.service.launch_app(package.to_string()) {
+            Ok(())
+        } else {
+            Err(InputError::CommandFailed(format!(
+                "{} is not installed or has no launcher activity",
+                package
+            )))
+        }
+    }
+
+    fn force_stop(&self, package: &str) -> Result<(), InputError> {
+        Err(InputError::PermissionDenied(format!(
+            "cannot force-stop {} in Accessibility mode",
+            package
+        )))
+    }
 
-        log::warn!("shell 功能待实现,暂时只打印日志: {}", msg);
+    fn ui_snapshot(&self) -> Result<Option<UiNode>, InputError> {
+        let Some(json) = self.service.dump_node_tree() else {
+            return Ok(None);
+        };
+        UiNode::from_json(&json)
+            .map(Some)
+            .map_err(|e| InputError::CommandFailed(format!("invalid node tree: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 21 个点的直线轨迹 (拟人化滑动默认 20 步)
+    fn path() -> Vec<Vec<i32>> {
+        (0..=20)
+            .map(|i| vec![100 + i * 40, 1500 - i * 50])
+            .collect()
+    }
+
+    fn actions(script: &str) -> Vec<&str> {
+        script
+            .split("; ")
+            .filter_map(|cmd| cmd.strip_prefix("input motionevent "))
+            .collect()
+    }
+
+    #[test]
+    fn motion_events_are_one_continuous_touch() {
+        for (duration, overhead) in [(200, 150), (400, 150), (1000, 100), (2000, 50), (500, 0)] {
+            let motion = motion_event_script(&path(), duration, overhead);
+            let events = actions(&motion.script);
+            assert_eq!(events.len(), motion.events);
+            assert_eq!(events[0], "DOWN 100 1500");
+            assert_eq!(*events.last().unwrap(), "UP 900 500");
+            // 只按下一次、抬起一次
+            assert_eq!(events.iter().filter(|e| e.starts_with("DOWN")).count(), 1);
+            assert_eq!(events.iter().filter(|e| e.starts_with("UP")).count(), 1);
+            assert!(events[1..events.len() - 1]
+                .iter()
+                .all(|e| e.starts_with("MOVE")));
+        }
+    }
+
+    #[test]
+    fn motion_events_fit_requested_duration() {
+        // 启动开销 + sleep 接近请求的时长
+        for (duration, overhead) in [(400, 150), (1000, 100), (2000, 50), (3000, 100)] {
+            let motion = motion_event_script(&path(), duration, overhead);
+            let gaps = motion.events as u64 - 1;
+            let total = gaps * overhead + motion.sleep_ms;
+            assert!(
+                total <= duration && duration - total < gaps.max(1),
+                "{}ms with {}ms overhead: {}",
+                duration,
+                overhead,
+                motion.script
+            );
+        }
+        // 比一次启动开销还短: 只保留起点和终点，不 sleep
+        let motion = motion_event_script(&path(), 100, 150);
+        assert_eq!(motion.events, 2);
+        assert_eq!(motion.sleep_ms, 0);
+        assert!(!motion.script.contains("sleep"));
+        // 开销可以忽略时每个点都保留
+        let motion = motion_event_script(&path(), 2000, 0);
+        assert_eq!(motion.events, 21);
+        assert_eq!(motion.sleep_ms, 2000);
+    }
+
+    #[test]
+    fn clipboard_broadcast_targets_installed_package() {
+        let cmd = clipboard_broadcast_command("org.eu.freex.touchhelper", "它's");
+        assert_eq!(
+            cmd,
+            "am broadcast -a org.eu.freex.SET_CLIPBOARD \
+             -n org.eu.freex.touchhelper/org.eu.freex.app.ClipboardReceiver --es text '它'\\''s'"
+        );
+        assert!(clipboard_delivered(
+            "Broadcasting: Intent { act=org.eu.freex.SET_CLIPBOARD flg=0x400000 }\n\
+             Broadcast completed: result=-1\n"
+        ));
+        assert!(!clipboard_delivered(
+            "Broadcasting: Intent { act=org.eu.freex.SET_CLIPBOARD flg=0x400000 }\n\
+             Broadcast completed: result=0\n"
+        ));
     }
 }
@@ -0,0 +1,211 @@
+// ==================================================
+// 📱 前台应用检测 / 启动 / 停止 (Root 模式的命令和输出解析)
+// 不同 Android 版本 dumpsys 的字段名不一样，按优先级依次找:
+//   topResumedActivity=ActivityRecord{b5a3d0 u0 com.game/.MainActivity t8}     (12+)
+//   ResumedActivity: ActivityRecord{c0ffee u0 com.game/.MainActivity t123}      (10 ~ 11)
+//   mResumedActivity: ActivityRecord{c0ffee u0 com.game/.MainActivity t123}     (9 及以下)
+//   mFocusedApp=ActivityRecord{...} / AppWindowToken{... ActivityRecord{...}}  (dumpsys window)
+//   mCurrentFocus=Window{2f3b1c u0 com.game/com.game.MainActivity}
+// 锁屏 / 对话框时 mCurrentFocus 可能没有 Activity (如 "StatusBar")，排在最后
+// ==================================================
+
+use crate::events::ForegroundApp;
+
+/// 取前台 Activity 用的命令 (grep 没匹配时也返回成功，交给解析判断)
+pub const FOREGROUND_COMMAND: &str =
+    "{ dumpsys activity activities; dumpsys window; } 2>/dev/null \
+     | grep -E 'ResumedActivity|mFocusedApp|mCurrentFocus' || true";
+
+/// 按优先级排列的字段名
+const FOREGROUND_KEYS: &[&str] = &[
+    "topResumedActivity",
+    "mTopResumedActivity",
+    "mResumedActivity",
+    "ResumedActivity",
+    "mFocusedApp",
+    "mCurrentFocus",
+];
+
+/// 从 dumpsys 输出里解析前台应用，找不到返回 None
+pub fn parse_foreground(dumpsys: &str) -> Option<ForegroundApp> {
+    FOREGROUND_KEYS.iter().find_map(|key| {
+        dumpsys
+            .lines()
+            .map(str::trim_start)
+            .filter(|line| {
+                line.strip_prefix(key)
+                    .is_some_and(|rest| rest.starts_with(['=', ':']))
+            })
+            .find_map(parse_component_in)
+    })
+}
+
+/// 在一行里找第一个 "包名/Activity" 形式的组件名
+fn parse_component_in(line: &str) -> Option<ForegroundApp> {
+    line.split(|c: char| c.is_whitespace() || c == '{' || c == '}')
+        .find_map(parse_component)
+}
+
+/// "com.game/.MainActivity" -> (com.game, com.game.MainActivity)
+pub fn parse_component(token: &str) -> Option<ForegroundApp> {
+    let (package, activity) = token.split_once('/')?;
+    if !is_valid_package(package) || activity.is_empty() {
+        return None;
+    }
+    let activity = match activity.strip_prefix('.') {
+        Some(short) => format!("{}.{}", package, short),
+        None => activity.to_string(),
+    };
+    Some(ForegroundApp {
+        package: package.to_string(),
+        activity: Some(activity),
+    })
+}
+
+/// 包名: 至少两段，每段字母开头，只含字母数字下划线 (同时保证拼进 shell 命令是安全的)
+pub fn is_valid_package(name: &str) -> bool {
+    let mut segments = 0;
+    for segment in name.split('.') {
+        let mut chars = segment.chars();
+        if !chars.next().is_some_and(|c| c.is_ascii_alphabetic()) {
+            return false;
+        }
+        if !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return false;
+        }
+        segments += 1;
+    }
+    segments >= 2
+}
+
+/// 启动应用的桌面入口 (和点图标一样，已在运行则切回前台)
+pub fn launch_command(package: &str) -> String {
+    format!(
+        "monkey -p {} -c android.intent.category.LAUNCHER 1",
+        package
+    )
+}
+
+/// monkey 找不到入口时退出码也可能是 0，看输出判断
+pub fn launch_failed(output: &str) -> bool {
+    output.contains("No activities found to run") || output.contains("monkey aborted")
+}
+
+pub fn force_stop_command(package: &str) -> String {
+    format!("am force-stop {}", package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // FOREGROUND_COMMAND 在各版本真机上的输出 (grep 之后)
+
+    /// Android 14 (Pixel 7)，微信在前台
+    const ANDROID_14: &str = "    topResumedActivity=ActivityRecord{8a1b2c3 u0 com.tencent.mm/.ui.LauncherUI t1234}
+  ResumedActivity: ActivityRecord{8a1b2c3 u0 com.tencent.mm/.ui.LauncherUI t1234}
+    mLastResumedActivity=ActivityRecord{f00d u0 com.android.launcher3/.uioverrides.QuickstepLauncher t1}
+  mFocusedApp=ActivityRecord{8a1b2c3 u0 com.tencent.mm/.ui.LauncherUI t1234}
+  mCurrentFocus=Window{d3c1e0a u0 com.tencent.mm/com.tencent.mm.ui.LauncherUI}
+";
+
+    /// Android 11 (小米 MIUI 12.5)，Activity 名不是包名开头
+    const ANDROID_11: &str = "  mLastResumedActivity: ActivityRecord{3c8e5 u0 com.miui.home/.launcher.Launcher t2}
+  ResumedActivity: ActivityRecord{5ee1d4a u0 com.miHoYo.Yuanshen/com.miHoYo.GetMobileInfo.MainActivity t57}
+  mCurrentFocus=Window{1f2e3d u0 com.miHoYo.Yuanshen/com.miHoYo.GetMobileInfo.MainActivity}
+  mFocusedApp=ActivityRecord{5ee1d4a u0 com.miHoYo.Yuanshen/com.miHoYo.GetMobileInfo.MainActivity t57}
+";
+
+    /// Android 9 (模拟器)，mFocusedApp 是 AppWindowToken 包着 ActivityRecord
+    const ANDROID_9: &str = "    mResumedActivity: ActivityRecord{4e51a0 u0 com.android.settings/.Settings t45}
+  mFocusedApp=AppWindowToken{5c8f1e token=Token{a23b4c ActivityRecord{4e51a0 u0 com.android.settings/.Settings t45}}}
+  mCurrentFocus=Window{7d8e9f u0 com.android.settings/com.android.settings.Settings}
+";
+
+    /// Android 7 (老设备): activities 里只有 mFocusedActivity (没被 grep 到)，只能靠 window
+    const ANDROID_7: &str = "  mFocusedApp=AppWindowToken{2b3c4d token=Token{9a8b7c ActivityRecord{6d5e4f u0 com.example.game/com.unity3d.player.UnityPlayerActivity t12}}}
+  mCurrentFocus=Window{1a2b3c u0 com.example.game/com.unity3d.player.UnityPlayerActivity}
+";
+
+    /// 锁屏: 没有 resumed Activity，焦点是通知栏
+    const LOCKED: &str = "  mFocusedApp=null
+  mCurrentFocus=Window{6d3e2a u0 NotificationShade}
+";
+
+    /// 应用崩溃弹窗盖在前面: 焦点窗口不是 Activity，以 resumed 为准
+    const CRASH_DIALOG: &str =
+        "  ResumedActivity: ActivityRecord{77aa u0 com.example.game/.MainActivity t9}
+  mCurrentFocus=Window{88bb u0 Application Error: com.example.game}
+";
+
+    fn app(package: &str, activity: &str) -> Option<ForegroundApp> {
+        Some(ForegroundApp {
+            package: package.into(),
+            activity: Some(activity.into()),
+        })
+    }
+
+    #[test]
+    fn parses_foreground_across_versions() {
+        assert_eq!(
+            parse_foreground(ANDROID_14),
+            app("com.tencent.mm", "com.tencent.mm.ui.LauncherUI")
+        );
+        assert_eq!(
+            parse_foreground(ANDROID_11),
+            app(
+                "com.miHoYo.Yuanshen",
+                "com.miHoYo.GetMobileInfo.MainActivity"
+            )
+        );
+        assert_eq!(
+            parse_foreground(ANDROID_9),
+            app("com.android.settings", "com.android.settings.Settings")
+        );
+        assert_eq!(
+            parse_foreground(ANDROID_7),
+            app("com.example.game", "com.unity3d.player.UnityPlayerActivity")
+        );
+        assert_eq!(
+            parse_foreground(CRASH_DIALOG),
+            app("com.example.game", "com.example.game.MainActivity")
+        );
+    }
+
+    #[test]
+    fn no_focus_is_none() {
+        assert_eq!(parse_foreground(""), None);
+        assert_eq!(parse_foreground("\n"), None);
+        assert_eq!(parse_foreground(LOCKED), None);
+        assert_eq!(parse_foreground("  mCurrentFocus=null\n"), None);
+    }
+
+    #[test]
+    fn component_and_package_validation() {
+        assert_eq!(
+            parse_component("com.game/.ui.Main"),
+            app("com.game", "com.game.ui.Main")
+        );
+        assert_eq!(parse_component("com.game/"), None);
+        assert_eq!(parse_component("game/.Main"), None);
+        assert_eq!(parse_component("t1234"), None);
+
+        assert!(is_valid_package("com.miHoYo.Yuanshen"));
+        assert!(is_valid_package("org.eu.freex.touch_helper"));
+        assert!(!is_valid_package("com"));
+        assert!(!is_valid_package("com..game"));
+        assert!(!is_valid_package("com.1game"));
+        assert!(!is_valid_package("com.game;reboot"));
+        assert!(!is_valid_package("com.game $(id)"));
+    }
+
+    #[test]
+    fn detects_launch_failure() {
+        assert!(launch_failed(
+            "  bash arg: -p\n  bash arg: com.missing.app\n** No activities found to run, monkey aborted.\n"
+        ));
+        assert!(!launch_failed(
+            "  bash arg: -p\n  bash arg: com.game\nEvents injected: 1\n## Network stats: elapsed time=12ms\n"
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+// ==========================================================
+// 🚥 输入仲裁 (多脚本同时运行时，谁能操作设备)
+//
+// Shared:   不仲裁，各脚本的动作交替执行
+// Priority: 某个脚本操作设备后在 hold_ms 内 "持有" 设备，
+//           其它脚本的动作要等它释放；优先级更高的脚本可以直接抢占
+//           (看门狗关弹窗时打断主脚本)
+// 脚本也可以显式 Device.acquire() / Device.release() 持有一段完整操作
+// ==========================================================
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::registry::ScriptId;
+
+/// 默认持有时长: 一次点击后 300ms 内其它脚本不能插入
+pub const DEFAULT_HOLD_MS: u64 = 300;
+/// 持有时长上限 (宿主传入的 hold_ms 超过时按上限)
+pub const MAX_HOLD_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum InputPolicy {
+    Shared,
+    Priority { hold_ms: u64 },
+}
+
+impl Default for InputPolicy {
+    fn default() -> Self {
+        InputPolicy::Priority {
+            hold_ms: DEFAULT_HOLD_MS,
+        }
+    }
+}
+
+struct Holder {
+    script: ScriptId,
+    priority: i32,
+    /// None = 显式 acquire，直到 release
+    until: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Arbiter {
+    policy: InputPolicy,
+    holder: Option<Holder>,
+}
+
+lazy_static::lazy_static! {
+    static ref ARBITER: Mutex<Arbiter> = Mutex::new(Arbiter::default());
+}
+
+/// now + ms，溢出时为 None (当作一直持有到 release)
+fn deadline(now: Instant, ms: u64) -> Option<Instant> {
+    now.checked_add(Duration::from_millis(ms))
+}
+
+impl Holder {
+    fn expired(&self, now: Instant) -> bool {
+        self.until.is_some_and(|t| now >= t)
+    }
+}
+
+impl Arbiter {
+    /// script 现在能否持有设备
+    fn can_take(&self, script: ScriptId, priority: i32, now: Instant) -> bool {
+        match &self.holder {
+            None => true,
+            Some(h) => h.script == script || h.expired(now) || priority > h.priority,
+        }
+    }
+}
+
+pub fn set_policy(policy: InputPolicy) {
+    let policy = match policy {
+        InputPolicy::Priority { hold_ms } => InputPolicy::Priority {
+            hold_ms: hold_ms.min(MAX_HOLD_MS),
+        },
+        shared => shared,
+    };
+    let mut arbiter = ARBITER.lock().unwrap();
+    arbiter.policy = policy;
+    arbiter.holder = None;
+}
+
+pub fn policy() -> InputPolicy {
+    ARBITER.lock().unwrap().policy
+}
+
+/// 尝试执行一次动作: 允许则刷新持有期并返回 true，否则返回 false (调用方稍后重试)
+pub fn try_enter(script: ScriptId, priority: i32) -> bool {
+    let mut arbiter = ARBITER.lock().unwrap();
+    let InputPolicy::Priority { hold_ms } = arbiter.policy else {
+        return true;
+    };
+    let now = Instant::now();
+    if !arbiter.can_take(script, priority, now) {
+        return false;
+    }
+    let until = deadline(now, hold_ms);
+    match &mut arbiter.holder {
+        // 显式持有中，不缩短
+        Some(h) if h.script == script && h.until.is_none() => {}
+        Some(h) if h.script == script => h.until = until,
+        _ => {
+            arbiter.holder = Some(Holder {
+                script,
+                priority,
+                until,
+            })
+        }
+    }
+    true
+}
+
+/// 显式持有设备 (ms = None 直到 release)，被占用时返回 false
+pub fn try_acquire(script: ScriptId, priority: i32, ms: Option<u64>) -> bool {
+    let mut arbiter = ARBITER.lock().unwrap();
+    if arbiter.policy == InputPolicy::Shared {
+        return true;
+    }
+    let now = Instant::now();
+    if !arbiter.can_take(script, priority, now) {
+        return false;
+    }
+    arbiter.holder = Some(Holder {
+        script,
+        priority,
+        until: ms.and_then(|ms| deadline(now, ms)),
+    });
+    true
+}
+
+/// 释放 script 持有的设备 (脚本结束时自动调用)
+pub fn release_all(script: ScriptId) {
+    let mut arbiter = ARBITER.lock().unwrap();
+    if arbiter.holder.as_ref().is_some_and(|h| h.script == script) {
+        arbiter.holder = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ARBITER 是全局的，所有场景放在一个测试里顺序执行
+    #[test]
+    fn out_of_range_durations_do_not_panic() {
+        set_policy(InputPolicy::Priority { hold_ms: u64::MAX });
+        assert_eq!(
+            policy(),
+            InputPolicy::Priority {
+                hold_ms: MAX_HOLD_MS
+            }
+        );
+        assert!(try_enter(1, 0));
+        assert!(!try_enter(2, 0));
+        // 优先级更高的脚本照常抢占
+        assert!(try_enter(3, 10));
+        release_all(3);
+
+        // 溢出的 acquire 视为一直持有到 release
+        assert!(try_acquire(1, 0, Some(u64::MAX)));
+        assert!(!try_acquire(2, 0, Some(10)));
+        assert!(!try_enter(2, 0));
+        release_all(1);
+        assert!(try_acquire(2, 0, Some(0)));
+        assert!(try_enter(1, 0));
+
+        // 锁没有因为 panic 中毒
+        assert!(ARBITER.lock().is_ok());
+        set_policy(InputPolicy::default());
+    }
+}
@@ -0,0 +1,387 @@
+// ==================================================
+// 🧑 拟人化装饰器: 包裹任意 InputController
+// 点击随机偏移 + 随机按压时长 + 贝塞尔曲线滑动 + 偶发停顿
+// ==================================================
+
+use std::{sync::Mutex, thread, time::Duration};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::events::ForegroundApp;
+use crate::input::{keys::KeyEvent, InputController, InputError, InputMode, TextInputMethod};
+use crate::ui::UiNode;
+
+/// 拟人化参数 (Kotlin 侧可直接构造)
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct HumanizeConfig {
+    /// 随机种子，相同种子 + 相同操作序列 => 相同结果 (方便测试复现)
+    /// None 表示每次随机
+    pub seed: Option<u64>,
+    /// 单点点击的高斯抖动标准差 (像素)
+    pub tap_sigma_px: f64,
+    /// 单点点击的最大偏移 (像素)，超出部分会被截断
+    pub tap_max_offset_px: i32,
+    /// 按压时长下限 (毫秒)
+    pub press_min_ms: u64,
+    /// 按压时长上限 (毫秒)
+    pub press_max_ms: u64,
+    /// 滑动弯曲程度，控制点偏离直线的比例 (0 = 直线)
+    pub swipe_curvature: f64,
+    /// 滑动总时长的随机浮动比例 (0.2 => ±20%)
+    pub swipe_speed_variation: f64,
+    /// 滑动轨迹采样点数
+    pub swipe_steps: u32,
+    /// 每次操作前插入微停顿的概率 (0~1)
+    pub pause_probability: f64,
+    /// 微停顿时长下限 (毫秒)
+    pub pause_min_ms: u64,
+    /// 微停顿时长上限 (毫秒)
+    pub pause_max_ms: u64,
+}
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum HumanizeError {
+    #[error("Invalid humanize config: {0}")]
+    InvalidConfig(String),
+}
+
+impl HumanizeConfig {
+    /// 宿主传入的浮点参数必须是有限值 (NaN / 无穷会让随机数采样 panic)
+    pub fn validate(&self) -> Result<(), HumanizeError> {
+        let floats = [
+            ("tapSigmaPx", self.tap_sigma_px),
+            ("swipeCurvature", self.swipe_curvature),
+            ("swipeSpeedVariation", self.swipe_speed_variation),
+            ("pauseProbability", self.pause_probability),
+        ];
+        if let Some((name, value)) = floats.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(HumanizeError::InvalidConfig(format!(
+                "{} must be finite, got {}",
+                name, value
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.pause_probability) {
+            return Err(HumanizeError::InvalidConfig(format!(
+                "pauseProbability must be within 0..=1, got {}",
+                self.pause_probability
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HumanizeConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            tap_sigma_px: 3.0,
+            tap_max_offset_px: 8,
+            press_min_ms: 40,
+            press_max_ms: 120,
+            swipe_curvature: 0.15,
+            swipe_speed_variation: 0.2,
+            swipe_steps: 20,
+            pause_probability: 0.05,
+            pause_min_ms: 80,
+            pause_max_ms: 300,
+        }
+    }
+}
+
+pub struct HumanizedController {
+    inner: Box<dyn InputController>,
+    config: HumanizeConfig,
+    // trait 方法是 &self，随机数状态需要内部可变
+    rng: Mutex<StdRng>,
+}
+
+impl HumanizedController {
+    pub fn new(inner: Box<dyn InputController>, config: HumanizeConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            inner,
+            config,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// 取回被包裹的原始控制器
+    pub fn into_inner(self) -> Box<dyn InputController> {
+        self.inner
+    }
+
+    /// 单点抖动: 以 (x, y) 为中心的高斯偏移，并截断到最大偏移内
+    pub fn jitter_point(&self, x: i32, y: i32) -> (i32, i32) {
+        let max = self.config.tap_max_offset_px.max(0) as f64;
+        let mut rng = self.rng.lock().unwrap();
+        let dx = gaussian(&mut rng, 0.0, self.config.tap_sigma_px).clamp(-max, max);
+        let dy = gaussian(&mut rng, 0.0, self.config.tap_sigma_px).clamp(-max, max);
+        (x + dx.round() as i32, y + dy.round() as i32)
+    }
+
+    /// 矩形内取点: 以中心为均值、宽高的 1/6 为标准差 (≈99.7% 落在矩形内)，再截断到矩形内
+    pub fn point_in_rect(&self, left: i32, top: i32, width: i32, height: i32) -> (i32, i32) {
+        let w = width.max(1) as f64;
+        let h = height.max(1) as f64;
+        let mut rng = self.rng.lock().unwrap();
+        let x = gaussian(&mut rng, left as f64 + w / 2.0, w / 6.0)
+            .clamp(left as f64, left as f64 + w - 1.0);
+        let y = gaussian(&mut rng, top as f64 + h / 2.0, h / 6.0)
+            .clamp(top as f64, top as f64 + h - 1.0);
+        (x.round() as i32, y.round() as i32)
+    }
+
+    /// 随机按压时长
+    pub fn press_duration(&self) -> u64 {
+        let min = self.config.press_min_ms;
+        let max = self.config.press_max_ms.max(min);
+        self.rng.lock().unwrap().gen_range(min..=max)
+    }
+
+    /// 生成曲线轨迹: 三次贝塞尔 + ease-in-out 采样 (两头慢中间快)，返回 (轨迹点, 总时长)
+    pub fn curve_path(
+        &self,
+        start: (i32, i32),
+        end: (i32, i32),
+        duration_ms: u64,
+    ) -> (Vec<Vec<i32>>, u64) {
+        let mut rng = self.rng.lock().unwrap();
+
+        let (x0, y0) = (start.0 as f64, start.1 as f64);
+        let (x3, y3) = (end.0 as f64, end.1 as f64);
+        let (dx, dy) = (x3 - x0, y3 - y0);
+        let len = (dx * dx + dy * dy).sqrt();
+
+        // 法向量，控制点沿法向随机偏移
+        let (nx, ny) = if len > 0.0 {
+            (-dy / len, dx / len)
+        } else {
+            (0.0, 0.0)
+        };
+        let bend = self.config.swipe_curvature * len;
+        let o1 = rng.gen_range(-1.0..=1.0) * bend;
+        let o2 = rng.gen_range(-1.0..=1.0) * bend;
+        let (x1, y1) = (x0 + dx / 3.0 + nx * o1, y0 + dy / 3.0 + ny * o1);
+        let (x2, y2) = (x0 + dx * 2.0 / 3.0 + nx * o2, y0 + dy * 2.0 / 3.0 + ny * o2);
+
+        let steps = self.config.swipe_steps.max(2);
+        let mut points = Vec::with_capacity(steps as usize + 1);
+        for i in 0..=steps {
+            let t = ease_in_out(i as f64 / steps as f64);
+            let u = 1.0 - t;
+            let x = u * u * u * x0 + 3.0 * u * u * t * x1 + 3.0 * u * t * t * x2 + t * t * t * x3;
+            let y = u * u * u * y0 + 3.0 * u * u * t * y1 + 3.0 * u * t * t * y2 + t * t * t * y3;
+            points.push(vec![x.round() as i32, y.round() as i32]);
+        }
+
+        let v = self.config.swipe_speed_variation.abs();
+        let factor = if v > 0.0 {
+            1.0 + rng.gen_range(-v..=v)
+        } else {
+            1.0
+        };
+        let duration = ((duration_ms as f64) * factor).max(1.0) as u64;
+
+        (points, duration)
+    }
+
+    /// 按概率插入一次微停顿
+    fn maybe_pause(&self) {
+        let pause = {
+            let mut rng = self.rng.lock().unwrap();
+            let p = self.config.pause_probability.clamp(0.0, 1.0);
+            if rng.gen_bool(p) {
+                let min = self.config.pause_min_ms;
+                let max = self.config.pause_max_ms.max(min);
+                Some(rng.gen_range(min..=max))
+            } else {
+                None
+            }
+        };
+        if let Some(ms) = pause {
+            thread::sleep(Duration::from_millis(ms));
+        }
+    }
+}
+
+impl InputController for HumanizedController {
+    fn mode(&self) -> InputMode {
+        self.inner.mode()
+    }
+
+    fn click(&self, x: i32, y: i32) -> Result<(), InputError> {
+        self.maybe_pause();
+        let (jx, jy) = self.jitter_point(x, y);
+        let duration = self.press_duration();
+        self.inner.press(jx, jy, duration)
+    }
+
+    fn press(&self, x: i32, y: i32, duration_ms: u64) -> Result<(), InputError> {
+        self.maybe_pause();
+        let (jx, jy) = self.jitter_point(x, y);
+        self.inner.press(jx, jy, duration_ms)
+    }
+
+    fn click_in_rect(
+        &self,
+        left: i32,
+        top: i32,
+        width: i32,
+        height: i32,
+    ) -> Result<(), InputError> {
+        self.maybe_pause();
+        let (x, y) = self.point_in_rect(left, top, width, height);
+        let duration = self.press_duration();
+        self.inner.press(x, y, duration)
+    }
+
+    fn swipe(&self, points: &[Vec<i32>], duration_ms: u64) -> Result<(), InputError> {
+        self.maybe_pause();
+        if points.len() != 2 {
+            // 调用方已经给出完整轨迹，不再改写
+            return self.inner.swipe(points, duration_ms);
+        }
+        let start = self.jitter_point(points[0][0], points[0][1]);
+        let end = self.jitter_point(points[1][0], points[1][1]);
+        let (path, duration) = self.curve_path(start, end, duration_ms);
+        self.inner.swipe(&path, duration)
+    }
+
+    fn input_text(&self, text: &str, method: TextInputMethod) -> Result<(), InputError> {
+        self.maybe_pause();
+        self.inner.input_text(text, method)
+    }
+
+    fn key_event(&self, event: &KeyEvent) -> Result<(), InputError> {
+        self.maybe_pause();
+        self.inner.key_event(event)
+    }
+
+    fn shell(&self, cmd: &str) -> Result<String, InputError> {
+        self.inner.shell(cmd)
+    }
+
+    fn foreground_app(&self) -> Result<Option<ForegroundApp>, InputError> {
+        self.inner.foreground_app()
+    }
+
+    fn launch_app(&self, package: &str) -> Result<(), InputError> {
+        self.maybe_pause();
+        self.inner.launch_app(package)
+    }
+
+    fn force_stop(&self, package: &str) -> Result<(), InputError> {
+        self.inner.force_stop(package)
+    }
+
+    fn ui_snapshot(&self) -> Result<Option<UiNode>, InputError> {
+        self.inner.ui_snapshot()
+    }
+}
+
+fn gaussian(rng: &mut StdRng, mean: f64, std_dev: f64) -> f64 {
+    match Normal::new(mean, std_dev.abs()) {
+        Ok(n) => n.sample(rng),
+        Err(_) => mean,
+    }
+}
+
+// smoothstep: 速度两头慢中间快
+fn ease_in_out(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::recorder::{RecordedAction, RecordingController};
+
+    fn seeded(seed: u64) -> HumanizeConfig {
+        HumanizeConfig {
+            seed: Some(seed),
+            // 停顿只影响时间，不影响记录，测试里关掉免得 sleep
+            pause_probability: 0.0,
+            ..HumanizeConfig::default()
+        }
+    }
+
+    /// 跑一组固定操作，返回 (类型, 真实坐标, 时长) 序列
+    fn run(config: HumanizeConfig) -> Vec<(String, Vec<(i32, i32)>, Option<u64>)> {
+        let recorder = RecordingController::new();
+        let log = recorder.log_handle();
+        let ctrl = HumanizedController::new(Box::new(recorder), config);
+        for i in 0..5 {
+            ctrl.click(500 + i, 800).unwrap();
+            ctrl.click_in_rect(100, 200, 300, 120).unwrap();
+            ctrl.press(640, 360, 700).unwrap();
+            ctrl.swipe(&[vec![100, 1500], vec![900, 400]], 400).unwrap();
+        }
+        let actions: Vec<RecordedAction> = log.lock().unwrap().clone();
+        actions
+            .into_iter()
+            .map(|a| {
+                let points = a.points.iter().map(|p| (p.x, p.y)).collect();
+                (format!("{:?}", a.kind), points, a.duration_ms)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_reproduces_points_durations_and_paths() {
+        let a = run(seeded(42));
+        let b = run(seeded(42));
+        assert_eq!(a.len(), 20);
+        assert_eq!(a, b);
+        // 拟人化确实生效: 滑动被改写成多点曲线
+        assert!(a
+            .iter()
+            .any(|(kind, points, _)| kind == "Swipe" && points.len() > 2));
+    }
+
+    #[test]
+    fn different_seed_diverges() {
+        assert_ne!(run(seeded(1)), run(seeded(2)));
+    }
+
+    #[test]
+    fn click_in_rect_stays_inside() {
+        let ctrl = HumanizedController::new(Box::new(RecordingController::new()), seeded(7));
+        for _ in 0..500 {
+            let (x, y) = ctrl.point_in_rect(100, 200, 30, 10);
+            assert!((100..130).contains(&x) && (200..210).contains(&y));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_non_finite_values() {
+        assert!(HumanizeConfig::default().validate().is_ok());
+        for config in [
+            HumanizeConfig {
+                pause_probability: f64::NAN,
+                ..HumanizeConfig::default()
+            },
+            HumanizeConfig {
+                pause_probability: 1.5,
+                ..HumanizeConfig::default()
+            },
+            HumanizeConfig {
+                swipe_speed_variation: f64::INFINITY,
+                ..HumanizeConfig::default()
+            },
+            HumanizeConfig {
+                tap_sigma_px: f64::NEG_INFINITY,
+                ..HumanizeConfig::default()
+            },
+            HumanizeConfig {
+                swipe_curvature: f64::NAN,
+                ..HumanizeConfig::default()
+            },
+        ] {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+// ==================================================
+// ⌨️ 按键: 名字表 + 结构化按键事件
+// 键值与 android.view.KeyEvent.KEYCODE_* 一致
+// ==================================================
+
+pub const KEYCODE_HOME: i32 = 3;
+pub const KEYCODE_BACK: i32 = 4;
+pub const KEYCODE_POWER: i32 = 26;
+pub const KEYCODE_ALT_LEFT: i32 = 57;
+pub const KEYCODE_SHIFT_LEFT: i32 = 59;
+pub const KEYCODE_NOTIFICATION: i32 = 83;
+pub const KEYCODE_CTRL_LEFT: i32 = 113;
+pub const KEYCODE_META_LEFT: i32 = 117;
+pub const KEYCODE_SYSRQ: i32 = 120;
+pub const KEYCODE_APP_SWITCH: i32 = 187;
+pub const KEYCODE_PASTE: i32 = 279;
+
+/// 一次按键事件的连按次数上限 (Root 模式会拼成一条命令)
+pub const MAX_KEY_REPEAT: u32 = 100;
+
+// 常用键名 (不含 A-Z / 0-9 / F1-F12，这些按规则计算)
+const NAMED_KEYS: &[(&str, i32)] = &[
+    ("HOME", KEYCODE_HOME),
+    ("BACK", KEYCODE_BACK),
+    ("CALL", 5),
+    ("ENDCALL", 6),
+    ("STAR", 17),
+    ("POUND", 18),
+    ("DPAD_UP", 19),
+    ("DPAD_DOWN", 20),
+    ("DPAD_LEFT", 21),
+    ("DPAD_RIGHT", 22),
+    ("DPAD_CENTER", 23),
+    ("VOLUME_UP", 24),
+    ("VOLUME_DOWN", 25),
+    ("POWER", KEYCODE_POWER),
+    ("CAMERA", 27),
+    ("COMMA", 55),
+    ("PERIOD", 56),
+    ("ALT_LEFT", KEYCODE_ALT_LEFT),
+    ("ALT_RIGHT", 58),
+    ("SHIFT_LEFT", KEYCODE_SHIFT_LEFT),
+    ("SHIFT_RIGHT", 60),
+    ("TAB", 61),
+    ("SPACE", 62),
+    ("ENTER", 66),
+    ("DEL", 67),
+    ("MENU", 82),
+    ("NOTIFICATION", KEYCODE_NOTIFICATION),
+    ("SEARCH", 84),
+    ("MEDIA_PLAY_PAUSE", 85),
+    ("MEDIA_STOP", 86),
+    ("MEDIA_NEXT", 87),
+    ("MEDIA_PREVIOUS", 88),
+    ("PAGE_UP", 92),
+    ("PAGE_DOWN", 93),
+    ("ESCAPE", 111),
+    ("FORWARD_DEL", 112),
+    ("CTRL_LEFT", KEYCODE_CTRL_LEFT),
+    ("CTRL_RIGHT", 114),
+    ("META_LEFT", KEYCODE_META_LEFT),
+    ("META_RIGHT", 118),
+    ("SYSRQ", KEYCODE_SYSRQ),
+    ("MOVE_HOME", 122),
+    ("MOVE_END", 123),
+    ("VOLUME_MUTE", 164),
+    ("APP_SWITCH", KEYCODE_APP_SWITCH),
+    ("BRIGHTNESS_DOWN", 220),
+    ("BRIGHTNESS_UP", 221),
+    ("SLEEP", 223),
+    ("WAKEUP", 224),
+    ("CUT", 277),
+    ("COPY", 278),
+    ("PASTE", KEYCODE_PASTE),
+    // 别名
+    ("BACKSPACE", 67),
+    ("DELETE", 112),
+    ("ESC", 111),
+    ("RECENTS", KEYCODE_APP_SWITCH),
+    ("SCREENSHOT", KEYCODE_SYSRQ),
+    ("SHIFT", KEYCODE_SHIFT_LEFT),
+    ("CTRL", KEYCODE_CTRL_LEFT),
+    ("ALT", KEYCODE_ALT_LEFT),
+    ("META", KEYCODE_META_LEFT),
+];
+
+/// 键名 -> 键值，大小写不敏感，可带 KEYCODE_ 前缀
+pub fn key_code_from_name(name: &str) -> Option<i32> {
+    let upper = name.trim().to_ascii_uppercase();
+    let key = upper.strip_prefix("KEYCODE_").unwrap_or(&upper);
+
+    if let Some((_, code)) = NAMED_KEYS.iter().find(|(n, _)| *n == key) {
+        return Some(*code);
+    }
+
+    let bytes = key.as_bytes();
+    match bytes {
+        // A-Z: 29..=54
+        [c @ b'A'..=b'Z'] => Some(29 + (c - b'A') as i32),
+        // 0-9: 7..=16
+        [c @ b'0'..=b'9'] => Some(7 + (c - b'0') as i32),
+        // F1-F12: 131..=142
+        [b'F', rest @ ..] => match std::str::from_utf8(rest).ok()?.parse::<i32>() {
+            Ok(n @ 1..=12) => Some(130 + n),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 修饰键 (Shift/Ctrl/Alt/Meta 左右各一) 判断，按下期间会组合到其它键上
+/// 115 / 116 是 CAPS_LOCK / SCROLL_LOCK，不算修饰键
+pub fn is_modifier(code: i32) -> bool {
+    matches!(code, 57 | 58 | 59 | 60 | 113 | 114 | 117 | 118)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// 按下并抬起
+    Press,
+    /// 只按下 (之后需要 Up)
+    Down,
+    /// 只抬起
+    Up,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub code: i32,
+    pub action: KeyAction,
+    /// 长按 (仅 Press 有效)
+    pub long_press: bool,
+    /// 连按次数 (仅 Press 有效，1..=MAX_KEY_REPEAT)
+    pub repeat: u32,
+    /// 同时按住的修饰键键值 (如 CTRL_LEFT)，构成组合键
+    pub meta: Vec<i32>,
+}
+
+impl KeyEvent {
+    pub fn press(code: i32) -> Self {
+        Self {
+            code,
+            action: KeyAction::Press,
+            long_press: false,
+            repeat: 1,
+            meta: vec![],
+        }
+    }
+
+    pub fn down(code: i32) -> Self {
+        Self {
+            action: KeyAction::Down,
+            ..Self::press(code)
+        }
+    }
+
+    pub fn up(code: i32) -> Self {
+        Self {
+            action: KeyAction::Up,
+            ..Self::press(code)
+        }
+    }
+}
+
+/// 无障碍模式只能通过全局动作模拟少量按键
+/// 返回 AccessibilityService.GLOBAL_ACTION_* 常量
+pub fn global_action_for(code: i32, long_press: bool) -> Option<i32> {
+    match (code, long_press) {
+        (KEYCODE_BACK, false) => Some(1),         // GLOBAL_ACTION_BACK
+        (KEYCODE_HOME, false) => Some(2),         // GLOBAL_ACTION_HOME
+        (KEYCODE_APP_SWITCH, false) => Some(3),   // GLOBAL_ACTION_RECENTS
+        (KEYCODE_NOTIFICATION, false) => Some(4), // GLOBAL_ACTION_NOTIFICATIONS
+        (KEYCODE_POWER, true) => Some(6),         // GLOBAL_ACTION_POWER_DIALOG
+        (KEYCODE_POWER, false) => Some(8),        // GLOBAL_ACTION_LOCK_SCREEN
+        (KEYCODE_SYSRQ, false) => Some(9),        // GLOBAL_ACTION_TAKE_SCREENSHOT
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_are_exactly_shift_ctrl_alt_meta() {
+        let modifiers: Vec<i32> = (0..300).filter(|c| is_modifier(*c)).collect();
+        assert_eq!(modifiers, [57, 58, 59, 60, 113, 114, 117, 118]);
+        for name in ["SHIFT", "CTRL_RIGHT", "ALT", "META_RIGHT"] {
+            assert!(is_modifier(key_code_from_name(name).unwrap()), "{}", name);
+        }
+        // CAPS_LOCK / SCROLL_LOCK
+        assert!(!is_modifier(115));
+        assert!(!is_modifier(116));
+    }
+
+    #[test]
+    fn key_names() {
+        assert_eq!(key_code_from_name("HOME"), Some(KEYCODE_HOME));
+        assert_eq!(key_code_from_name(" back "), Some(KEYCODE_BACK));
+        assert_eq!(key_code_from_name("KEYCODE_enter"), Some(66));
+        assert_eq!(key_code_from_name("keycode_Volume_Up"), Some(24));
+        assert_eq!(key_code_from_name("Esc"), Some(111));
+        assert_eq!(key_code_from_name("a"), Some(29));
+        assert_eq!(key_code_from_name("Z"), Some(54));
+        assert_eq!(key_code_from_name("f"), Some(34));
+        assert_eq!(key_code_from_name("0"), Some(7));
+        assert_eq!(key_code_from_name("9"), Some(16));
+        assert_eq!(key_code_from_name("f1"), Some(131));
+        assert_eq!(key_code_from_name("F12"), Some(142));
+
+        for unknown in ["", "F0", "F13", "F1X", "AB", "KEYCODE_", "CAPS", "é"] {
+            assert_eq!(key_code_from_name(unknown), None, "{:?}", unknown);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// ==================================================
+// 📼 录制控制器: 不碰设备，只记录每个动作
+// 用于桌面端 dry run 和单元测试断言点击序列
+// ==================================================
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::core::unmap_coordinates;
+use crate::events::{self, ForegroundApp};
+use crate::input::{
+    keys::{KeyAction, KeyEvent},
+    InputController, InputError, InputMode, TextInputMethod,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum RecordedActionKind {
+    Click,
+    Press,
+    Swipe,
+    InputText,
+    KeyEvent,
+    KeyDown,
+    KeyUp,
+    Shell,
+    LaunchApp,
+    ForceStop,
+}
+
+/// 一个坐标点: 脚本里的逻辑坐标 + 映射后的真实坐标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct RecordedPoint {
+    pub logical_x: i32,
+    pub logical_y: i32,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RecordedAction {
+    /// 距离录制开始的毫秒数
+    pub timestamp_ms: u64,
+    pub kind: RecordedActionKind,
+    pub points: Vec<RecordedPoint>,
+    pub duration_ms: Option<u64>,
+    /// input_text 的文本 / shell 的命令 / launch_app、force_stop 的包名
+    pub text: Option<String>,
+    pub key_code: Option<i32>,
+}
+
+pub type ActionLog = Arc<Mutex<Vec<RecordedAction>>>;
+
+pub struct RecordingController {
+    actions: ActionLog,
+    started: Instant,
+}
+
+impl RecordingController {
+    pub fn new() -> Self {
+        Self {
+            actions: Arc::new(Mutex::new(Vec::new())),
+            started: Instant::now(),
+        }
+    }
+
+    /// 控制器装进 CONTROLLER 之后仍可通过这个句柄读取记录
+    pub fn log_handle(&self) -> ActionLog {
+        self.actions.clone()
+    }
+
+    pub fn actions(&self) -> Vec<RecordedAction> {
+        self.actions.lock().unwrap().clone()
+    }
+
+    fn record(
+        &self,
+        kind: RecordedActionKind,
+        points: Vec<RecordedPoint>,
+        duration_ms: Option<u64>,
+        text: Option<String>,
+        key_code: Option<i32>,
+    ) {
+        let action = RecordedAction {
+            timestamp_ms: self.started.elapsed().as_millis() as u64,
+            kind,
+            points,
+            duration_ms,
+            text,
+            key_code,
+        };
+        log::debug!("[Recorder] {:?}", action);
+        self.actions.lock().unwrap().push(action);
+    }
+}
+
+impl Default for RecordingController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn point(x: i32, y: i32) -> RecordedPoint {
+    let (logical_x, logical_y) = unmap_coordinates(x, y);
+    RecordedPoint {
+        logical_x,
+        logical_y,
+        x,
+        y,
+    }
+}
+
+impl InputController for RecordingController {
+    fn mode(&self) -> InputMode {
+        InputMode::Recording
+    }
+
+    fn click(&self, x: i32, y: i32) -> Result<(), InputError> {
+        self.record(
+            RecordedActionKind::Click,
+            vec![point(x, y)],
+            None,
+            None,
+            None,
+        );
+        Ok(())
+    }
+
+    fn press(&self, x: i32, y: i32, duration_ms: u64) -> Result<(), InputError> {
+        self.record(
+            RecordedActionKind::Press,
+            vec![point(x, y)],
+            Some(duration_ms),
+            None,
+            None,
+        );
+        Ok(())
+    }
+
+    fn swipe(&self, points: &[Vec<i32>], duration_ms: u64) -> Result<(), InputError> {
+        let points = points.iter().map(|p| point(p[0], p[1])).collect();
+        self.record(
+            RecordedActionKind::Swipe,
+            points,
+            Some(duration_ms),
+            None,
+            None,
+        );
+        Ok(())
+    }
+
+    fn input_text(&self, text: &str, _method: TextInputMethod) -> Result<(), InputError> {
+        self.record(
+            RecordedActionKind::InputText,
+            vec![],
+            None,
+            Some(text.to_string()),
+            None,
+        );
+        Ok(())
+    }
+
+    fn key_event(&self, event: &KeyEvent) -> Result<(), InputError> {
+        let kind = match event.action {
+            KeyAction::Press => RecordedActionKind::KeyEvent,
+            KeyAction::Down => RecordedActionKind::KeyDown,
+            KeyAction::Up => RecordedActionKind::KeyUp,
+        };
+        for _ in 0..event.repeat.max(1) {
+            self.record(kind, vec![], None, None, Some(event.code));
+        }
+        Ok(())
+    }
+
+    fn shell(&self, cmd: &str) -> Result<String, InputError> {
+        self.record(
+            RecordedActionKind::Shell,
+            vec![],
+            None,
+            Some(cmd.to_string()),
+            None,
+        );
+        Ok(String::new())
+    }
+
+    /// 没有真实设备，用宿主上报的前台应用 (report_foreground_app) 模拟
+    fn foreground_app(&self) -> Result<Option<ForegroundApp>, InputError> {
+        Ok(events::foreground())
+    }
+
+    fn launch_app(&self, package: &str) -> Result<(), InputError> {
+        self.record(
+            RecordedActionKind::LaunchApp,
+            vec![],
+            None,
+            Some(package.to_string()),
+            None,
+        );
+        Ok(())
+    }
+
+    fn force_stop(&self, package: &str) -> Result<(), InputError> {
+        self.record(
+            RecordedActionKind::ForceStop,
+            vec![],
+            None,
+            Some(package.to_string()),
+            None,
+        );
+        Ok(())
+    }
+}
@@ -63,4 +63,5 @@ pub unsafe extern "C" fn Java_org_eu_freex_app_NativeLib_pushScreenImage(
         guard.3 = row_stride as usize;
         guard.4 = scale;
     }
+    crate::events::frame_updated(width as usize, height as usize, scale);
 }
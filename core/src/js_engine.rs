@@ -1,86 +1,564 @@
+use std::sync::Arc;
+
 use crate::api;
-use log::info;
-use rquickjs::{AsyncContext, AsyncRuntime};
-use tokio::task::AbortHandle;
+use crate::bundle::{normalize_path, ScriptBundle};
+use crate::debugger::{self, Debuggee};
+use crate::lifecycle::ScriptErrorKind;
+use crate::manifest::NetworkConfig;
+use log::{info, warn};
+use rquickjs::{
+    async_with, AsyncContext, AsyncRuntime, CatchResultExt, CaughtError, Exception, Module, Object,
+    Promise, Value,
+};
+use tokio::sync::watch;
+
+pub mod control;
+pub mod limits;
+pub mod loader;
+pub mod source_map;
+
+pub use control::ScriptControl;
+pub use limits::RuntimeLimits;
+
+use source_map::{ScriptOrigin, SourceMap};
+
+/// 旧版脚本在 ctx.eval 里的文件名，以及脚本第 1 行之前模板占的行数
+const LEGACY_FILE: &str = "eval_script";
+const LEGACY_LINE_OFFSET: u32 = 1;
+
+/// 脚本来源
+pub enum ScriptSource {
+    /// 旧版: Vite IIFE 打包的单文件 (var GameScript = ...)
+    /// entry: 清单里声明的入口函数名，None 时按 main/start/run/第一个函数 猜测
+    /// source_map: 同时打包出的 script.js.map 内容
+    /// name: 清单名 (带清单运行时)
+    /// network: 清单的网络声明 (没有清单时为空，不能访问任何主机)
+    Legacy {
+        script: String,
+        entry: Option<String>,
+        source_map: Option<String>,
+        name: Option<String>,
+        network: NetworkConfig,
+    },
+    /// 脚本包: ES 模块 + 资源
+    Bundle(Arc<ScriptBundle>),
+}
+
+impl ScriptSource {
+    /// Storage / Files 的命名空间: 清单名；没有清单的旧版脚本用脚本内容的哈希，
+    /// 不同脚本不会共用数据 (代价是脚本改动后数据不再沿用，需要保留数据的脚本应带上清单)
+    /// 和显示名 (ScriptOptions::name) 无关
+    pub fn namespace(&self) -> String {
+        match self {
+            ScriptSource::Bundle(bundle) => bundle.manifest().name.clone(),
+            ScriptSource::Legacy {
+                name: Some(name), ..
+            } => name.clone(),
+            ScriptSource::Legacy { script, .. } => format!("script-{:016x}", fnv1a(script)),
+        }
+    }
+}
 
-// 🔥 全局任务句柄：用于存储当前正在跑的脚本任务
-// 这样我们才能在外部调用 stop_script 时找到它并杀掉
-pub static CURRENT_SCRIPT_TASK: std::sync::OnceLock<std::sync::Mutex<Option<AbortHandle>>> =
-    std::sync::OnceLock::new();
+/// FNV-1a 64 位哈希，跨版本 / 平台稳定 (std 的 DefaultHasher 不保证)
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// 脚本运行失败 (JS 异常带调用栈)
+#[derive(Debug, Clone)]
+pub struct ScriptError {
+    pub kind: ScriptErrorKind,
+    pub message: String,
+    /// 脚本结束时按 source map 改写 (见 map_stack)
+    pub stack: Option<String>,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
 
-pub async fn run_script_async(script_content: String) -> Result<(), String> {
-    info!("🚀 Initializing JS Runtime (OO Mode)...");
+impl From<String> for ScriptError {
+    fn from(message: String) -> Self {
+        Self {
+            kind: ScriptErrorKind::Exception,
+            message,
+            stack: None,
+        }
+    }
+}
+
+impl From<CaughtError<'_>> for ScriptError {
+    fn from(e: CaughtError<'_>) -> Self {
+        match e {
+            CaughtError::Exception(ex) => {
+                let name: String = ex.get("name").unwrap_or_else(|_| "Error".into());
+                let message = ex.message().unwrap_or_default();
+                Self {
+                    kind: classify(&name, &message),
+                    message: format!("{}: {}", name, message),
+                    stack: ex.stack().filter(|s| !s.is_empty()),
+                }
+            }
+            CaughtError::Value(v) if v.is_null() => THROWN_NULL.to_string().into(),
+            CaughtError::Value(v) if v.as_string().is_some() => {
+                let message = v
+                    .as_string()
+                    .and_then(|s| s.to_string().ok())
+                    .unwrap_or_default();
+                Self {
+                    kind: classify("", &message),
+                    message,
+                    stack: None,
+                }
+            }
+            CaughtError::Error(rquickjs::Error::Allocation) => Self {
+                kind: ScriptErrorKind::OutOfMemory,
+                message: "out of memory".into(),
+                stack: None,
+            },
+            other => other.to_string().into(),
+        }
+    }
+}
+
+/// 抛出的是 null (见 detect_out_of_memory)
+const THROWN_NULL: &str = "null";
+
+/// 按 QuickJS 的错误信息区分内存 / 栈溢出
+fn classify(name: &str, message: &str) -> ScriptErrorKind {
+    let message = message.to_ascii_lowercase();
+    if message.contains("out of memory") {
+        ScriptErrorKind::OutOfMemory
+    } else if message.contains("stack overflow")
+        || (name == "RangeError" && message.contains("call stack"))
+    {
+        ScriptErrorKind::StackOverflow
+    } else {
+        ScriptErrorKind::Exception
+    }
+}
+
+/// 运行脚本。超过 limits.timeout_ms 返回 Timeout (control 需用同一个 timeout 创建)
+pub async fn run_script_async(
+    source: ScriptSource,
+    control: ScriptControl,
+    limits: RuntimeLimits,
+) -> Result<(), ScriptError> {
+    let deadline = control.deadline();
+    let namespace = source.namespace();
+    let run = async {
+        match source {
+            ScriptSource::Legacy {
+                script,
+                entry,
+                source_map,
+                network,
+                ..
+            } => {
+                run_legacy_script(
+                    script,
+                    entry,
+                    source_map,
+                    network,
+                    &namespace,
+                    control.clone(),
+                    &limits,
+                )
+                .await
+            }
+            ScriptSource::Bundle(bundle) => {
+                run_bundle(bundle, &namespace, control.clone(), &limits).await
+            }
+        }
+    };
+    // 同步代码由中断回调打断，await 中的 (如长时间 Thread.sleep) 由这里的超时打断
+    let result = match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), run)
+            .await
+            .unwrap_or_else(|_| Err(String::new().into())),
+        None => run.await,
+    };
+    result.map_err(|e| {
+        if control.is_timed_out() && !control.is_cancelled() {
+            ScriptError {
+                kind: ScriptErrorKind::Timeout,
+                message: format!(
+                    "Script exceeded its time budget of {} ms",
+                    limits.timeout_ms.unwrap_or_default()
+                ),
+                stack: e.stack,
+            }
+        } else {
+            e
+        }
+    })
+}
+
+/// 创建运行时 + 上下文，并注册 API
+/// 返回的 finished 在脚本主流程结束后置为 true，用于停掉后台分发任务 (配置变更 / 事件)
+/// main_override: 替换包里入口模块的源码 (调试插桩)
+/// namespace: Storage / Files 的命名空间 (ScriptSource::namespace)
+/// network: fetch 的网络声明
+async fn create_runtime(
+    bundle: Option<Arc<ScriptBundle>>,
+    main_override: Option<(String, String)>,
+    network: NetworkConfig,
+    namespace: &str,
+    control: ScriptControl,
+    limits: &RuntimeLimits,
+    debuggee: Option<Debuggee>,
+) -> Result<(AsyncRuntime, AsyncContext, watch::Sender<bool>), String> {
+    info!(
+        "🚀 Initializing JS Runtime (OO Mode)... limits: {:?}",
+        limits
+    );
 
     let rt = AsyncRuntime::new().map_err(|e| e.to_string())?;
+    limits.apply(&rt).await;
+    rt.set_interrupt_handler(Some(control.interrupt_handler()))
+        .await;
+    if let Some(bundle) = &bundle {
+        let mut bundle_loader = loader::BundleLoader::new(bundle.clone());
+        if let Some((name, source)) = main_override {
+            bundle_loader = bundle_loader.with_override(name, source);
+        }
+        rt.set_loader(loader::BundleResolver::new(bundle.clone()), bundle_loader)
+            .await;
+    }
     let ctx = AsyncContext::full(&rt).await.map_err(|e| e.to_string())?;
+    let (finished, _) = watch::channel(false);
 
     // 注册 API
     ctx.with(|ctx| {
         let global = ctx.globals();
+        // 原生 API 通过 userdata 拿到停止标志 (api::checkpoint)
+        let _ = ctx.store_userdata(control);
         // 传入 ctx 以便注册 Class
-        if let Err(e) = api::register_globals(&global, &ctx) {
+        if let Err(e) = api::register_globals(&global, &ctx, bundle, network, namespace) {
             log::error!("Failed to register globals: {}", e);
         }
+        api::config::spawn_change_pump(&ctx, finished.subscribe());
+        api::events::spawn_event_pump(&ctx, finished.subscribe());
+        if let Some(debuggee) = debuggee {
+            if let Err(e) = debugger::hook::register(&ctx, debuggee) {
+                log::error!("Failed to register debug hook: {}", e);
+            }
+        }
     })
     .await;
 
+    Ok((rt, ctx, finished))
+}
+
+/// 解析 source map，格式不对时忽略 (只影响调用栈 / 断点映射)
+fn parse_source_map(json: Option<&str>) -> Option<SourceMap> {
+    SourceMap::parse(json?)
+        .map_err(|e| warn!("Ignoring source map: {}", e))
+        .ok()
+}
+
+/// 调试服务开着时附加调试器，返回插桩后的源码和钩子状态
+fn attach_debugger(
+    control: &ScriptControl,
+    source: &str,
+    origin: &ScriptOrigin,
+) -> Option<(String, Debuggee)> {
+    debugger::server_port()?;
+    debugger::attach(control.id(), source, origin.clone())
+}
+
+/// 脚本包模式: import 入口模块，调用清单声明的入口函数
+async fn run_bundle(
+    bundle: Arc<ScriptBundle>,
+    namespace: &str,
+    control: ScriptControl,
+    limits: &RuntimeLimits,
+) -> Result<(), ScriptError> {
+    let manifest = bundle.manifest().clone();
+    let script_id = control.id();
+    let main = normalize_path("", &manifest.main).unwrap_or_else(|_| manifest.main.clone());
+    // 入口模块读不到时由 import 报错，这里只是不做映射 / 调试
+    let source = bundle.read_text(&main).ok();
+    let source_map = bundle.read_text(&format!("{}.map", main)).ok();
+    let origin = ScriptOrigin::new(
+        &main,
+        0,
+        source.as_deref().unwrap_or_default(),
+        parse_source_map(source_map.as_deref()),
+    );
+    let debug = source.and_then(|source| attach_debugger(&control, &source, &origin));
+    let (main_override, debuggee) = match debug {
+        Some((code, debuggee)) => (Some((main.clone(), code)), Some(debuggee)),
+        None => (None, None),
+    };
+    let (rt, ctx, finished) = create_runtime(
+        Some(bundle.clone()),
+        main_override,
+        manifest.network.clone(),
+        namespace,
+        control,
+        limits,
+        debuggee,
+    )
+    .await?;
+    info!(
+        "📦 Running bundle [{} {}] entry: {}#{}",
+        manifest.name, manifest.version, manifest.main, manifest.entry
+    );
+
+    let result = async_with!(ctx => |ctx| {
+        let run = async {
+            let namespace: Object = Module::import(&ctx, manifest.main.clone())?
+                .into_future()
+                .await?;
+            let entry: Value = namespace.get(manifest.entry.as_str())?;
+            let Some(entry) = entry.into_function() else {
+                return Err(Exception::throw_reference(
+                    &ctx,
+                    &format!("Entry function `{}` is not exported by {}", manifest.entry, manifest.main),
+                ));
+            };
+            let ret: Value = entry.call(())?;
+            if let Some(promise) = ret.as_promise() {
+                promise.clone().into_future::<()>().await?;
+            }
+            api::timer::wait_idle(&ctx).await
+        };
+        let result = run.await.catch(&ctx).map_err(ScriptError::from);
+        if let Err(e) = &result {
+            debugger::hook::on_uncaught(&ctx, e);
+        }
+        // 正常结束时定时器已经跑完，这里清掉的是出错 / 停止 / 超时时剩下的
+        api::timer::clear_all(&ctx);
+        result
+    })
+    .await;
+
+    let mut result = result;
+    detect_out_of_memory(limits, &mut result);
+    map_stack(&origin, &mut result);
+    report(&result);
+    debugger::detach(script_id);
+    finished.send_replace(true);
+    rt.idle().await;
+    result
+}
+
+async fn run_legacy_script(
+    script_content: String,
+    entry: Option<String>,
+    source_map: Option<String>,
+    network: NetworkConfig,
+    namespace: &str,
+    control: ScriptControl,
+    limits: &RuntimeLimits,
+) -> Result<(), ScriptError> {
+    let script_id = control.id();
+    let origin = ScriptOrigin::new(
+        LEGACY_FILE,
+        LEGACY_LINE_OFFSET,
+        &script_content,
+        parse_source_map(source_map.as_deref()),
+    );
+    let (script_content, debuggee) = match attach_debugger(&control, &script_content, &origin) {
+        Some((code, debuggee)) => (code, Some(debuggee)),
+        None => (script_content, None),
+    };
+    let (rt, ctx, finished) =
+        create_runtime(None, None, network, namespace, control, limits, debuggee).await?;
+
+    // 入口名以 JSON 字面量注入，避免引号问题
+    let entry_literal = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+
     // 执行脚本
+    // 1. 脚本内容 (var GameScript = ...) 从第 2 行开始 (LEGACY_LINE_OFFSET)，调用栈行号减 1 即脚本行号
     let code = format!(
-        r#"
-        (async () => {{
-            try {{
-                log("🚀 Script System Initialized");
+        r#"(async () => {{ log("🚀 Script System Initialized");
+{}
+
+            const declaredEntry = {};
+
+            // 2. 入口查找: 清单声明了入口则严格使用，否则走智能查找
+            if (typeof GameScript !== 'undefined' && declaredEntry !== null) {{
+                if (typeof GameScript[declaredEntry] !== 'function') {{
+                    throw new ReferenceError("Entry function `" + declaredEntry + "` is not exported by GameScript");
+                }}
+                log("✅ Entry point: [" + declaredEntry + "]");
+                await GameScript[declaredEntry]();
+            }} else if (typeof GameScript !== 'undefined') {{
+                let entry = null;
+                let entryName = "unknown";
+
+                // 优先级 A: 检查常用入口名
+                if (GameScript.main) {{ entry = GameScript.main; entryName = "main"; }}
+                else if (GameScript.start) {{ entry = GameScript.start; entryName = "start"; }}
+                else if (GameScript.run) {{ entry = GameScript.run; entryName = "run"; }}
                 
-                // 1. 注入脚本内容 (var GameScript = ...)
-                {} 
-
-                // 2. 智能入口查找逻辑
-                if (typeof GameScript !== 'undefined') {{
-                    let entry = null;
-                    let entryName = "unknown";
-
-                    // 优先级 A: 检查常用入口名
-                    if (GameScript.main) {{ entry = GameScript.main; entryName = "main"; }}
-                    else if (GameScript.start) {{ entry = GameScript.start; entryName = "start"; }}
-                    else if (GameScript.run) {{ entry = GameScript.run; entryName = "run"; }}
-                    
-                    // 优先级 B: 如果都没有，遍历导出对象，找第一个是函数的
-                    if (!entry) {{
-                        for (let key in GameScript) {{
-                            if (typeof GameScript[key] === 'function') {{
-                                entry = GameScript[key];
-                                entryName = key;
-                                break;
-                            }}
+                // 优先级 B: 如果都没有，遍历导出对象，找第一个是函数的
+                if (!entry) {{
+                    for (let key in GameScript) {{
+                        if (typeof GameScript[key] === 'function') {{
+                            entry = GameScript[key];
+                            entryName = key;
+                            break;
                         }}
                     }}
+                }}
 
-                    // 3. 执行入口
-                    if (entry) {{
-                        log("✅ Auto-detected entry point: [" + entryName + "]");
-                        await entry(); // <--- 关键：这里 await 保证了脚本不会失控
-                    }} else {{
-                        log("⚠️ Warning: No exported function found! Did you forget 'export async function...'?");
-                    }}
-
+                // 3. 执行入口
+                if (entry) {{
+                    log("✅ Auto-detected entry point: [" + entryName + "] (declare `entry` in manifest to be explicit)");
+                    await entry(); // <--- 关键：这里 await 保证了脚本不会失控
                 }} else {{
-                    log("⚠️ Warning: GameScript object not found.");
+                    log("⚠️ Warning: No exported function found! Did you forget 'export async function...'?");
                 }}
 
-                log("🏁 Script Finished");
-            }} catch(e) {{
-                log("❌ Script Error: " + e);
-                if (e.stack) {{ log(e.stack); }}
+            }} else {{
+                log("⚠️ Warning: GameScript object not found.");
             }}
         }})()
         "#,
-        script_content
+        script_content, entry_literal
     );
 
-    ctx.with(|ctx| ctx.eval::<(), _>(code))
-        .await
-        .map_err(|e| e.to_string())?;
+    let result = async_with!(ctx => |ctx| {
+        let run = async {
+            let promise: Promise = ctx.eval(code)?;
+            promise.into_future::<()>().await?;
+            api::timer::wait_idle(&ctx).await
+        };
+        let result = run.await.catch(&ctx).map_err(ScriptError::from);
+        if let Err(e) = &result {
+            debugger::hook::on_uncaught(&ctx, e);
+        }
+        // 正常结束时定时器已经跑完，这里清掉的是出错 / 停止 / 超时时剩下的
+        api::timer::clear_all(&ctx);
+        result
+    })
+    .await;
 
+    let mut result = result;
+    detect_out_of_memory(limits, &mut result);
+    map_stack(&origin, &mut result);
+    report(&result);
+    debugger::detach(script_id);
+    finished.send_replace(true);
     rt.idle().await;
-    Ok(())
+    result
+}
+
+/// 真正 OOM 时 QuickJS 连错误对象都分配不出来，抛出的是 null (而且随后 GC 已经回收，
+/// 事后看内存占用也看不出来)，所以设置了堆上限时把 "抛出 null" 视为 OOM
+fn detect_out_of_memory(limits: &RuntimeLimits, result: &mut Result<(), ScriptError>) {
+    let (Err(e), Some(limit)) = (result.as_mut(), limits.memory_limit_bytes) else {
+        return;
+    };
+    if e.kind == ScriptErrorKind::Exception && e.stack.is_none() && e.message == THROWN_NULL {
+        e.kind = ScriptErrorKind::OutOfMemory;
+        e.message = format!("out of memory (limit {} bytes)", limit);
+    }
+}
+
+/// 调用栈指向打包后的 script.js，按 source map 改写成 TS 源码位置 (日志和 Failed 事件都用改写后的)
+fn map_stack(origin: &ScriptOrigin, result: &mut Result<(), ScriptError>) {
+    if let Err(ScriptError {
+        stack: Some(stack), ..
+    }) = result
+    {
+        *stack = origin.rewrite_stack(stack);
+    }
+}
+
+fn report(result: &Result<(), ScriptError>) {
+    match result {
+        Ok(_) => info!("🏁 Script Finished"),
+        Err(e) => {
+            info!("❌ Script Error: {}", e);
+            if let Some(stack) = &e.stack {
+                info!("{}", stack);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy(script: &str, name: Option<&str>) -> ScriptSource {
+        ScriptSource::Legacy {
+            script: script.to_string(),
+            entry: None,
+            source_map: None,
+            name: name.map(str::to_string),
+            network: NetworkConfig::default(),
+        }
+    }
+
+    #[test]
+    fn namespace_prefers_manifest_name_then_script_hash() {
+        assert_eq!(legacy("a", Some("daily")).namespace(), "daily");
+        let a = legacy("var GameScript = { main() {} }", None).namespace();
+        let b = legacy("var GameScript = { run() {} }", None).namespace();
+        assert!(a.starts_with("script-"));
+        assert_ne!(a, b);
+        assert_eq!(
+            a,
+            legacy("var GameScript = { main() {} }", None).namespace()
+        );
+        // 哈希要跨版本稳定，改了算法会让旧数据找不到
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    /// 带清单的旧版脚本按清单的 network 放行 fetch，没有清单的不能联网
+    #[tokio::test]
+    async fn legacy_script_uses_manifest_network() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut head = [0u8; 1024];
+                let _ = stream.read(&mut head).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                    .await;
+            }
+        });
+        let script = format!(
+            r#"var GameScript = {{ async main() {{
+                const response = await fetch("http://127.0.0.1:{}/");
+                if (response.status !== 200) throw new Error("status " + response.status);
+            }} }};"#,
+            port
+        );
+        let run = |network: NetworkConfig| {
+            let source = ScriptSource::Legacy {
+                script: script.clone(),
+                entry: Some("main".to_string()),
+                source_map: None,
+                name: Some("net-test".to_string()),
+                network,
+            };
+            run_script_async(source, ScriptControl::default(), RuntimeLimits::default())
+        };
+
+        let allowed = NetworkConfig {
+            allowed_hosts: vec![format!("127.0.0.1:{}", port)],
+            ..NetworkConfig::default()
+        };
+        run(allowed).await.unwrap();
+        let denied = run(NetworkConfig::default()).await.unwrap_err();
+        assert!(
+            denied.message.contains("allowedHosts"),
+            "{}",
+            denied.message
+        );
+    }
 }
@@ -0,0 +1,102 @@
+// ==========================================================
+// 🛑 协作式停止 / 暂停
+// tokio abort 只能在 await 点生效，同步死循环 (while(true){ Colors.findColor(...) })
+// 永远不会让出，所以再加两道检查:
+// 1. QuickJS 中断回调: 解释器每执行一段字节码就会调用一次
+// 2. 原生 API 入口 (api::checkpoint): 每次调用 Device / Colors 等都检查
+// 暂停时两处都会阻塞当前脚本线程，直到恢复或停止 (每个脚本独立暂停)
+// 超过运行时长 (RuntimeLimits.timeout_ms) 与停止同样处理
+// ==========================================================
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use rquickjs::{runtime::InterruptHandler, JsLifetime};
+
+use crate::registry::ScriptId;
+
+/// 暂停时的轮询间隔
+const PAUSE_POLL: Duration = Duration::from_millis(10);
+
+/// 单个脚本的控制句柄 (停止 / 暂停标志 + 截止时间)
+/// 同时作为 ctx userdata，原生 API 通过它知道是哪个脚本在调用
+#[derive(Clone, Default, JsLifetime)]
+pub struct ScriptControl {
+    id: ScriptId,
+    input_priority: i32,
+    cancelled: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl ScriptControl {
+    /// timeout: 从现在开始计时，超过视为超时 (大到算不出截止时间时视为不限时)
+    pub fn new(id: ScriptId, input_priority: i32, timeout: Option<Duration>) -> Self {
+        Self {
+            id,
+            input_priority,
+            cancelled: Arc::default(),
+            paused: Arc::default(),
+            deadline: timeout.and_then(|t| Instant::now().checked_add(t)),
+        }
+    }
+
+    pub fn id(&self) -> ScriptId {
+        self.id
+    }
+
+    /// 输入仲裁优先级 (见 input::arbiter)
+    pub fn input_priority(&self) -> i32 {
+        self.input_priority
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否被外部停止 (stop_script)
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// 被停止或超时
+    pub fn should_stop(&self) -> bool {
+        self.is_cancelled() || self.is_timed_out()
+    }
+
+    /// 暂停中则阻塞等待，返回是否应该结束 (被停止或超时)
+    pub fn wait_while_paused(&self) -> bool {
+        while self.is_paused() && !self.should_stop() {
+            thread::sleep(PAUSE_POLL);
+        }
+        self.should_stop()
+    }
+
+    /// QuickJS 中断回调: 返回 true 时解释器抛出不可捕获的异常
+    pub fn interrupt_handler(&self) -> InterruptHandler {
+        let control = self.clone();
+        Box::new(move || control.wait_while_paused())
+    }
+}
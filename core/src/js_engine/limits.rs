@@ -0,0 +1,124 @@
+// ==========================================================
+// 📏 运行时限制 (堆内存 / GC 阈值 / 栈大小 / 总运行时长)
+// 防止脚本无限分配内存把宿主 App 进程拖崩
+// ==========================================================
+
+use std::time::Duration;
+
+use rquickjs::AsyncRuntime;
+
+/// 默认堆上限 256MB
+pub const DEFAULT_MEMORY_LIMIT: u64 = 256 * 1024 * 1024;
+/// 默认 JS 栈上限 1MB
+pub const DEFAULT_MAX_STACK: u64 = 1024 * 1024;
+/// 脚本线程的原生栈要比 JS 栈上限留出余量，否则会先于 QuickJS 检查真正栈溢出
+const THREAD_STACK_MARGIN: u64 = 2 * 1024 * 1024;
+/// JS 栈上限的最大值 (脚本线程的栈按它分配)
+const MAX_STACK_LIMIT: u64 = 256 * 1024 * 1024;
+/// 运行时长上限的最大值 (一年)，更大的值在部分平台上算不出截止时间
+const MAX_TIMEOUT_MS: u64 = 365 * 24 * 60 * 60 * 1000;
+
+/// 运行限制，字段为 None 表示不限制 (栈大小为 None 时用 QuickJS 默认值)
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RuntimeLimits {
+    /// 堆内存上限 (字节)，超出抛 OutOfMemory
+    pub memory_limit_bytes: Option<u64>,
+    /// 分配多少字节后触发一次 GC
+    pub gc_threshold_bytes: Option<u64>,
+    /// JS 调用栈上限 (字节)，超出抛 StackOverflow
+    pub max_stack_bytes: Option<u64>,
+    /// 总运行时长 (含暂停)，超出按 Timeout 结束
+    pub timeout_ms: Option<u64>,
+}
+
+impl Default for RuntimeLimits {
+    fn default() -> Self {
+        Self {
+            memory_limit_bytes: Some(DEFAULT_MEMORY_LIMIT),
+            gc_threshold_bytes: None,
+            max_stack_bytes: Some(DEFAULT_MAX_STACK),
+            timeout_ms: None,
+        }
+    }
+}
+
+impl RuntimeLimits {
+    pub async fn apply(&self, rt: &AsyncRuntime) {
+        if let Some(bytes) = self.memory_limit_bytes {
+            rt.set_memory_limit(bytes as usize).await;
+        }
+        if let Some(bytes) = self.gc_threshold_bytes {
+            rt.set_gc_threshold(bytes as usize).await;
+        }
+        if let Some(bytes) = self.max_stack_bytes {
+            rt.set_max_stack_size(bytes as usize).await;
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    /// 宿主传入的限制必须能换算成本机的字节数 / 截止时间，不合法时不启动脚本
+    pub fn validate(&self) -> Result<(), String> {
+        let bytes = [
+            ("memoryLimitBytes", self.memory_limit_bytes),
+            ("gcThresholdBytes", self.gc_threshold_bytes),
+        ];
+        if let Some((name, Some(value))) = bytes
+            .iter()
+            .find(|(_, v)| v.is_some_and(|v| usize::try_from(v).is_err()))
+        {
+            return Err(format!("{} is too large: {}", name, value));
+        }
+        if let Some(bytes) = self.max_stack_bytes.filter(|b| *b > MAX_STACK_LIMIT) {
+            return Err(format!(
+                "maxStackBytes must be at most {}, got {}",
+                MAX_STACK_LIMIT, bytes
+            ));
+        }
+        if let Some(ms) = self.timeout_ms.filter(|ms| *ms > MAX_TIMEOUT_MS) {
+            return Err(format!(
+                "timeoutMs must be at most {}, got {}",
+                MAX_TIMEOUT_MS, ms
+            ));
+        }
+        Ok(())
+    }
+
+    /// 脚本线程需要的原生栈大小 (算不出来时取 usize::MAX，由创建线程失败报错)
+    pub fn thread_stack_size(&self) -> usize {
+        self.max_stack_bytes
+            .unwrap_or(DEFAULT_MAX_STACK)
+            .checked_add(THREAD_STACK_MARGIN)
+            .and_then(|bytes| usize::try_from(bytes).ok())
+            .unwrap_or(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js_engine::ScriptControl;
+
+    #[test]
+    fn out_of_range_limits_are_rejected() {
+        assert!(RuntimeLimits::default().validate().is_ok());
+        let limits = |f: fn(&mut RuntimeLimits)| {
+            let mut limits = RuntimeLimits::default();
+            f(&mut limits);
+            limits
+        };
+        let huge_stack = limits(|l| l.max_stack_bytes = Some(u64::MAX));
+        assert!(huge_stack.validate().is_err());
+        // 不校验也不能 panic
+        assert_eq!(huge_stack.thread_stack_size(), usize::MAX);
+        assert!(limits(|l| l.timeout_ms = Some(u64::MAX))
+            .validate()
+            .is_err());
+        assert!(limits(|l| l.timeout_ms = Some(60_000)).validate().is_ok());
+        assert!(ScriptControl::new(1, 0, Some(Duration::MAX))
+            .deadline()
+            .is_none());
+    }
+}
@@ -0,0 +1,88 @@
+// ==========================================================
+// 📦 ES 模块加载: 从脚本包里解析 / 读取模块
+// import "./utils.js"      -> 相对当前模块
+// import "lib/utils"       -> 相对包根目录 (自动补 .js / .mjs / /index.js)
+// import cfg from "./a.json" -> JSON 作为 default 导出
+// ==========================================================
+
+use std::{collections::HashMap, sync::Arc};
+
+use rquickjs::{
+    loader::{Loader, Resolver},
+    module::Declared,
+    Ctx, Error, Module, Result,
+};
+
+use crate::bundle::{normalize_path, parent_dir, ScriptBundle};
+
+pub struct BundleResolver {
+    bundle: Arc<ScriptBundle>,
+}
+
+impl BundleResolver {
+    pub fn new(bundle: Arc<ScriptBundle>) -> Self {
+        Self { bundle }
+    }
+}
+
+impl Resolver for BundleResolver {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let base_dir = if name.starts_with("./") || name.starts_with("../") {
+            parent_dir(base)
+        } else {
+            ""
+        };
+        let path = normalize_path(base_dir, name)
+            .map_err(|e| Error::new_resolving_message(base, name, e.to_string()))?;
+
+        let candidates = [
+            path.clone(),
+            format!("{}.js", path),
+            format!("{}.mjs", path),
+            format!("{}/index.js", path),
+        ];
+        candidates
+            .into_iter()
+            .find(|c| self.bundle.exists(c))
+            .ok_or_else(|| Error::new_resolving(base, name))
+    }
+}
+
+pub struct BundleLoader {
+    bundle: Arc<ScriptBundle>,
+    /// 替换包里某个模块的源码 (调试插桩后的入口模块)
+    overrides: HashMap<String, String>,
+}
+
+impl BundleLoader {
+    pub fn new(bundle: Arc<ScriptBundle>) -> Self {
+        Self {
+            bundle,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn with_override(mut self, name: String, source: String) -> Self {
+        self.overrides.insert(name, source);
+        self
+    }
+}
+
+impl Loader for BundleLoader {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        let source = match self.overrides.get(name) {
+            Some(source) => source.clone(),
+            None => self
+                .bundle
+                .read_text(name)
+                .map_err(|e| Error::new_loading_message(name, e.to_string()))?,
+        };
+
+        let source = if name.ends_with(".json") {
+            format!("export default {};", source)
+        } else {
+            source
+        };
+        Module::declare(ctx.clone(), name, source)
+    }
+}
@@ -0,0 +1,470 @@
+// ==========================================================
+// 🗺️ Source Map (v3) 解析
+// esbuild / Vite 打包时生成 script.js.map，这里把 script.js 里的位置
+// 映射回 TypeScript 源文件 (调试器断点、错误调用栈都用它)
+// 对外的行号 / 列号都是 1 开始，内部存 0 开始
+// ==========================================================
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+/// 一个映射段: 生成代码的列 -> 源文件位置
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    generated_column: u32,
+    /// (源文件, 行, 列)；只有生成列的段为 None，表示从这一列开始没有对应的源码
+    original: Option<(u32, u32, u32)>,
+}
+
+/// 源文件中的位置 (1 开始)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalPosition {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// 按生成代码的行分组，每行内按列排序
+    lines: Vec<Vec<Segment>>,
+}
+
+impl SourceMap {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let raw: RawSourceMap =
+            serde_json::from_str(json).map_err(|e| format!("Invalid source map: {}", e))?;
+        if raw.version != 3 {
+            return Err(format!("Unsupported source map version {}", raw.version));
+        }
+        let root = raw.source_root.unwrap_or_default();
+        let sources = raw
+            .sources
+            .into_iter()
+            .map(|s| {
+                let s = s.unwrap_or_default();
+                if root.is_empty() {
+                    s
+                } else {
+                    format!("{}/{}", root.trim_end_matches('/'), s)
+                }
+            })
+            .collect();
+        let lines = decode_mappings(&raw.mappings)?;
+        Ok(Self { sources, lines })
+    }
+
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// 生成代码位置 -> 源位置 (取该列之前最近的映射段，列在第一段之前时取第一段)
+    /// 落在没有源码的段上返回 None
+    pub fn lookup(&self, line: u32, column: u32) -> Option<OriginalPosition> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let index = segments
+            .partition_point(|s| s.generated_column <= column)
+            .saturating_sub(1);
+        let (source, line, column) = segments.get(index)?.original?;
+        Some(OriginalPosition {
+            source: self.sources.get(source as usize)?.clone(),
+            line: line + 1,
+            column: column + 1,
+        })
+    }
+
+    /// 源文件的某一行 -> 生成代码中最早对应的行 (断点用)
+    /// source 可以只写文件名或路径后缀: "index.ts" / "Legend/index.ts"
+    pub fn generated_line(&self, source: &str, line: u32) -> Option<u32> {
+        let source = self.find_source(source)?;
+        let line = line.checked_sub(1)?;
+        self.lines
+            .iter()
+            .position(|segments| {
+                segments
+                    .iter()
+                    .any(|s| s.original.is_some_and(|(s, l, _)| s == source && l == line))
+            })
+            .map(|i| i as u32 + 1)
+    }
+
+    fn find_source(&self, query: &str) -> Option<u32> {
+        let query = query.trim_start_matches("./");
+        self.sources
+            .iter()
+            .position(|s| s == query || s.ends_with(&format!("/{}", query)))
+            .map(|i| i as u32)
+    }
+}
+
+/// 脚本在调用栈里的位置: 把 QuickJS 调用栈里的 (文件, 行, 列) 映射回脚本 / 源码
+#[derive(Debug, Clone)]
+pub struct ScriptOrigin {
+    /// 调用栈里脚本的文件名 (模块名 / eval_script)
+    pub file: String,
+    /// 脚本第 1 行在 file 中的行号 - 1 (旧版脚本外面包了一层模板)
+    pub line_offset: u32,
+    /// 脚本总行数 (超出的是外层模板)
+    pub line_count: u32,
+    pub source_map: Option<SourceMap>,
+}
+
+impl ScriptOrigin {
+    pub fn new(file: &str, line_offset: u32, source: &str, source_map: Option<SourceMap>) -> Self {
+        Self {
+            file: file.to_string(),
+            line_offset,
+            line_count: source.lines().count() as u32,
+            source_map,
+        }
+    }
+
+    /// 调用栈里的 (文件, 行号) -> 脚本行号，不属于脚本返回 None
+    pub fn script_line(&self, file: &str, line: u32) -> Option<u32> {
+        let line = line.checked_sub(self.line_offset)?;
+        (file == self.file && (1..=self.line_count).contains(&line)).then_some(line)
+    }
+
+    /// 脚本位置 -> 源码位置 (没有 source map 时为 None)
+    pub fn original(&self, line: u32, column: u32) -> Option<OriginalPosition> {
+        self.source_map.as_ref()?.lookup(line, column)
+    }
+
+    /// 改写错误的调用栈: 属于脚本的帧换成源码位置 (没有 source map 时换成脚本行号)，其它帧原样保留
+    pub fn rewrite_stack(&self, stack: &str) -> String {
+        let mut out = String::with_capacity(stack.len());
+        for frame in stack.lines() {
+            match self.rewrite_frame(frame) {
+                Some(frame) => out.push_str(&frame),
+                None => out.push_str(frame),
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn rewrite_frame(&self, frame: &str) -> Option<String> {
+        let trimmed = frame.trim_start();
+        let indent = &frame[..frame.len() - trimmed.len()];
+        let parsed = parse_frame(trimmed)?;
+        let line = self.script_line(&parsed.file, parsed.line)?;
+        let position = match self.original(line, parsed.column) {
+            Some(p) => format!("{}:{}:{}", p.source, p.line, p.column),
+            None => format!("{}:{}:{}", parsed.file, line, parsed.column),
+        };
+        Some(match parsed.function {
+            Some(function) => format!("{}at {} ({})", indent, function, position),
+            None => format!("{}at {}", indent, position),
+        })
+    }
+}
+
+/// 调用栈的一帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    pub function: Option<String>,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// 解析 "at name (file:line:col)" / "at file:line:col"
+pub fn parse_frame(frame: &str) -> Option<StackFrame> {
+    let frame = frame.strip_prefix("at ")?;
+    let (function, position) = match frame.strip_suffix(')').and_then(|f| f.rsplit_once(" (")) {
+        Some((function, position)) => (Some(function.to_string()), position),
+        None => (None, frame),
+    };
+    let mut parts = position.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?.to_string();
+    Some(StackFrame {
+        function,
+        file,
+        line,
+        column,
+    })
+}
+
+/// 解码 mappings 字段 (";" 分行, "," 分段, 每段是 Base64 VLQ，字段相对上一段)
+fn decode_mappings(mappings: &str) -> Result<Vec<Vec<Segment>>, String> {
+    let mut lines = Vec::new();
+    // 除生成列外，其它字段跨行累加
+    let (mut source, mut line, mut column) = (0i64, 0i64, 0i64);
+    for line_str in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut generated_column = 0i64;
+        for segment in line_str.split(',').filter(|s| !s.is_empty()) {
+            let fields = decode_vlq(segment)?;
+            let invalid = || format!("Invalid mapping segment `{}`", segment);
+            generated_column += fields[0];
+            if generated_column < 0 {
+                return Err(invalid());
+            }
+            // 只有 1 个字段的段没有对应源位置 (第 5 个字段是名字，用不到)
+            let original = match fields.len() {
+                1 => None,
+                4 | 5 => {
+                    source += fields[1];
+                    line += fields[2];
+                    column += fields[3];
+                    if source < 0 || line < 0 || column < 0 {
+                        return Err(invalid());
+                    }
+                    Some((source as u32, line as u32, column as u32))
+                }
+                _ => return Err(invalid()),
+            };
+            segments.push(Segment {
+                generated_column: generated_column as u32,
+                original,
+            });
+        }
+        segments.sort_by_key(|s| s.generated_column);
+        lines.push(segments);
+    }
+    Ok(lines)
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, String> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+    for c in segment.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(format!("Invalid base64 VLQ character `{}`", c as char)),
+        } as i64;
+        if shift > 60 {
+            return Err(format!("VLQ value too large in `{}`", segment));
+        }
+        value += (digit & 0b11111) << shift;
+        if digit & 0b100000 != 0 {
+            shift += 5;
+            continue;
+        }
+        // 最低位是符号位
+        values.push(if value & 1 == 1 {
+            -(value >> 1)
+        } else {
+            value >> 1
+        });
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 || values.is_empty() {
+        return Err(format!("Truncated VLQ segment `{}`", segment));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // esbuild 风格的 IIFE 打包 (src/utils.ts + src/index.ts)，map 里有负数增量、
+    // 一行多段、带名字的 5 字段段和只有生成列的未映射段
+    const SCRIPT: &str = include_str!("../../tests/fixtures/source_map/script.js");
+    const MAP: &str = include_str!("../../tests/fixtures/source_map/script.js.map");
+
+    fn map() -> SourceMap {
+        SourceMap::parse(MAP).unwrap()
+    }
+
+    fn pos(source: &str, line: u32, column: u32) -> Option<OriginalPosition> {
+        Some(OriginalPosition {
+            source: source.into(),
+            line,
+            column,
+        })
+    }
+
+    #[test]
+    fn decodes_vlq() {
+        assert_eq!(decode_vlq("A").unwrap(), [0]);
+        assert_eq!(decode_vlq("C").unwrap(), [1]);
+        assert_eq!(decode_vlq("D").unwrap(), [-1]);
+        assert_eq!(decode_vlq("gB").unwrap(), [16]);
+        assert_eq!(decode_vlq("hB").unwrap(), [-16]);
+        assert_eq!(decode_vlq("2H").unwrap(), [123]);
+        assert_eq!(decode_vlq("EAJF").unwrap(), [2, 0, -4, -2]);
+        assert_eq!(decode_vlq("+/////D").unwrap(), [i32::MAX as i64]);
+
+        assert!(decode_vlq("g").is_err());
+        assert!(decode_vlq("AA!A").is_err());
+        assert!(decode_vlq("").is_err());
+        assert!(decode_vlq("gggggggggggggggA").is_err());
+    }
+
+    #[test]
+    fn decodes_mappings() {
+        let lines = decode_mappings(";AAAA,EAAE;;IACI,K").unwrap();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].is_empty() && lines[2].is_empty());
+        let columns: Vec<_> = lines[1].iter().map(|s| s.generated_column).collect();
+        assert_eq!(columns, [0, 2]);
+        assert_eq!(lines[1][1].original, Some((0, 0, 2)));
+        // 源位置跨行累加，生成列每行从 0 开始
+        assert_eq!(lines[3][0].generated_column, 4);
+        assert_eq!(lines[3][0].original, Some((0, 1, 6)));
+        assert_eq!(lines[3][1].generated_column, 9);
+        assert_eq!(lines[3][1].original, None);
+
+        // 累加后出现负数 / 字段数不对
+        assert!(decode_mappings("AAAD").is_err());
+        assert!(decode_mappings("D").is_err());
+        assert!(decode_mappings("AA").is_err());
+        assert!(decode_mappings("AAAAAA").is_err());
+    }
+
+    #[test]
+    fn parses_header() {
+        let map = map();
+        assert_eq!(map.sources(), ["../src/utils.ts", "../src/index.ts"]);
+
+        let rooted = SourceMap::parse(
+            r#"{"version":3,"sourceRoot":"src/","sources":["a.ts",null],"mappings":"AAAA"}"#,
+        )
+        .unwrap();
+        assert_eq!(rooted.sources(), ["src/a.ts", "src/"]);
+        assert!(SourceMap::parse(r#"{"version":2,"sources":[],"mappings":""}"#).is_err());
+        assert!(SourceMap::parse(r#"{"version":3,"sources":[]}"#).is_err());
+        assert!(SourceMap::parse("not json").is_err());
+    }
+
+    #[test]
+    fn looks_up_original_positions() {
+        let map = map();
+        // 一行多段: 取该列之前最近的段
+        assert_eq!(map.lookup(4, 3), pos("../src/utils.ts", 1, 8));
+        assert_eq!(map.lookup(4, 12), pos("../src/utils.ts", 1, 17));
+        assert_eq!(map.lookup(4, 17), pos("../src/utils.ts", 1, 17));
+        assert_eq!(map.lookup(4, 100), pos("../src/utils.ts", 1, 51));
+        // 在第一段之前的列取第一段
+        assert_eq!(map.lookup(4, 1), pos("../src/utils.ts", 1, 8));
+        // 换源文件
+        assert_eq!(map.lookup(9, 7), pos("../src/index.ts", 3, 5));
+        assert_eq!(map.lookup(12, 13), pos("../src/index.ts", 6, 11));
+        // 源行号是负增量 (回到第 3 行)
+        assert_eq!(map.lookup(14, 3), pos("../src/index.ts", 3, 1));
+
+        // 未映射的段之后没有源码位置，之前的列不受影响
+        assert_eq!(map.lookup(11, 17), pos("../src/index.ts", 5, 15));
+        assert_eq!(map.lookup(11, 31), None);
+        assert_eq!(map.lookup(11, 40), None);
+        // 没有映射的行 / 超出范围
+        assert_eq!(map.lookup(1, 1), None);
+        assert_eq!(map.lookup(7, 1), None);
+        assert_eq!(map.lookup(15, 1), None);
+        assert_eq!(map.lookup(99, 1), None);
+        assert_eq!(map.lookup(0, 1), None);
+    }
+
+    #[test]
+    fn finds_generated_lines() {
+        let map = map();
+        assert_eq!(map.generated_line("index.ts", 5), Some(11));
+        assert_eq!(map.generated_line("src/index.ts", 6), Some(12));
+        assert_eq!(map.generated_line("./utils.ts", 2), Some(5));
+        // 对象字面量的第 3 行同时出现在第 9 行和第 14 行，取最早的
+        assert_eq!(map.generated_line("index.ts", 3), Some(9));
+        // import 行没有生成代码
+        assert_eq!(map.generated_line("index.ts", 1), None);
+        assert_eq!(map.generated_line("dex.ts", 5), None);
+        assert_eq!(map.generated_line("other.ts", 1), None);
+        assert_eq!(map.generated_line("index.ts", 0), None);
+    }
+
+    #[test]
+    fn parses_frames() {
+        assert_eq!(
+            parse_frame("at main (eval_script:13:13)"),
+            Some(StackFrame {
+                function: Some("main".into()),
+                file: "eval_script".into(),
+                line: 13,
+                column: 13,
+            })
+        );
+        assert_eq!(
+            parse_frame("at eval_script:6:12"),
+            Some(StackFrame {
+                function: None,
+                file: "eval_script".into(),
+                line: 6,
+                column: 12,
+            })
+        );
+        // 文件名里有冒号 / 函数名里有空格
+        let frame = parse_frame("at async Object.run (bundle://lib/a.js:3:1)").unwrap();
+        assert_eq!(frame.function.as_deref(), Some("async Object.run"));
+        assert_eq!(frame.file, "bundle://lib/a.js");
+        assert_eq!((frame.line, frame.column), (3, 1));
+        assert_eq!(
+            parse_frame("at <anonymous> (main.js:1:2)")
+                .unwrap()
+                .function
+                .as_deref(),
+            Some("<anonymous>")
+        );
+
+        assert_eq!(parse_frame("at native"), None);
+        assert_eq!(parse_frame("Error: boom"), None);
+        assert_eq!(parse_frame("at main (eval_script:13)"), None);
+        assert_eq!(parse_frame("    at main (eval_script:13:13)"), None);
+    }
+
+    #[test]
+    fn rewrites_stack_with_source_map() {
+        let origin = ScriptOrigin::new("eval_script", 1, SCRIPT, Some(map()));
+        let stack = "    at <anonymous> (eval_script:13:13)
+    at main (eval_script:12:17)
+    at main (eval_script:12:31)
+    at eval_script:6:12
+    at run (eval_script:30:5)
+    at load (other.js:13:13)
+    at native";
+        assert_eq!(
+            origin.rewrite_stack(stack),
+            "    at <anonymous> (../src/index.ts:6:11)
+    at main (../src/index.ts:5:15)
+    at main (eval_script:11:31)
+    at ../src/utils.ts:2:10
+    at run (eval_script:30:5)
+    at load (other.js:13:13)
+    at native
+"
+        );
+    }
+
+    #[test]
+    fn rewrites_stack_without_source_map() {
+        let origin = ScriptOrigin::new("eval_script", 1, SCRIPT, None);
+        assert_eq!(origin.line_count, 15);
+        assert_eq!(origin.script_line("eval_script", 13), Some(12));
+        assert_eq!(origin.script_line("eval_script", 1), None);
+        assert_eq!(origin.script_line("eval_script", 17), None);
+        assert_eq!(origin.script_line("other.js", 13), None);
+        assert_eq!(
+            origin.rewrite_stack(
+                "Error: boom\n    at main (eval_script:13:13)\n  at eval_script:2:1"
+            ),
+            "Error: boom\n    at main (eval_script:12:13)\n  at eval_script:1:1\n"
+        );
+    }
+}
@@ -1,10 +1,23 @@
 #[macro_use]
 pub mod macros;
 pub mod bindgen;
+pub mod bundle;
+pub mod config;
 pub mod constants;
 pub mod core;
+pub mod data_dir;
+pub mod debugger;
+pub mod dev_auth;
+pub mod events;
+pub mod hot_reload;
 pub mod jni_binding;
+pub mod lifecycle;
 pub mod logger;
+pub mod manifest;
+pub mod net;
+pub mod registry;
+pub mod sandbox;
+pub mod storage;
 pub mod uniffi_binding;
 
 pub use uniffi_binding::UniFfiTag;
@@ -13,4 +26,5 @@ pub mod api;
 pub mod input;
 pub mod js_engine;
 pub mod types;
+pub mod ui;
 pub mod vision;
@@ -0,0 +1,98 @@
+// ==========================================================
+// 🚦 脚本生命周期 (状态 + 事件回调)
+// Idle -> Running <-> Paused -> Finished / Failed / Aborted
+// 每个脚本的状态保存在 registry 里，这里只有类型定义和宿主回调
+// ==========================================================
+
+use std::sync::{Arc, Mutex};
+
+use log::info;
+
+use crate::{registry::ScriptId, types::ScriptListener};
+
+/// 失败原因分类 (超限类错误单独区分，方便宿主提示 / 调整 RuntimeLimits)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ScriptErrorKind {
+    /// 普通 JS 异常
+    Exception,
+    /// 超出堆内存上限
+    OutOfMemory,
+    /// 超出调用栈上限
+    StackOverflow,
+    /// 超出总运行时长
+    Timeout,
+}
+
+/// 脚本当前状态 (script_status() 返回)
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum ScriptStatus {
+    Idle,
+    Running,
+    Paused,
+    Finished,
+    Failed {
+        kind: ScriptErrorKind,
+        message: String,
+        stack: Option<String>,
+    },
+    Aborted,
+}
+
+/// 推送给宿主的生命周期事件
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum ScriptEvent {
+    Started,
+    Paused,
+    Resumed,
+    Finished,
+    Failed {
+        kind: ScriptErrorKind,
+        message: String,
+        stack: Option<String>,
+    },
+    Aborted,
+}
+
+lazy_static::lazy_static! {
+    static ref LISTENER: Mutex<Option<Arc<dyn ScriptListener>>> = Mutex::new(None);
+}
+
+impl ScriptStatus {
+    /// 运行中 (含暂停)
+    pub fn is_active(&self) -> bool {
+        matches!(self, ScriptStatus::Running | ScriptStatus::Paused)
+    }
+}
+
+impl ScriptEvent {
+    /// 结束类事件对应的最终状态 (Started / Paused / Resumed 返回 None)
+    pub fn final_status(&self) -> Option<ScriptStatus> {
+        match self {
+            ScriptEvent::Finished => Some(ScriptStatus::Finished),
+            ScriptEvent::Failed {
+                kind,
+                message,
+                stack,
+            } => Some(ScriptStatus::Failed {
+                kind: *kind,
+                message: message.clone(),
+                stack: stack.clone(),
+            }),
+            ScriptEvent::Aborted => Some(ScriptStatus::Aborted),
+            _ => None,
+        }
+    }
+}
+
+pub fn set_listener(listener: Option<Box<dyn ScriptListener>>) {
+    *LISTENER.lock().unwrap() = listener.map(Arc::from);
+}
+
+/// 通知宿主。调用方不能持有注册表的锁 (宿主可能在回调里再查状态)
+pub fn emit(script_id: ScriptId, event: ScriptEvent) {
+    info!("Script #{} event: {:?}", script_id, event);
+    let listener = LISTENER.lock().unwrap().clone();
+    if let Some(listener) = listener {
+        listener.on_event(script_id, event);
+    }
+}
@@ -0,0 +1,211 @@
+// ==========================================================
+// 📜 脚本清单 (manifest.json)
+// 显式声明入口、依赖的引擎版本、权限、分辨率和配置项，运行前统一校验
+//
+// {
+//   "name": "Legend",
+//   "version": "1.0.0",
+//   "main": "index.js",
+//   "entry": "main",
+//   "minApiVersion": 1,
+//   "permissions": ["root"],
+//   "resolution": { "width": 540, "height": 960 },
+//   "config": [
+//     { "key": "loops", "type": "int", "default": 3, "label": "循环次数" }
+//   ],
+//   "network": { "allowedHosts": ["192.168.1.10:8080", "*.lan"], "timeoutMs": 5000 }
+// }
+// ==========================================================
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::bundle::BundleError;
+use crate::constants::ENGINE_API_VERSION;
+use crate::input::InputMode;
+use crate::net;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    /// 入口模块 (相对包根目录，仅脚本包模式使用)
+    #[serde(default = "default_main")]
+    pub main: String,
+    /// 入口函数名 (模块导出名 / GameScript 上的属性名)
+    #[serde(default = "default_entry")]
+    pub entry: String,
+    /// 需要的最低引擎 API 版本
+    #[serde(default)]
+    pub min_api_version: u32,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    /// 脚本编写时的逻辑分辨率 (即截图缓冲区尺寸)，不填则不校验
+    #[serde(default)]
+    pub resolution: Option<Resolution>,
+    #[serde(default)]
+    pub config: Vec<ConfigField>,
+    /// fetch 的访问限制，不写则脚本不能发网络请求
+    #[serde(default)]
+    pub network: NetworkConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    Root,
+    Accessibility,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigType {
+    String,
+    Int,
+    Float,
+    Bool,
+    List,
+}
+
+/// 配置项声明
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigField {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub kind: ConfigType,
+    #[serde(default)]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// 网络访问声明
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConfig {
+    /// 允许访问的主机: "host" / "host:port" / "*.lan"
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// 单个请求的超时上限，默认 net::DEFAULT_TIMEOUT_MS
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// 响应体大小上限，默认 net::DEFAULT_MAX_RESPONSE_BYTES
+    #[serde(default)]
+    pub max_response_bytes: Option<u64>,
+}
+
+fn default_main() -> String {
+    "index.js".to_string()
+}
+
+fn default_entry() -> String {
+    "main".to_string()
+}
+
+/// 运行环境快照，用于校验
+pub struct RuntimeEnv {
+    /// None = 控制器未初始化
+    pub mode: Option<InputMode>,
+    /// 当前截图缓冲区尺寸，None = 还没有画面
+    pub screen: Option<(u32, u32)>,
+}
+
+impl ConfigType {
+    /// 判断 JSON 值是否符合该类型
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ConfigType::String => value.is_string(),
+            ConfigType::Int => value.is_i64() || value.is_u64(),
+            ConfigType::Float => value.is_number(),
+            ConfigType::Bool => value.is_boolean(),
+            ConfigType::List => value.is_array(),
+        }
+    }
+}
+
+impl ScriptManifest {
+    pub fn parse(raw: &[u8]) -> Result<Self, BundleError> {
+        let manifest: Self =
+            serde_json::from_slice(raw).map_err(|e| BundleError::ManifestError(e.to_string()))?;
+        if manifest.entry.is_empty() {
+            return Err(BundleError::ManifestError(
+                "`entry` must not be empty".into(),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    /// 运行前校验: API 版本 / 权限 / 分辨率 / 配置声明 / 网络白名单
+    pub fn validate(&self, env: &RuntimeEnv) -> Result<(), BundleError> {
+        if self.min_api_version > ENGINE_API_VERSION {
+            return Err(BundleError::ApiVersionTooLow {
+                required: self.min_api_version,
+                current: ENGINE_API_VERSION,
+            });
+        }
+
+        for permission in &self.permissions {
+            let granted = match (permission, env.mode) {
+                // 录制模式用于 dry run，放行所有权限
+                (_, Some(InputMode::Recording)) => true,
+                (Permission::Root, Some(InputMode::Root)) => true,
+                (Permission::Accessibility, Some(InputMode::Accessibility)) => true,
+                _ => false,
+            };
+            if !granted {
+                return Err(BundleError::PermissionMissing {
+                    permission: format!("{:?}", permission).to_lowercase(),
+                });
+            }
+        }
+
+        if let (Some(expected), Some((w, h))) = (self.resolution, env.screen) {
+            // 横竖屏互换视为一致
+            let same = (expected.width, expected.height) == (w, h)
+                || (expected.height, expected.width) == (w, h);
+            if !same {
+                return Err(BundleError::ResolutionMismatch {
+                    expected_width: expected.width,
+                    expected_height: expected.height,
+                    actual_width: w,
+                    actual_height: h,
+                });
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for field in &self.config {
+            if !seen.insert(field.key.as_str()) {
+                return Err(BundleError::InvalidConfigSchema(format!(
+                    "duplicate key `{}`",
+                    field.key
+                )));
+            }
+            if let Some(default) = &field.default {
+                if !field.kind.accepts(default) {
+                    return Err(BundleError::InvalidConfigSchema(format!(
+                        "default of `{}` is not a {:?}",
+                        field.key, field.kind
+                    )));
+                }
+            }
+        }
+
+        if let Some(pattern) = net::invalid_host_pattern(&self.network) {
+            return Err(BundleError::ManifestError(format!(
+                "invalid network.allowedHosts entry `{}`",
+                pattern
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,647 @@
+// ==========================================================
+// 🌐 脚本网络请求 (最小 HTTP/1.1 客户端，跑在现有 tokio 运行时上)
+// 用于向局域网看板上报进度 / 从本地服务拉配置:
+// - 只支持 http://，不跟随重定向 (3xx 原样返回)
+// - 只能访问清单 network.allowedHosts 声明的主机
+// - 整个请求 (连接 + 发送 + 接收) 受超时限制，请求体 / 响应体有大小上限
+// ==========================================================
+
+use std::time::Duration;
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+use crate::manifest::NetworkConfig;
+
+/// 清单没写 timeoutMs 时的超时
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+/// 清单没写 maxResponseBytes 时的响应体上限
+pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 1024 * 1024;
+/// 请求体上限
+pub const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+/// 状态行 + 响应头的上限
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Only http:// URLs are supported: {0}")]
+    UnsupportedScheme(String),
+
+    #[error("Host `{0}` is not listed in the manifest's network.allowedHosts")]
+    HostNotAllowed(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Request timed out after {0} ms")]
+    Timeout(u64),
+
+    #[error("{what} exceeds the limit of {limit} bytes")]
+    TooLarge { what: &'static str, limit: u64 },
+
+    #[error("Connection failed: {0}")]
+    IoError(String),
+
+    #[error("Invalid HTTP response: {0}")]
+    InvalidResponse(String),
+}
+
+impl From<std::io::Error> for NetError {
+    fn from(e: std::io::Error) -> Self {
+        NetError::IoError(e.to_string())
+    }
+}
+
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    /// 名字已转小写，保持服务端的顺序
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// 解析后的 http:// 地址
+struct Url {
+    host: String,
+    port: u16,
+    /// 路径 + 查询串 (去掉了 #片段)
+    target: String,
+}
+
+// ==========================================
+// 主机白名单
+// ==========================================
+
+/// 白名单条目: "host" (任意端口) / "host:port" / "*.lan" (子域名) / "[::1]:8080"
+struct HostPattern {
+    host: String,
+    port: Option<u16>,
+}
+
+impl HostPattern {
+    fn parse(pattern: &str) -> Option<Self> {
+        let (host, port) = split_host_port(pattern)?;
+        let name = host.strip_prefix("*.").unwrap_or(&host);
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':'));
+        valid.then_some(Self { host, port })
+    }
+
+    fn matches(&self, host: &str, port: u16) -> bool {
+        if self.port.is_some_and(|p| p != port) {
+            return false;
+        }
+        match self.host.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+            None => self.host == host,
+        }
+    }
+}
+
+/// 校验清单里的白名单条目，返回第一个非法条目
+pub fn invalid_host_pattern(config: &NetworkConfig) -> Option<&str> {
+    config
+        .allowed_hosts
+        .iter()
+        .find(|p| HostPattern::parse(p).is_none())
+        .map(String::as_str)
+}
+
+fn is_allowed(config: &NetworkConfig, host: &str, port: u16) -> bool {
+    config
+        .allowed_hosts
+        .iter()
+        .filter_map(|p| HostPattern::parse(p))
+        .any(|p| p.matches(host, port))
+}
+
+/// "host:port" / "[v6]:port" 拆开，主机名转小写；端口非法返回 None
+fn split_host_port(authority: &str) -> Option<(String, Option<u16>)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        match rest {
+            "" => (host, None),
+            _ => (host, Some(rest.strip_prefix(':')?)),
+        }
+    } else {
+        match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        Some(p) => Some(p.parse::<u16>().ok().filter(|p| *p != 0)?),
+        None => None,
+    };
+    (!host.is_empty()).then(|| (host.to_ascii_lowercase(), port))
+}
+
+fn parse_url(url: &str) -> Result<Url, NetError> {
+    let invalid = || NetError::InvalidUrl(url.to_string());
+    let rest = match url.split_once("://") {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") => rest,
+        Some(_) => return Err(NetError::UnsupportedScheme(url.to_string())),
+        None => return Err(invalid()),
+    };
+    let split = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, target) = rest.split_at(split);
+    // 不支持 user:pass@host
+    if authority.contains('@') {
+        return Err(invalid());
+    }
+    let (host, port) = split_host_port(authority).ok_or_else(invalid)?;
+    let target = target.split('#').next().unwrap_or_default();
+    // 请求行里不能有空白 / 控制字符
+    if target.chars().any(|c| c <= ' ' || c == '\x7f') {
+        return Err(invalid());
+    }
+    let target = match target {
+        "" => "/".to_string(),
+        t if t.starts_with('?') => format!("/{}", t),
+        t => t.to_string(),
+    };
+    Ok(Url {
+        host,
+        port: port.unwrap_or(80),
+        target,
+    })
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+// ==========================================
+// 发送请求
+// ==========================================
+
+/// 发送请求并读取完整响应 (受白名单 / 超时 / 大小限制)
+/// timeout_ms: 脚本指定的超时，不能超过清单的上限
+pub async fn send(
+    request: &HttpRequest,
+    config: &NetworkConfig,
+    timeout_ms: Option<u64>,
+) -> Result<HttpResponse, NetError> {
+    let url = parse_url(&request.url)?;
+    if !is_allowed(config, &url.host, url.port) {
+        return Err(NetError::HostNotAllowed(format!(
+            "{}:{}",
+            url.host, url.port
+        )));
+    }
+    if !is_token(&request.method) {
+        return Err(NetError::InvalidRequest(format!(
+            "bad method `{}`",
+            request.method
+        )));
+    }
+    if request.body.len() > MAX_REQUEST_BYTES {
+        return Err(NetError::TooLarge {
+            what: "Request body",
+            limit: MAX_REQUEST_BYTES as u64,
+        });
+    }
+    let limit = config
+        .max_response_bytes
+        .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES);
+    let max_timeout = config.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    let timeout = timeout_ms.map_or(max_timeout, |t| t.min(max_timeout));
+    let raw = encode_request(request, &url)?;
+    let exchange = async {
+        let mut stream = TcpStream::connect((url.host.as_str(), url.port)).await?;
+        stream.write_all(&raw).await?;
+        read_response(stream, &request.method, limit).await
+    };
+    tokio::time::timeout(Duration::from_millis(timeout), exchange)
+        .await
+        .map_err(|_| NetError::Timeout(timeout))?
+}
+
+fn encode_request(request: &HttpRequest, url: &Url) -> Result<Vec<u8>, NetError> {
+    let host = match (url.host.contains(':'), url.port) {
+        (true, 80) => format!("[{}]", url.host),
+        (true, port) => format!("[{}]:{}", url.host, port),
+        (false, 80) => url.host.clone(),
+        (false, port) => format!("{}:{}", url.host, port),
+    };
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        request.method, url.target, host
+    );
+    let mut has_agent = false;
+    for (name, value) in &request.headers {
+        if !is_token(name) || value.contains(['\r', '\n']) {
+            return Err(NetError::InvalidRequest(format!("bad header `{}`", name)));
+        }
+        // 这几个由客户端自己管理
+        let lower = name.to_ascii_lowercase();
+        if matches!(
+            lower.as_str(),
+            "host" | "connection" | "content-length" | "transfer-encoding"
+        ) {
+            continue;
+        }
+        has_agent |= lower == "user-agent";
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if !has_agent {
+        head.push_str(&format!(
+            "User-Agent: touch_core/{}\r\n",
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+    if !request.body.is_empty() || !matches!(request.method.as_str(), "GET" | "HEAD") {
+        head.push_str(&format!("Content-Length: {}\r\n", request.body.len()));
+    }
+    head.push_str("\r\n");
+    let mut raw = head.into_bytes();
+    raw.extend_from_slice(&request.body);
+    Ok(raw)
+}
+
+async fn read_response(
+    stream: TcpStream,
+    method: &str,
+    limit: u64,
+) -> Result<HttpResponse, NetError> {
+    let mut reader = BufReader::new(stream);
+    let mut header_bytes = 0;
+
+    // 1xx 临时响应跳过，直到拿到最终状态
+    let (status, status_text, headers) = loop {
+        let line = read_head_line(&mut reader, &mut header_bytes).await?;
+        let mut parts = line.splitn(3, ' ');
+        let (Some(version), Some(code)) = (parts.next(), parts.next()) else {
+            return Err(NetError::InvalidResponse(line));
+        };
+        let status = match code.parse::<u16>() {
+            Ok(status) if version.starts_with("HTTP/1.") => status,
+            _ => return Err(NetError::InvalidResponse(line)),
+        };
+        let status_text = parts.next().unwrap_or_default().to_string();
+        let mut headers = Vec::new();
+        loop {
+            let line = read_head_line(&mut reader, &mut header_bytes).await?;
+            if line.is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                return Err(NetError::InvalidResponse(line));
+            };
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+        if !(100..200).contains(&status) {
+            break (status, status_text, headers);
+        }
+    };
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let too_large = || NetError::TooLarge {
+        what: "Response body",
+        limit,
+    };
+    let body = if method == "HEAD" || status == 204 || status == 304 {
+        Vec::new()
+    } else if header("transfer-encoding")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"))
+    {
+        read_chunked(&mut reader, limit, &mut header_bytes).await?
+    } else if let Some(length) = header("content-length") {
+        let length: u64 = length
+            .parse()
+            .map_err(|_| NetError::InvalidResponse(format!("bad content-length `{}`", length)))?;
+        if length > limit {
+            return Err(too_large());
+        }
+        let mut body = vec![0; length as usize];
+        reader.read_exact(&mut body).await?;
+        body
+    } else {
+        // 没有长度，读到连接关闭
+        let mut body = Vec::new();
+        reader.take(limit + 1).read_to_end(&mut body).await?;
+        if body.len() as u64 > limit {
+            return Err(too_large());
+        }
+        body
+    };
+
+    Ok(HttpResponse {
+        status,
+        status_text,
+        headers,
+        body,
+    })
+}
+
+/// 读一行响应头 (去掉 CRLF)，累计长度超限报错
+async fn read_head_line(
+    reader: &mut BufReader<TcpStream>,
+    total: &mut usize,
+) -> Result<String, NetError> {
+    let mut line = Vec::new();
+    let remaining = MAX_HEADER_BYTES.saturating_sub(*total) as u64;
+    (&mut *reader)
+        .take(remaining)
+        .read_until(b'\n', &mut line)
+        .await?;
+    *total += line.len();
+    if !line.ends_with(b"\n") {
+        return Err(if *total >= MAX_HEADER_BYTES {
+            NetError::TooLarge {
+                what: "Response headers",
+                limit: MAX_HEADER_BYTES as u64,
+            }
+        } else {
+            NetError::InvalidResponse("connection closed before headers ended".into())
+        });
+    }
+    let line = String::from_utf8_lossy(&line);
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn read_chunked(
+    reader: &mut BufReader<TcpStream>,
+    limit: u64,
+    header_bytes: &mut usize,
+) -> Result<Vec<u8>, NetError> {
+    let mut body = Vec::new();
+    loop {
+        // 块大小行也算进头部的长度限制里，防止无限长的行
+        *header_bytes = 0;
+        let line = read_head_line(reader, header_bytes).await?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = u64::from_str_radix(size, 16)
+            .map_err(|_| NetError::InvalidResponse(format!("bad chunk size `{}`", size)))?;
+        if size == 0 {
+            // 跳过 trailer
+            while !read_head_line(reader, header_bytes).await?.is_empty() {}
+            return Ok(body);
+        }
+        // 十六进制长度可以接近 u64::MAX，相加溢出同样按超限处理
+        if (body.len() as u64)
+            .checked_add(size)
+            .is_none_or(|total| total > limit)
+        {
+            return Err(NetError::TooLarge {
+                what: "Response body",
+                limit,
+            });
+        }
+        let start = body.len();
+        body.resize(start + size as usize, 0);
+        reader.read_exact(&mut body[start..]).await?;
+        let mut crlf = [0; 2];
+        reader.read_exact(&mut crlf).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{net::TcpListener, task::JoinHandle};
+
+    use super::*;
+
+    /// 本机假服务器: 接受一个连接，读完请求头后写回 response (None 表示一直不回)
+    /// 返回端口和收到的请求头
+    async fn serve(response: Option<Vec<u8>>) -> (u16, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let task = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            while reader.read_line(&mut head).await.unwrap() > 0 && !head.ends_with("\r\n\r\n") {}
+            match response {
+                Some(response) => {
+                    let _ = reader.get_mut().write_all(&response).await;
+                }
+                None => tokio::time::sleep(Duration::from_secs(5)).await,
+            }
+            head
+        });
+        (port, task)
+    }
+
+    fn config(hosts: &[&str]) -> NetworkConfig {
+        NetworkConfig {
+            allowed_hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            ..NetworkConfig::default()
+        }
+    }
+
+    fn get(url: String) -> HttpRequest {
+        HttpRequest {
+            method: "GET".into(),
+            url,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    #[test]
+    fn host_patterns() {
+        let lan = config(&["*.lan", "nas.local:8080", "[::1]:9000"]);
+        assert!(is_allowed(&lan, "nas.lan", 80));
+        assert!(is_allowed(&lan, "a.b.lan", 1234));
+        assert!(!is_allowed(&lan, "lan", 80));
+        assert!(!is_allowed(&lan, ".lan", 80));
+        assert!(!is_allowed(&lan, "evillan", 80));
+        assert!(!is_allowed(&lan, "nas.lan.evil.com", 80));
+        assert!(is_allowed(&lan, "nas.local", 8080));
+        assert!(!is_allowed(&lan, "nas.local", 80));
+        assert!(is_allowed(&lan, "::1", 9000));
+        assert!(!is_allowed(&lan, "::1", 9001));
+
+        assert_eq!(invalid_host_pattern(&lan), None);
+        assert_eq!(
+            invalid_host_pattern(&config(&["ok.lan", "bad host"])),
+            Some("bad host")
+        );
+        assert_eq!(invalid_host_pattern(&config(&["x:0"])), Some("x:0"));
+        assert_eq!(invalid_host_pattern(&config(&["*."])), Some("*."));
+    }
+
+    #[tokio::test]
+    async fn allowlist_pins_ports() {
+        let (port, server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok".to_vec(),
+        ))
+        .await;
+        let url = format!("http://127.0.0.1:{}/status?x=1#frag", port);
+
+        // 另一个端口 / 另一个主机: 不连接直接拒绝
+        let other_port = config(&[&format!("127.0.0.1:{}", port.wrapping_add(1).max(1))]);
+        assert!(matches!(
+            send(&get(url.clone()), &other_port, None).await,
+            Err(NetError::HostNotAllowed(_))
+        ));
+        assert!(matches!(
+            send(&get(url.clone()), &config(&["*.lan", "localhost"]), None).await,
+            Err(NetError::HostNotAllowed(_))
+        ));
+
+        let pinned = config(&[&format!("127.0.0.1:{}", port)]);
+        let response = send(&get(url), &pinned, None).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"ok");
+        let head = server.await.unwrap();
+        assert!(head.starts_with("GET /status?x=1 HTTP/1.1\r\n"), "{}", head);
+        assert!(head.contains(&format!("Host: 127.0.0.1:{}\r\n", port)));
+        assert!(head.contains("Connection: close\r\n"));
+    }
+
+    #[tokio::test]
+    async fn times_out_with_the_smaller_limit() {
+        let (port, _server) = serve(None).await;
+        let url = format!("http://127.0.0.1:{}/", port);
+        let mut cfg = config(&["127.0.0.1"]);
+
+        let started = std::time::Instant::now();
+        assert!(matches!(
+            send(&get(url.clone()), &cfg, Some(100)).await,
+            Err(NetError::Timeout(100))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // 脚本指定的超时不能超过清单的上限
+        let (port, _server) = serve(None).await;
+        let url = format!("http://127.0.0.1:{}/", port);
+        cfg.timeout_ms = Some(50);
+        assert!(matches!(
+            send(&get(url), &cfg, Some(60_000)).await,
+            Err(NetError::Timeout(50))
+        ));
+    }
+
+    #[tokio::test]
+    async fn content_length_limit() {
+        let mut cfg = config(&["127.0.0.1"]);
+        cfg.max_response_bytes = Some(10);
+
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n0123456789".to_vec(),
+        ))
+        .await;
+        let response = send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None)
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"0123456789");
+
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n0123456789A".to_vec(),
+        ))
+        .await;
+        assert!(matches!(
+            send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None).await,
+            Err(NetError::TooLarge { limit: 10, .. })
+        ));
+
+        // 没有长度时读到关闭为止，同样受限
+        let (port, _server) = serve(Some(b"HTTP/1.1 200 OK\r\n\r\n0123456789A".to_vec())).await;
+        assert!(matches!(
+            send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None).await,
+            Err(NetError::TooLarge { limit: 10, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn chunked_limit() {
+        let mut cfg = config(&["127.0.0.1"]);
+        cfg.max_response_bytes = Some(10);
+
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+              4;ext=1\r\nabcd\r\n6\r\nefghij\r\n0\r\nX-Trailer: 1\r\n\r\n"
+                .to_vec(),
+        ))
+        .await;
+        let response = send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None)
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"abcdefghij");
+
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+              6\r\nabcdef\r\n5\r\nghijk\r\n0\r\n\r\n"
+                .to_vec(),
+        ))
+        .await;
+        assert!(matches!(
+            send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None).await,
+            Err(NetError::TooLarge { limit: 10, .. })
+        ));
+
+        // 已读的长度加上接近 u64::MAX 的块长度会溢出
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+              1\r\na\r\nffffffffffffffff\r\n"
+                .to_vec(),
+        ))
+        .await;
+        assert!(matches!(
+            send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None).await,
+            Err(NetError::TooLarge { limit: 10, .. })
+        ));
+
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n".to_vec(),
+        ))
+        .await;
+        assert!(matches!(
+            send(&get(format!("http://127.0.0.1:{}/", port)), &cfg, None).await,
+            Err(NetError::InvalidResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn skips_informational_responses() {
+        let (port, _server) = serve(Some(
+            b"HTTP/1.1 100 Continue\r\n\r\n\
+              HTTP/1.1 103 Early Hints\r\nLink: </a.css>; rel=preload\r\n\r\n\
+              HTTP/1.1 201 Created\r\nX-Id: 7\r\nContent-Length: 2\r\n\r\nok"
+                .to_vec(),
+        ))
+        .await;
+        let response = send(
+            &get(format!("http://127.0.0.1:{}/", port)),
+            &config(&["127.0.0.1"]),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.status_text, "Created");
+        assert_eq!(
+            response.headers,
+            vec![
+                ("x-id".to_string(), "7".to_string()),
+                ("content-length".to_string(), "2".to_string())
+            ]
+        );
+        assert_eq!(response.body, b"ok");
+    }
+}
@@ -0,0 +1,398 @@
+// ==========================================================
+// 🗂️ 脚本注册表 (多脚本并行)
+// 每个脚本: 独立 id / 线程 + 运行时 / 暂停与停止句柄 / 状态
+// Main: 主脚本，同时只有一个，启动新的主脚本会停掉旧的
+// Helper: 辅助脚本 (弹窗看门狗、每日奖励...)，与主脚本并行
+// 谁能操作设备由 input::arbiter 决定
+// ==========================================================
+
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use log::info;
+use tokio::task::AbortHandle;
+
+use crate::{
+    input::arbiter,
+    js_engine::ScriptControl,
+    lifecycle::{self, ScriptErrorKind, ScriptEvent, ScriptStatus},
+};
+
+pub type ScriptId = u64;
+
+/// 最多保留多少个已结束脚本的状态 (供 list_scripts 查询)
+const MAX_FINISHED_ENTRIES: usize = 16;
+/// 等待脚本线程退出时的轮询间隔
+const EXIT_POLL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ScriptRole {
+    Main,
+    Helper,
+}
+
+/// 启动选项
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ScriptOptions {
+    /// 显示名，不填用清单名 / "main" / "helper"
+    pub name: Option<String>,
+    pub role: ScriptRole,
+    /// 输入仲裁优先级，越大越优先；不填: Main = 0, Helper = 10
+    /// (看门狗一类的辅助脚本需要打断主脚本去关弹窗)
+    pub input_priority: Option<i32>,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        Self {
+            name: None,
+            role: ScriptRole::Main,
+            input_priority: None,
+        }
+    }
+}
+
+impl ScriptOptions {
+    pub fn priority(&self) -> i32 {
+        self.input_priority.unwrap_or(match self.role {
+            ScriptRole::Main => 0,
+            ScriptRole::Helper => 10,
+        })
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ScriptInfo {
+    pub id: ScriptId,
+    pub name: String,
+    pub role: ScriptRole,
+    pub input_priority: i32,
+    pub status: ScriptStatus,
+}
+
+struct Entry {
+    name: String,
+    role: ScriptRole,
+    status: ScriptStatus,
+    control: ScriptControl,
+    abort: Option<AbortHandle>,
+    /// 脚本线程 (stop 只是置标志，线程真正退出前脚本可能还在执行最后一段同步代码)
+    thread: Option<JoinHandle<()>>,
+    /// 已登记、线程句柄还没挂上 (attach_thread / start_failed 之前)
+    starting: bool,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: ScriptId,
+    entries: BTreeMap<ScriptId, Entry>,
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+impl Entry {
+    fn info(&self, id: ScriptId) -> ScriptInfo {
+        ScriptInfo {
+            id,
+            name: self.name.clone(),
+            role: self.role,
+            input_priority: self.control.input_priority(),
+            status: self.status.clone(),
+        }
+    }
+
+    /// 置停止标志 (打断同步循环) 并 abort 任务 (打断 await)
+    fn stop(&mut self) {
+        self.control.cancel();
+        if let Some(abort) = self.abort.take() {
+            abort.abort();
+        }
+    }
+}
+
+impl Registry {
+    /// 只保留最近的若干个已结束脚本
+    /// 线程还没退出的不清理，否则 stop_all_and_wait 就等不到它了
+    fn prune(&mut self) {
+        let finished: Vec<ScriptId> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| {
+                !e.status.is_active()
+                    && !e.starting
+                    && e.thread.as_ref().is_none_or(|h| h.is_finished())
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_ENTRIES);
+        for id in finished.into_iter().take(excess) {
+            self.entries.remove(&id);
+        }
+    }
+}
+
+/// 登记一个新脚本 (状态 Running)，返回其 id 和控制句柄
+pub fn register(
+    name: String,
+    options: &ScriptOptions,
+    timeout: Option<std::time::Duration>,
+) -> (ScriptId, ScriptControl) {
+    let (id, control) = {
+        let mut registry = REGISTRY.lock().unwrap();
+        registry.next_id += 1;
+        let id = registry.next_id;
+        let control = ScriptControl::new(id, options.priority(), timeout);
+        registry.entries.insert(
+            id,
+            Entry {
+                name,
+                role: options.role,
+                status: ScriptStatus::Running,
+                control: control.clone(),
+                abort: None,
+                thread: None,
+                starting: true,
+            },
+        );
+        (id, control)
+    };
+    lifecycle::emit(id, ScriptEvent::Started);
+    (id, control)
+}
+
+/// 任务启动后登记 abort 句柄；脚本已经被停止 (启动期间调了 stop) 则立即 abort
+pub fn attach_task(id: ScriptId, abort: AbortHandle) {
+    let mut registry = REGISTRY.lock().unwrap();
+    match registry.entries.get_mut(&id) {
+        Some(entry) if entry.status.is_active() => entry.abort = Some(abort),
+        _ => abort.abort(),
+    }
+}
+
+/// 登记脚本线程，供 stop_all_and_wait 等待退出
+pub fn attach_thread(id: ScriptId, handle: JoinHandle<()>) {
+    let mut registry = REGISTRY.lock().unwrap();
+    if let Some(entry) = registry.entries.get_mut(&id) {
+        entry.thread = Some(handle);
+        entry.starting = false;
+    }
+}
+
+/// 脚本线程没能启动 (限制不合法 / 创建线程失败)，没有线程可等，直接按失败结束
+pub fn start_failed(id: ScriptId, message: String) {
+    if let Some(entry) = REGISTRY.lock().unwrap().entries.get_mut(&id) {
+        entry.starting = false;
+    }
+    end(
+        id,
+        ScriptEvent::Failed {
+            kind: ScriptErrorKind::Exception,
+            message,
+            stack: None,
+        },
+    );
+}
+
+/// 脚本结束 (Finished / Failed / Aborted)
+/// 已经结束过 (例如先被 stop 再自然退出) 则忽略
+pub fn end(id: ScriptId, event: ScriptEvent) {
+    let Some(status) = event.final_status() else {
+        return;
+    };
+    {
+        let mut registry = REGISTRY.lock().unwrap();
+        let Some(entry) = registry.entries.get_mut(&id) else {
+            return;
+        };
+        if !entry.status.is_active() {
+            return;
+        }
+        entry.status = status;
+        entry.abort = None;
+        registry.prune();
+    }
+    arbiter::release_all(id);
+    lifecycle::emit(id, event);
+}
+
+/// 停止一个脚本，返回是否找到运行中的脚本
+pub fn stop(id: ScriptId) -> bool {
+    let found = {
+        let mut registry = REGISTRY.lock().unwrap();
+        match registry.entries.get_mut(&id) {
+            Some(entry) if entry.status.is_active() => {
+                info!("🛑 Stopping script #{} [{}]", id, entry.name);
+                entry.stop();
+                true
+            }
+            _ => false,
+        }
+    };
+    if found {
+        end(id, ScriptEvent::Aborted);
+    }
+    found
+}
+
+/// 停止所有 (role = None) 或指定角色的运行中脚本
+pub fn stop_all(role: Option<ScriptRole>) {
+    for id in active_ids(role) {
+        stop(id);
+    }
+}
+
+/// 停止并等待脚本线程真正退出 (包括之前已经被停止、还没退出的)
+/// 超时返回 false，此时旧脚本可能仍在执行
+pub fn stop_all_and_wait(role: Option<ScriptRole>, timeout: Duration) -> bool {
+    stop_all(role);
+    let deadline = Instant::now() + timeout;
+    loop {
+        let running = {
+            let registry = REGISTRY.lock().unwrap();
+            registry.entries.values().any(|e| {
+                role.is_none_or(|r| e.role == r)
+                    && e.thread.as_ref().is_some_and(|h| !h.is_finished())
+            })
+        };
+        if !running {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(EXIT_POLL);
+    }
+}
+
+/// 暂停 / 恢复一个脚本 (重复调用不会重复发事件)，返回是否找到运行中的脚本
+pub fn set_paused(id: ScriptId, paused: bool) -> bool {
+    let event = {
+        let mut registry = REGISTRY.lock().unwrap();
+        let Some(entry) = registry.entries.get_mut(&id) else {
+            return false;
+        };
+        let event = match (&entry.status, paused) {
+            (ScriptStatus::Running, true) => ScriptEvent::Paused,
+            (ScriptStatus::Paused, false) => ScriptEvent::Resumed,
+            (ScriptStatus::Running, false) | (ScriptStatus::Paused, true) => return true,
+            _ => return false,
+        };
+        entry.control.set_paused(paused);
+        entry.status = if paused {
+            ScriptStatus::Paused
+        } else {
+            ScriptStatus::Running
+        };
+        event
+    };
+    lifecycle::emit(id, event);
+    true
+}
+
+pub fn set_paused_all(paused: bool) {
+    for id in active_ids(None) {
+        set_paused(id, paused);
+    }
+}
+
+pub fn status(id: ScriptId) -> Option<ScriptStatus> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.entries.get(&id).map(|e| e.status.clone())
+}
+
+pub fn role(id: ScriptId) -> Option<ScriptRole> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.entries.get(&id).map(|e| e.role)
+}
+
+pub fn name(id: ScriptId) -> Option<String> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.entries.get(&id).map(|e| e.name.clone())
+}
+
+/// 最近一次启动的主脚本的状态 (没有则 Idle)
+pub fn main_status() -> ScriptStatus {
+    let registry = REGISTRY.lock().unwrap();
+    registry
+        .entries
+        .values()
+        .rev()
+        .find(|e| e.role == ScriptRole::Main)
+        .map(|e| e.status.clone())
+        .unwrap_or(ScriptStatus::Idle)
+}
+
+pub fn list() -> Vec<ScriptInfo> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.entries.iter().map(|(id, e)| e.info(*id)).collect()
+}
+
+fn active_ids(role: Option<ScriptRole>) -> Vec<ScriptId> {
+    let registry = REGISTRY.lock().unwrap();
+    registry
+        .entries
+        .iter()
+        .filter(|(_, e)| e.status.is_active() && role.is_none_or(|r| e.role == r))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    fn finished_entry(thread: Option<JoinHandle<()>>, starting: bool) -> Entry {
+        Entry {
+            name: String::new(),
+            role: ScriptRole::Helper,
+            status: ScriptStatus::Aborted,
+            control: ScriptControl::default(),
+            abort: None,
+            thread,
+            starting,
+        }
+    }
+
+    #[test]
+    fn prune_keeps_entries_whose_thread_is_still_running() {
+        let (release, wait) = mpsc::channel::<()>();
+        let mut registry = Registry::default();
+        // 已停止但线程还在跑 / 还没挂上线程: 不能清理
+        let running = thread::spawn(move || {
+            let _ = wait.recv();
+        });
+        registry
+            .entries
+            .insert(1, finished_entry(Some(running), false));
+        registry.entries.insert(2, finished_entry(None, true));
+        for id in 3..(3 + MAX_FINISHED_ENTRIES as u64 + 4) {
+            registry.entries.insert(id, finished_entry(None, false));
+        }
+
+        registry.prune();
+        assert!(registry.entries.contains_key(&1));
+        assert!(registry.entries.contains_key(&2));
+        let prunable = registry
+            .entries
+            .values()
+            .filter(|e| e.thread.is_none() && !e.starting);
+        assert_eq!(prunable.count(), MAX_FINISHED_ENTRIES);
+
+        // 线程退出后可以清理
+        release.send(()).unwrap();
+        let entry = registry.entries.get_mut(&1).unwrap();
+        while !entry.thread.as_ref().unwrap().is_finished() {
+            thread::sleep(EXIT_POLL);
+        }
+        registry.prune();
+        assert!(!registry.entries.contains_key(&1));
+    }
+}
@@ -0,0 +1,414 @@
+// ==========================================================
+// 📂 脚本文件沙箱
+// 脚本只能访问两个根目录:
+//   bundle://path  脚本包内的文件 (只读，只有脚本包模式才有)
+//   data://path    脚本自己的数据目录 <数据目录>/files/<命名空间>/ (读写)
+//   不带前缀的相对路径等同于 data://
+// 绝对路径、越过根目录的 "..", 指向根目录外的符号链接都会被拒绝
+// JS 的 Files 和视觉 API (模板图 / 字库) 都通过这里读文件
+// ==========================================================
+
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::{
+    bundle::{normalize_path, BundleError, ScriptBundle},
+    data_dir,
+};
+
+const FILES_DIR: &str = "files";
+const BUNDLE_SCHEME: &str = "bundle://";
+const DATA_SCHEME: &str = "data://";
+
+#[derive(Debug, thiserror::Error)]
+pub enum FileError {
+    #[error("Path is outside the script sandbox: {0}")]
+    InvalidPath(String),
+
+    #[error("File not found: {0}")]
+    NotFound(String),
+
+    #[error("bundle:// is read-only: {0}")]
+    ReadOnly(String),
+
+    #[error("bundle:// is only available when running a script bundle")]
+    NoBundle,
+
+    #[error("Data directory is not configured (set_data_dir)")]
+    NoDataDir,
+
+    #[error("File operation failed: {0}")]
+    IoError(String),
+}
+
+/// 解析后的位置 (路径已规整，不含 "." / "..")
+enum Location {
+    Bundle(String),
+    Data(String),
+}
+
+pub struct Sandbox {
+    bundle: Option<Arc<ScriptBundle>>,
+    /// 脚本的数据目录 (没设置 App 数据目录时为 None)
+    data_root: Option<PathBuf>,
+}
+
+impl Sandbox {
+    /// namespace: 脚本的命名空间 (和 Storage 一样，见 ScriptSource::namespace)
+    pub fn new(bundle: Option<Arc<ScriptBundle>>, namespace: &str) -> Self {
+        Self {
+            bundle,
+            data_root: data_dir::subdir(FILES_DIR)
+                .map(|dir| dir.join(data_dir::sanitize(namespace))),
+        }
+    }
+
+    fn parse(&self, path: &str) -> Result<Location, FileError> {
+        let invalid = || FileError::InvalidPath(path.to_string());
+        let (bundle, rest) = match path.strip_prefix(BUNDLE_SCHEME) {
+            Some(rest) => (true, rest),
+            None => (false, path.strip_prefix(DATA_SCHEME).unwrap_or(path)),
+        };
+        // 不接受绝对路径 (含 Windows 盘符)，避免误以为能访问外部文件
+        if rest.starts_with(['/', '\\']) || rest.contains(':') {
+            return Err(invalid());
+        }
+        let key = normalize_path("", rest).map_err(|_| invalid())?;
+        Ok(if bundle {
+            Location::Bundle(key)
+        } else {
+            Location::Data(key)
+        })
+    }
+
+    fn bundle(&self) -> Result<&ScriptBundle, FileError> {
+        self.bundle.as_deref().ok_or(FileError::NoBundle)
+    }
+
+    /// 数据目录下的真实路径，并确认没有通过符号链接跑到根目录外
+    fn data_path(&self, key: &str) -> Result<PathBuf, FileError> {
+        let root = self.data_root.as_ref().ok_or(FileError::NoDataDir)?;
+        let path = root.join(key);
+        let Ok(canonical_root) = root.canonicalize() else {
+            // 根目录还不存在，里面自然也没有链接
+            return Ok(path);
+        };
+        // 找到最深的已存在的祖先 (含自身)，解析链接后必须仍在根目录下
+        let existing = path.ancestors().find(|p| p.exists()).unwrap_or(root);
+        let resolved = existing
+            .canonicalize()
+            .map_err(|e| FileError::IoError(e.to_string()))?;
+        if !resolved.starts_with(&canonical_root) {
+            return Err(FileError::InvalidPath(key.to_string()));
+        }
+        Ok(path)
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        match self.parse(path)? {
+            Location::Bundle(key) => self.bundle()?.read(&key).map_err(|e| match e {
+                BundleError::NotFound(_) => FileError::NotFound(path.to_string()),
+                e => FileError::IoError(e.to_string()),
+            }),
+            Location::Data(key) => fs::read(self.data_path(&key)?).map_err(|e| io_error(e, path)),
+        }
+    }
+
+    pub fn read_text(&self, path: &str) -> Result<String, FileError> {
+        String::from_utf8(self.read(path)?).map_err(|e| FileError::IoError(e.to_string()))
+    }
+
+    /// 写入 (覆盖或追加)，自动创建父目录
+    pub fn write(&self, path: &str, data: &[u8], append: bool) -> Result<(), FileError> {
+        let key = match self.parse(path)? {
+            Location::Bundle(_) => return Err(FileError::ReadOnly(path.to_string())),
+            Location::Data(key) if key.is_empty() => {
+                return Err(FileError::InvalidPath(path.to_string()))
+            }
+            Location::Data(key) => key,
+        };
+        let file = self.data_path(&key)?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).map_err(|e| io_error(e, path))?;
+        }
+        let mut handle = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&file)
+            .map_err(|e| io_error(e, path))?;
+        handle.write_all(data).map_err(|e| io_error(e, path))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        match self.parse(path) {
+            Ok(Location::Bundle(key)) => self.bundle.as_ref().is_some_and(|b| b.exists(&key)),
+            Ok(Location::Data(key)) => self.data_path(&key).is_ok_and(|p| p.exists()),
+            Err(_) => false,
+        }
+    }
+
+    /// 列出目录下的条目 (子目录以 "/" 结尾)，按名字排序；目录不存在返回空
+    pub fn list(&self, dir: &str) -> Result<Vec<String>, FileError> {
+        let mut names = match self.parse(dir)? {
+            Location::Bundle(key) => self.bundle()?.list(&key),
+            Location::Data(key) => {
+                let path = self.data_path(&key)?;
+                match fs::read_dir(&path) {
+                    Ok(entries) => entries
+                        .flatten()
+                        .map(|entry| {
+                            let name = entry.file_name().to_string_lossy().into_owned();
+                            if entry.path().is_dir() {
+                                format!("{}/", name)
+                            } else {
+                                name
+                            }
+                        })
+                        .collect(),
+                    Err(e) if e.kind() == ErrorKind::NotFound => vec![],
+                    Err(e) => return Err(io_error(e, dir)),
+                }
+            }
+        };
+        names.sort();
+        Ok(names)
+    }
+
+    /// 删除文件或目录 (递归)，返回是否存在
+    pub fn remove(&self, path: &str) -> Result<bool, FileError> {
+        let key = match self.parse(path)? {
+            Location::Bundle(_) => return Err(FileError::ReadOnly(path.to_string())),
+            // 不允许删除根目录本身
+            Location::Data(key) if key.is_empty() => {
+                return Err(FileError::InvalidPath(path.to_string()))
+            }
+            Location::Data(key) => key,
+        };
+        let file = self.data_path(&key)?;
+        let result = if file.is_dir() {
+            fs::remove_dir_all(&file)
+        } else {
+            fs::remove_file(&file)
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(io_error(e, path)),
+        }
+    }
+}
+
+fn io_error(e: std::io::Error, path: &str) -> FileError {
+    match e.kind() {
+        ErrorKind::NotFound => FileError::NotFound(path.to_string()),
+        _ => FileError::IoError(format!("{}: {}", path, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        path::Path,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+
+    /// 测试用临时目录，drop 时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static NEXT: AtomicU32 = AtomicU32::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "touch_sandbox_{}_{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn sandbox(root: &Path, bundle: Option<ScriptBundle>) -> Sandbox {
+        Sandbox {
+            bundle: bundle.map(Arc::new),
+            data_root: Some(root.to_path_buf()),
+        }
+    }
+
+    fn demo_bundle() -> ScriptBundle {
+        ScriptBundle::from_files(HashMap::from([
+            (
+                "manifest.json".to_string(),
+                br#"{"name":"demo","main":"main.js","entry":"main"}"#.to_vec(),
+            ),
+            ("main.js".to_string(), b"export function main() {}".to_vec()),
+            ("img/ok.png".to_string(), b"png".to_vec()),
+        ]))
+        .unwrap()
+    }
+
+    fn is_invalid<T: std::fmt::Debug>(result: Result<T, FileError>) -> bool {
+        matches!(result, Err(FileError::InvalidPath(_)))
+    }
+
+    #[test]
+    fn rejects_parent_dir_escape() {
+        let tmp = TempDir::new();
+        let root = tmp.0.join("ns");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(tmp.0.join("secret.txt"), "secret").unwrap();
+        let sb = sandbox(&root, None);
+
+        assert!(is_invalid(sb.read("../secret.txt")));
+        assert!(is_invalid(sb.read("data://../secret.txt")));
+        assert!(is_invalid(sb.read("a/../../secret.txt")));
+        assert!(is_invalid(sb.read("..\\secret.txt")));
+        assert!(is_invalid(sb.write("../evil.txt", b"x", false)));
+        assert!(is_invalid(sb.list("..")));
+        assert!(is_invalid(sb.remove("../secret.txt")));
+        assert!(!sb.exists("../secret.txt"));
+        assert!(!tmp.0.join("evil.txt").exists());
+        assert!(tmp.0.join("secret.txt").exists());
+
+        // 不越过根目录的 ".." 照常可用
+        sb.write("a/../b.txt", b"ok", false).unwrap();
+        assert_eq!(sb.read_text("b.txt").unwrap(), "ok");
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        let tmp = TempDir::new();
+        let sb = sandbox(&tmp.0, Some(demo_bundle()));
+        for path in [
+            "/etc/passwd",
+            "data:///etc/passwd",
+            "bundle:///main.js",
+            "\\windows\\win.ini",
+            "C:\\windows\\win.ini",
+            "c:/x.txt",
+        ] {
+            assert!(is_invalid(sb.read(path)), "{}", path);
+            assert!(!sb.exists(path), "{}", path);
+        }
+        assert!(is_invalid(sb.write("/tmp/evil.txt", b"x", false)));
+        // 根目录本身不能被覆盖 / 删除
+        assert!(is_invalid(sb.write("data://", b"x", false)));
+        assert!(is_invalid(sb.remove("")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_pointing_outside() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new();
+        let root = tmp.0.join("ns");
+        let outside = tmp.0.join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+        symlink(&outside, root.join("dir_link")).unwrap();
+        symlink(outside.join("secret.txt"), root.join("file_link")).unwrap();
+        let sb = sandbox(&root, None);
+
+        assert!(is_invalid(sb.read("dir_link/secret.txt")));
+        assert!(is_invalid(sb.read("file_link")));
+        assert!(is_invalid(sb.write("dir_link/new.txt", b"x", false)));
+        assert!(is_invalid(sb.write("file_link", b"x", true)));
+        assert!(is_invalid(sb.list("dir_link")));
+        assert!(is_invalid(sb.remove("dir_link")));
+        assert!(!sb.exists("dir_link/secret.txt"));
+        assert!(!outside.join("new.txt").exists());
+        assert_eq!(
+            fs::read_to_string(outside.join("secret.txt")).unwrap(),
+            "secret"
+        );
+
+        // 指向根目录内的链接可以用
+        fs::write(root.join("real.txt"), "inside").unwrap();
+        symlink(root.join("real.txt"), root.join("inner_link")).unwrap();
+        assert_eq!(sb.read_text("inner_link").unwrap(), "inside");
+    }
+
+    #[test]
+    fn bundle_is_read_only() {
+        let tmp = TempDir::new();
+        let sb = sandbox(&tmp.0, Some(demo_bundle()));
+
+        assert_eq!(sb.read("bundle://img/ok.png").unwrap(), b"png");
+        assert!(sb.exists("bundle://main.js"));
+        assert_eq!(sb.list("bundle://img").unwrap(), vec!["ok.png"]);
+        assert!(matches!(
+            sb.write("bundle://main.js", b"x", false),
+            Err(FileError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            sb.write("bundle://new.js", b"x", true),
+            Err(FileError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            sb.remove("bundle://main.js"),
+            Err(FileError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            sb.read("bundle://missing.js"),
+            Err(FileError::NotFound(_))
+        ));
+        assert_eq!(
+            sb.read("bundle://main.js").unwrap(),
+            b"export function main() {}"
+        );
+        // 数据目录里没有被写入任何东西
+        assert!(fs::read_dir(&tmp.0).unwrap().next().is_none());
+
+        let no_bundle = sandbox(&tmp.0, None);
+        assert!(matches!(
+            no_bundle.read("bundle://main.js"),
+            Err(FileError::NoBundle)
+        ));
+        assert!(!no_bundle.exists("bundle://main.js"));
+    }
+
+    #[test]
+    fn missing_root_is_created_on_first_write() {
+        let tmp = TempDir::new();
+        let root = tmp.0.join("files").join("ns");
+        let sb = sandbox(&root, None);
+
+        assert!(!sb.exists("a.txt"));
+        assert!(matches!(sb.read("a.txt"), Err(FileError::NotFound(_))));
+        assert_eq!(sb.list("").unwrap(), Vec::<String>::new());
+        assert!(!sb.remove("a.txt").unwrap());
+        assert!(is_invalid(sb.read("../a.txt")));
+
+        sb.write("logs/a.txt", b"1", false).unwrap();
+        sb.write("logs/a.txt", b"2", true).unwrap();
+        assert_eq!(sb.read_text("data://logs/a.txt").unwrap(), "12");
+        assert_eq!(sb.list("").unwrap(), vec!["logs/"]);
+        assert!(root.join("logs/a.txt").is_file());
+        assert!(sb.remove("logs").unwrap());
+        assert!(!sb.exists("logs/a.txt"));
+
+        let no_data = Sandbox {
+            bundle: None,
+            data_root: None,
+        };
+        assert!(matches!(no_data.read("a.txt"), Err(FileError::NoDataDir)));
+    }
+}
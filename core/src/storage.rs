@@ -0,0 +1,357 @@
+// ==========================================================
+// 💾 脚本持久化存储 (Storage)
+// 每个脚本一个命名空间 (清单名，没有清单时是脚本内容的哈希，见 ScriptSource::namespace)，
+// 保存在 <数据目录>/storage/<命名空间 (百分号编码)>.json:
+//     { "key": { "value": <任意 JSON>, "expiresAt": 1700000000000 } }
+// - 读取时跳过已过期的条目，写入时顺便清掉
+// - 每次修改整份写入临时文件再 rename，写到一半崩溃不会损坏旧数据
+// - 写入成功后才更新内存里的缓存，写失败时内存和文件保持一致
+// - 同一个脚本 (同时运行的多个实例) 共享同一份数据
+// ==========================================================
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::data_dir;
+
+const STORAGE_DIR: &str = "storage";
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum StorageError {
+    #[error("Failed to write storage: {0}")]
+    IoError(String),
+
+    #[error("Invalid storage json: {0}")]
+    InvalidJson(String),
+}
+
+/// 宿主查看用的条目
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct StorageEntry {
+    pub key: String,
+    /// 值的 JSON 文本
+    pub json: String,
+    /// 过期时间 (Unix 毫秒)，None 表示不过期
+    pub expires_at_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    value: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<u64>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|t| t <= now)
+    }
+}
+
+/// 一个命名空间 (内存里的缓存 + 对应的文件)
+struct Namespace {
+    entries: BTreeMap<String, Entry>,
+    /// 没设置数据目录时为 None，只存在内存里
+    path: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref STORE: Mutex<HashMap<String, Namespace>> = Mutex::new(HashMap::new());
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn file_path(namespace: &str) -> Option<PathBuf> {
+    data_dir::subdir(STORAGE_DIR)
+        .map(|dir| dir.join(format!("{}.json", data_dir::sanitize(namespace))))
+}
+
+impl Namespace {
+    fn load(namespace: &str) -> Self {
+        let path = file_path(namespace);
+        let entries = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(text)) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                // 文件损坏: 留一份备份再从空白开始，不影响脚本运行
+                warn!(
+                    "Storage `{}` is corrupted, starting empty: {}",
+                    namespace, e
+                );
+                if let Some(path) = &path {
+                    let _ = fs::rename(path, path.with_extension("json.corrupt"));
+                }
+                BTreeMap::new()
+            }),
+            _ => BTreeMap::new(),
+        };
+        Self { entries, path }
+    }
+
+    fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.get(key).filter(|e| !e.is_expired(now_ms()))
+    }
+
+    /// 清掉过期条目后整份写入 (临时文件 + rename)，写入成功才替换缓存
+    fn save(&mut self, mut entries: BTreeMap<String, Entry>) -> Result<(), StorageError> {
+        let now = now_ms();
+        entries.retain(|_, e| !e.is_expired(now));
+        if let Some(path) = &self.path {
+            write_file(path, &entries)?;
+        }
+        self.entries = entries;
+        Ok(())
+    }
+}
+
+/// 整份写入临时文件再 rename 覆盖
+fn write_file(path: &Path, entries: &BTreeMap<String, Entry>) -> Result<(), StorageError> {
+    let io_error = |e: std::io::Error| StorageError::IoError(e.to_string());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    let json = serde_json::to_vec(entries).map_err(|e| StorageError::InvalidJson(e.to_string()))?;
+    let tmp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp).map_err(io_error)?;
+    file.write_all(&json).map_err(io_error)?;
+    file.sync_all().map_err(io_error)?;
+    fs::rename(&tmp, path).map_err(io_error)
+}
+
+/// 在命名空间上执行操作 (第一次访问时从磁盘载入)
+/// 文件名和命名空间一一对应 (data_dir::sanitize)，缓存直接按命名空间索引
+fn with_namespace<R>(namespace: &str, f: impl FnOnce(&mut Namespace) -> R) -> R {
+    let mut store = STORE.lock().unwrap();
+    let ns = store
+        .entry(namespace.to_string())
+        .or_insert_with(|| Namespace::load(namespace));
+    f(ns)
+}
+
+pub fn get(namespace: &str, key: &str) -> Option<Value> {
+    with_namespace(namespace, |ns| ns.get(key).map(|e| e.value.clone()))
+}
+
+/// ttl_ms: 多少毫秒后过期，None 不过期
+pub fn set(
+    namespace: &str,
+    key: &str,
+    value: Value,
+    ttl_ms: Option<u64>,
+) -> Result<(), StorageError> {
+    let entry = Entry {
+        value,
+        expires_at: ttl_ms.map(|ttl| now_ms().saturating_add(ttl)),
+    };
+    with_namespace(namespace, |ns| {
+        let mut entries = ns.entries.clone();
+        entries.insert(key.to_string(), entry);
+        ns.save(entries)
+    })
+}
+
+/// 返回是否存在 (未过期)
+pub fn remove(namespace: &str, key: &str) -> Result<bool, StorageError> {
+    with_namespace(namespace, |ns| {
+        let existed = ns.get(key).is_some();
+        if ns.entries.contains_key(key) {
+            let mut entries = ns.entries.clone();
+            entries.remove(key);
+            ns.save(entries)?;
+        }
+        Ok(existed)
+    })
+}
+
+pub fn keys(namespace: &str) -> Vec<String> {
+    let now = now_ms();
+    with_namespace(namespace, |ns| {
+        ns.entries
+            .iter()
+            .filter(|(_, e)| !e.is_expired(now))
+            .map(|(k, _)| k.clone())
+            .collect()
+    })
+}
+
+/// 剩余有效时间 (毫秒)，不存在或不过期返回 None
+pub fn ttl(namespace: &str, key: &str) -> Option<u64> {
+    let now = now_ms();
+    with_namespace(namespace, |ns| {
+        ns.get(key)
+            .and_then(|e| e.expires_at)
+            .map(|t| t.saturating_sub(now))
+    })
+}
+
+pub fn clear(namespace: &str) -> Result<(), StorageError> {
+    with_namespace(namespace, |ns| ns.save(BTreeMap::new()))
+}
+
+// ==========================================
+// 宿主接口
+// ==========================================
+
+/// 所有有数据的命名空间 (磁盘上的 + 只在内存里的)
+pub fn namespaces() -> Vec<String> {
+    let mut names: Vec<String> = STORE
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, ns)| !ns.entries.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect();
+    if let Some(Ok(dir)) = data_dir::subdir(STORAGE_DIR).map(fs::read_dir) {
+        for entry in dir.flatten() {
+            let file = entry.file_name().to_string_lossy().into_owned();
+            if let Some(name) = file.strip_suffix(".json").and_then(data_dir::unsanitize) {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn entries(namespace: &str) -> Vec<StorageEntry> {
+    let now = now_ms();
+    with_namespace(namespace, |ns| {
+        ns.entries
+            .iter()
+            .filter(|(_, e)| !e.is_expired(now))
+            .map(|(key, e)| StorageEntry {
+                key: key.clone(),
+                json: e.value.to_string(),
+                expires_at_ms: e.expires_at,
+            })
+            .collect()
+    })
+}
+
+/// 清空所有命名空间 (删除文件)
+pub fn clear_all() -> Result<(), StorageError> {
+    STORE.lock().unwrap().clear();
+    match data_dir::subdir(STORAGE_DIR) {
+        Some(dir) if dir.exists() => {
+            fs::remove_dir_all(&dir).map_err(|e| StorageError::IoError(e.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 数据目录变了，丢掉缓存 (下次访问从新目录载入)
+pub fn reset_cache() {
+    STORE.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static NEXT: AtomicU32 = AtomicU32::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "touch_storage_{}_{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entry(value: Value, expires_at: Option<u64>) -> Entry {
+        Entry { value, expires_at }
+    }
+
+    #[test]
+    fn expired_entries_are_hidden_and_dropped_on_save() {
+        let now = now_ms();
+        let mut ns = Namespace {
+            entries: BTreeMap::new(),
+            path: None,
+        };
+        let entries = BTreeMap::from([
+            ("old".to_string(), entry(Value::from(1), Some(now - 1))),
+            (
+                "live".to_string(),
+                entry(Value::from(2), Some(now + 60_000)),
+            ),
+            ("forever".to_string(), entry(Value::from(3), None)),
+        ]);
+        ns.entries = entries.clone();
+        assert!(ns.get("old").is_none());
+        assert_eq!(ns.get("live").unwrap().value, 2);
+        ns.save(entries).unwrap();
+        assert_eq!(ns.entries.keys().collect::<Vec<_>>(), ["forever", "live"]);
+
+        // 模块接口: ttl 为 0 立即过期
+        let name = format!("ttl-test-{}", now);
+        set(&name, "gone", Value::from(1), Some(0)).unwrap();
+        set(&name, "kept", Value::from(2), Some(60_000)).unwrap();
+        assert_eq!(get(&name, "gone"), None);
+        assert_eq!(keys(&name), ["kept"]);
+        assert!(ttl(&name, "kept").is_some_and(|ms| ms > 0 && ms <= 60_000));
+        assert!(!remove(&name, "gone").unwrap());
+    }
+
+    #[test]
+    fn save_writes_through_a_temp_file() {
+        let tmp = TempDir::new();
+        let path = tmp.0.join("storage").join("demo.json");
+        let mut ns = Namespace {
+            entries: BTreeMap::new(),
+            path: Some(path.clone()),
+        };
+        let entries = BTreeMap::from([("a".to_string(), entry(Value::from("x"), None))]);
+        ns.save(entries).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"a":{"value":"x"}}"#);
+        assert!(!path.with_extension("json.tmp").exists());
+        let loaded: BTreeMap<String, Entry> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded["a"].value, "x");
+    }
+
+    #[test]
+    fn failed_save_keeps_the_cache() {
+        let tmp = TempDir::new();
+        // 父目录位置被普通文件占了，写入必然失败
+        fs::write(tmp.0.join("storage"), "not a dir").unwrap();
+        let mut ns = Namespace {
+            entries: BTreeMap::from([("a".to_string(), entry(Value::from(1), None))]),
+            path: Some(tmp.0.join("storage").join("demo.json")),
+        };
+        let mut entries = ns.entries.clone();
+        entries.insert("b".to_string(), entry(Value::from(2), None));
+        assert!(ns.save(entries).is_err());
+        assert!(ns.save(BTreeMap::new()).is_err());
+        assert_eq!(ns.entries.keys().collect::<Vec<_>>(), ["a"]);
+    }
+}
@@ -8,6 +8,27 @@ pub trait PlatformLogger: Send + Sync {
 // ✋ 负责执行无障碍动作 (仅无障碍模式需要)
 #[uniffi::export(callback_interface)]
 pub trait AccessibilityService: Send + Sync {
-    fn dispatch_click(&self, x: i32, y: i32);
-    // 未来可以加: fn dispatch_swipe(...);
+    /// 返回手势是否成功派发 (服务未连接时返回 false)
+    fn dispatch_click(&self, x: i32, y: i32) -> bool;
+    /// 对当前获得焦点的输入框设置文字，没有可编辑焦点时返回 false
+    fn set_text(&self, text: String) -> bool;
+    /// 执行 AccessibilityService.performGlobalAction (返回/主页/最近任务...)
+    fn perform_global_action(&self, action: i32) -> bool;
+    /// 前台应用的包名 (最近一次 TYPE_WINDOW_STATE_CHANGED)，还不知道时返回 None
+    fn current_package(&self) -> Option<String>;
+    /// 前台 Activity 的完整类名，前台是对话框 / 悬浮窗等非 Activity 时返回 None
+    fn current_activity(&self) -> Option<String>;
+    /// 通过 getLaunchIntentForPackage 启动应用，未安装或没有启动入口时返回 false
+    fn launch_app(&self, package_name: String) -> bool;
+    /// 当前活动窗口的节点树快照 (JSON，格式见 ui.rs)，没有活动窗口时返回 None
+    fn dump_node_tree(&self) -> Option<String>;
+    /// 沿 points ([x, y] 列表) 派发一次单指滑动手势 (dispatchGesture)，返回是否派发成功
+    fn dispatch_swipe(&self, points: Vec<Vec<i32>>, duration_ms: u64) -> bool;
+}
+
+// 🚦 脚本生命周期回调 (开始/暂停/恢复/结束/失败/中止)，script_id 见 list_scripts()
+// 注意: 回调在脚本线程或调用 stop/pause 的线程上触发，更新 UI 需切回主线程
+#[uniffi::export(callback_interface)]
+pub trait ScriptListener: Send + Sync {
+    fn on_event(&self, script_id: u64, event: crate::lifecycle::ScriptEvent);
 }
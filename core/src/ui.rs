@@ -0,0 +1,178 @@
+// ==================================================
+// 🌳 界面节点树 (控件查找: 按文字 / id / 描述找按钮，适合非游戏应用)
+// 来源由控制器决定 (InputController::ui_snapshot):
+//   无障碍: AccessibilityService.dump_node_tree() 返回的 JSON 快照
+//   Root:   uiautomator dump 的 XML (ui/uiautomator.rs)
+// 两者都解析成同一个 UiNode，坐标是真实屏幕坐标 (给脚本时再换算成逻辑坐标)
+//
+// JSON 快照格式 (每个节点):
+//   { "className": "android.widget.Button", "text": "确定", "desc": null,
+//     "id": "com.app:id/ok", "packageName": "com.app", "bounds": [l, t, r, b],
+//     "clickable": true, "enabled": true, "checked": false, "selected": false,
+//     "focused": false, "scrollable": false, "children": [ ... ] }
+//   除 bounds 外都可以省略
+// ==================================================
+
+use serde::Deserialize;
+
+pub mod selector;
+pub mod uiautomator;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "[i32; 4]")]
+pub struct Bounds {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl From<[i32; 4]> for Bounds {
+    fn from([left, top, right, bottom]: [i32; 4]) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+}
+
+impl Bounds {
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        ((self.left + self.right) / 2, (self.top + self.bottom) / 2)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UiNode {
+    pub class_name: String,
+    pub text: Option<String>,
+    pub desc: Option<String>,
+    /// 资源 id (com.app:id/ok)
+    pub id: Option<String>,
+    pub package_name: String,
+    pub bounds: Bounds,
+    pub clickable: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub checked: bool,
+    pub selected: bool,
+    pub focused: bool,
+    pub scrollable: bool,
+    pub children: Vec<UiNode>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl UiNode {
+    /// 解析无障碍服务的 JSON 快照
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// 类名的最后一段 (android.widget.Button -> Button)
+    pub fn short_class_name(&self) -> &str {
+        self.class_name
+            .rsplit('.')
+            .next()
+            .unwrap_or(&self.class_name)
+    }
+
+    /// id 去掉 "包名:id/" 前缀的部分
+    pub fn short_id(&self) -> Option<&str> {
+        let id = self.id.as_deref()?;
+        Some(id.split_once(":id/").map_or(id, |(_, name)| name))
+    }
+
+    /// 先序遍历 (自身在前，子节点按顺序)
+    pub fn descendants(&self) -> Vec<&UiNode> {
+        let mut out = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            out.push(node);
+            stack.extend(node.children.iter().rev());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 无障碍快照 (设置页: 标题栏 + 列表 + 底部按钮，外加一个 Toast 窗口)
+    pub(crate) const SETTINGS: &str = include_str!("../tests/fixtures/ui/settings.json");
+
+    #[test]
+    fn parses_snapshot_with_defaults() {
+        let root = UiNode::from_json(SETTINGS).unwrap();
+        assert_eq!(root.class_name, "android.widget.FrameLayout");
+        assert_eq!(root.bounds, Bounds::from([0, 0, 1080, 2400]));
+        assert_eq!(root.children.len(), 2);
+
+        let nodes = root.descendants();
+        assert_eq!(nodes.len(), 15);
+        let title = nodes[3];
+        assert_eq!(title.text.as_deref(), Some("设置"));
+        // "desc": null 和缺省一样
+        assert_eq!(title.desc, None);
+        assert_eq!(title.short_id(), Some("title"));
+        assert_eq!(title.short_class_name(), "TextView");
+        // 省略的 enabled 默认为 true，其它布尔值默认为 false
+        assert!(title.enabled && !title.clickable && !title.checked);
+        // 省略 children 的叶子节点
+        let toast = nodes[14];
+        assert!(toast.children.is_empty());
+        assert_eq!(toast.short_class_name(), "Toast$TN");
+        assert_eq!(toast.package_name, "com.android.systemui");
+        assert_eq!(toast.bounds.center(), (540, 1945));
+        assert_eq!((toast.bounds.width(), toast.bounds.height()), (300, 90));
+    }
+
+    #[test]
+    fn descendants_are_pre_order() {
+        let root = UiNode::from_json(SETTINGS).unwrap();
+        let texts: Vec<&str> = root
+            .descendants()
+            .into_iter()
+            .filter_map(|n| n.text.as_deref())
+            .collect();
+        assert_eq!(
+            texts,
+            ["设置", "自动更新", "消息通知", "取消", "确定", "已保存"]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_snapshots() {
+        for json in [
+            "",
+            "null",
+            "\"View\"",
+            r#"{"className": "View", "bounds": [0, 0, 10]}"#,
+            r#"{"className": "View", "bounds": [0, 0, 10, "20"]}"#,
+            r#"{"className": "View", "bounds": [0, 0, 10, 20], "text": 5}"#,
+            r#"{"className": "View", "bounds": [0, 0, 10, 20], "children": {}}"#,
+            r#"{"className": "View", "bounds": [0, 0, 10, 20], "children": [{"bounds": null}]}"#,
+            r#"{"className": "View", "bounds": [0, 0, 10, 20], "children": ["#,
+        ] {
+            assert!(UiNode::from_json(json).is_err(), "{}", json);
+        }
+        // 未知字段忽略 (新版宿主多给的字段不影响旧版解析)
+        let node = UiNode::from_json(r#"{"bounds": [1, 2, 3, 4], "drawingOrder": 3}"#).unwrap();
+        assert_eq!(node.bounds, Bounds::from([1, 2, 3, 4]));
+        assert_eq!(node.short_id(), None);
+    }
+}
@@ -0,0 +1,211 @@
+// ==================================================
+// 🎯 控件选择器: 条件全部满足才算匹配，按先序 (从上到下) 返回
+// id / className 既可以写全名，也可以只写最后一段:
+//   id("ok") 匹配 "com.app:id/ok"，className("Button") 匹配 "android.widget.Button"
+// ==================================================
+
+use super::UiNode;
+
+#[derive(Debug, Clone)]
+pub enum Criterion {
+    Text(String),
+    TextContains(String),
+    Desc(String),
+    DescContains(String),
+    Id(String),
+    ClassName(String),
+    PackageName(String),
+    Clickable(bool),
+    Enabled(bool),
+    Checked(bool),
+    Selected(bool),
+    Scrollable(bool),
+}
+
+impl Criterion {
+    pub fn matches(&self, node: &UiNode) -> bool {
+        match self {
+            Self::Text(text) => node.text.as_deref() == Some(text),
+            Self::TextContains(part) => node.text.as_deref().is_some_and(|t| t.contains(part)),
+            Self::Desc(desc) => node.desc.as_deref() == Some(desc),
+            Self::DescContains(part) => node.desc.as_deref().is_some_and(|d| d.contains(part)),
+            Self::Id(id) => {
+                node.id.as_deref() == Some(id) || (!id.contains('/') && node.short_id() == Some(id))
+            }
+            Self::ClassName(name) => {
+                node.class_name == *name || (!name.contains('.') && node.short_class_name() == name)
+            }
+            Self::PackageName(name) => node.package_name == *name,
+            Self::Clickable(v) => node.clickable == *v,
+            Self::Enabled(v) => node.enabled == *v,
+            Self::Checked(v) => node.checked == *v,
+            Self::Selected(v) => node.selected == *v,
+            Self::Scrollable(v) => node.scrollable == *v,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Selector {
+    pub criteria: Vec<Criterion>,
+}
+
+impl Selector {
+    pub fn matches(&self, node: &UiNode) -> bool {
+        self.criteria.iter().all(|c| c.matches(node))
+    }
+
+    pub fn find_all<'a>(&self, root: &'a UiNode) -> Vec<&'a UiNode> {
+        root.descendants()
+            .into_iter()
+            .filter(|node| self.matches(node))
+            .collect()
+    }
+
+    pub fn find_one<'a>(&self, root: &'a UiNode) -> Option<&'a UiNode> {
+        root.descendants()
+            .into_iter()
+            .find(|node| self.matches(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::tests::SETTINGS;
+
+    fn root() -> UiNode {
+        UiNode::from_json(SETTINGS).unwrap()
+    }
+
+    fn select(criteria: Vec<Criterion>) -> Selector {
+        Selector { criteria }
+    }
+
+    fn texts<'a>(nodes: &[&'a UiNode]) -> Vec<&'a str> {
+        nodes
+            .iter()
+            .map(|n| n.text.as_deref().or(n.desc.as_deref()).unwrap_or("?"))
+            .collect()
+    }
+
+    #[test]
+    fn matches_text_and_desc() {
+        let root = root();
+        let ok = select(vec![Criterion::Text("确定".into())]);
+        assert_eq!(ok.find_one(&root).unwrap().short_id(), Some("ok"));
+        // text 是完全匹配
+        assert!(select(vec![Criterion::Text("确".into())])
+            .find_one(&root)
+            .is_none());
+        assert_eq!(
+            texts(&select(vec![Criterion::TextContains("通".into())]).find_all(&root)),
+            ["消息通知"]
+        );
+        assert_eq!(
+            select(vec![Criterion::Desc("返回".into())])
+                .find_one(&root)
+                .unwrap()
+                .short_class_name(),
+            "ImageButton"
+        );
+        assert_eq!(
+            texts(&select(vec![Criterion::DescContains("开关".into())]).find_all(&root)),
+            ["自动更新开关", "消息通知开关"]
+        );
+        // 没有 text 的节点不匹配任何 text 条件 (包括空字符串)
+        assert!(select(vec![Criterion::TextContains(String::new())])
+            .find_all(&root)
+            .iter()
+            .all(|n| n.text.is_some()));
+    }
+
+    #[test]
+    fn matches_full_or_short_id_and_class_name() {
+        let root = root();
+        let by_id = |id: &str| select(vec![Criterion::Id(id.into())]).find_all(&root).len();
+        assert_eq!(by_id("ok"), 1);
+        assert_eq!(by_id("com.example.app:id/ok"), 1);
+        assert_eq!(by_id("item_title"), 2);
+        // 带 '/' 就按全名比较，不做后缀匹配
+        assert_eq!(by_id("app:id/ok"), 0);
+        assert_eq!(by_id("com.other:id/ok"), 0);
+
+        let by_class = |name: &str| {
+            select(vec![Criterion::ClassName(name.into())])
+                .find_all(&root)
+                .len()
+        };
+        assert_eq!(by_class("Button"), 2);
+        assert_eq!(by_class("android.widget.Button"), 2);
+        assert_eq!(by_class("RecyclerView"), 1);
+        assert_eq!(by_class("LinearLayout"), 4);
+        // 带 '.' 就按全名比较
+        assert_eq!(by_class("widget.Button"), 0);
+        assert_eq!(by_class("ImageButton"), 1);
+    }
+
+    #[test]
+    fn combines_criteria_in_pre_order() {
+        let root = root();
+        let switches = select(vec![
+            Criterion::ClassName("Switch".into()),
+            Criterion::Clickable(true),
+        ]);
+        let found = switches.find_all(&root);
+        assert_eq!(texts(&found), ["自动更新开关", "消息通知开关"]);
+        assert_eq!(found[0].bounds.center(), (969, 325));
+
+        let on = select(vec![
+            Criterion::Id("item_switch".into()),
+            Criterion::Checked(false),
+        ]);
+        assert_eq!(texts(&on.find_all(&root)), ["消息通知开关"]);
+
+        let enabled_buttons = select(vec![
+            Criterion::ClassName("Button".into()),
+            Criterion::Enabled(true),
+        ]);
+        assert_eq!(texts(&enabled_buttons.find_all(&root)), ["确定"]);
+        assert!(select(vec![Criterion::Selected(true)])
+            .find_one(&root)
+            .is_some_and(|n| n.text.as_deref() == Some("确定")));
+
+        // 外层容器排在子节点前面
+        let clickable = select(vec![Criterion::Clickable(true)]).find_all(&root);
+        assert_eq!(
+            clickable
+                .iter()
+                .map(|n| n.short_class_name())
+                .collect::<Vec<_>>(),
+            [
+                "ImageButton",
+                "LinearLayout",
+                "Switch",
+                "LinearLayout",
+                "Switch",
+                "Button",
+                "Button"
+            ]
+        );
+
+        let scrollable = select(vec![Criterion::Scrollable(true)]);
+        assert_eq!(scrollable.find_one(&root).unwrap().short_id(), Some("list"));
+        let other_window = select(vec![Criterion::PackageName("com.android.systemui".into())]);
+        assert_eq!(texts(&other_window.find_all(&root)), ["已保存"]);
+    }
+
+    #[test]
+    fn empty_selector_matches_everything() {
+        let root = root();
+        let all = Selector::default().find_all(&root);
+        assert_eq!(all.len(), root.descendants().len());
+        assert!(std::ptr::eq(
+            Selector::default().find_one(&root).unwrap(),
+            &root
+        ));
+        assert!(select(vec![Criterion::Text("不存在".into())])
+            .find_all(&root)
+            .is_empty());
+    }
+}
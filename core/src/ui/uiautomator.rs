@@ -0,0 +1,416 @@
+// ==================================================
+// 🤖 Root 模式的节点树: uiautomator dump 的 XML
+//   <hierarchy rotation="0">
+//     <node index="0" text="同意" resource-id="com.app:id/ok" class="android.widget.Button"
+//           package="com.app" content-desc="" clickable="true" enabled="true" ...
+//           bounds="[600,2100][1000,2300]"> ... </node>
+//   </hierarchy>
+// 格式固定 (只有 node 元素和属性)，这里只做够用的解析，不引入 XML 库
+// 坐标和无障碍快照一样是真实屏幕坐标，交给脚本前统一换算 (core::unmap_coordinates)
+// ==================================================
+
+use super::{Bounds, UiNode};
+
+/// dump 到临时文件再读出来 (dump 到 /dev/tty 在部分系统上不可用)
+/// 界面一直在动画时 uiautomator 会报 "could not get idle state" 且不生成文件，输出为空
+pub const DUMP_COMMAND: &str = "f=/data/local/tmp/touch_ui_dump.xml; rm -f $f; \
+     uiautomator dump $f >/dev/null 2>&1; cat $f 2>/dev/null; rm -f $f";
+
+/// 解析 dump 输出；没有内容 (暂时抓不到界面) 返回 Ok(None)
+/// 有多个窗口 (状态栏 + 应用等) 时包一层根节点
+pub fn parse_dump(xml: &str) -> Result<Option<UiNode>, String> {
+    let Some(start) = xml.find("<hierarchy") else {
+        return Ok(None);
+    };
+    let mut parser = Parser {
+        rest: &xml[start..],
+    };
+    let mut stack: Vec<UiNode> = vec![UiNode {
+        class_name: "hierarchy".into(),
+        enabled: true,
+        ..UiNode::default()
+    }];
+    let mut in_hierarchy = false;
+    while let Some(tag) = parser.next_tag()? {
+        match tag {
+            Tag::Open {
+                name: "hierarchy", ..
+            } => in_hierarchy = true,
+            Tag::Close("hierarchy") => break,
+            Tag::Open {
+                name: "node",
+                attrs,
+                self_closing,
+            } if in_hierarchy => {
+                let node = node_from_attrs(&attrs)?;
+                if self_closing {
+                    stack.last_mut().unwrap().children.push(node);
+                } else {
+                    stack.push(node);
+                }
+            }
+            Tag::Close("node") => {
+                if stack.len() < 2 {
+                    return Err("unbalanced </node>".into());
+                }
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            _ => {}
+        }
+    }
+    if stack.len() != 1 {
+        return Err("unclosed <node>".into());
+    }
+    let mut root = stack.pop().unwrap();
+    Ok(match root.children.len() {
+        0 => None,
+        1 => root.children.pop(),
+        _ => {
+            root.bounds = root
+                .children
+                .iter()
+                .map(|c| c.bounds)
+                .reduce(|a, b| Bounds {
+                    left: a.left.min(b.left),
+                    top: a.top.min(b.top),
+                    right: a.right.max(b.right),
+                    bottom: a.bottom.max(b.bottom),
+                })
+                .unwrap_or_default();
+            root.package_name = root.children[0].package_name.clone();
+            Some(root)
+        }
+    })
+}
+
+fn node_from_attrs(attrs: &[(&str, String)]) -> Result<UiNode, String> {
+    let get = |key: &str| {
+        attrs
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    // 空字符串视为没有 (uiautomator 对缺失的属性输出 "")
+    let text = |key: &str| get(key).filter(|v| !v.is_empty()).map(str::to_string);
+    let flag = |key: &str| get(key) == Some("true");
+    let bounds = match get("bounds") {
+        Some(b) => parse_bounds(b).ok_or_else(|| format!("invalid bounds `{}`", b))?,
+        None => Bounds::default(),
+    };
+    Ok(UiNode {
+        class_name: get("class").unwrap_or_default().to_string(),
+        text: text("text"),
+        desc: text("content-desc"),
+        id: text("resource-id"),
+        package_name: get("package").unwrap_or_default().to_string(),
+        bounds,
+        clickable: flag("clickable"),
+        enabled: get("enabled") != Some("false"),
+        checked: flag("checked"),
+        selected: flag("selected"),
+        focused: flag("focused"),
+        scrollable: flag("scrollable"),
+        children: Vec::new(),
+    })
+}
+
+/// "[0,84][1080,2400]"
+fn parse_bounds(s: &str) -> Option<Bounds> {
+    let nums: Vec<i32> = s
+        .split(['[', ']', ','])
+        .filter(|p| !p.is_empty())
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let [left, top, right, bottom] = nums[..] else {
+        return None;
+    };
+    Some(Bounds {
+        left,
+        top,
+        right,
+        bottom,
+    })
+}
+
+enum Tag<'a> {
+    Open {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+        self_closing: bool,
+    },
+    Close(&'a str),
+    /// <?xml ...?> / <!-- --> 等
+    Other,
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn next_tag(&mut self) -> Result<Option<Tag<'a>>, String> {
+        let Some(start) = self.rest.find('<') else {
+            return Ok(None);
+        };
+        let rest = &self.rest[start + 1..];
+        if let Some(body) = rest.strip_prefix("!--") {
+            let end = body.find("-->").ok_or("unterminated comment")?;
+            self.rest = &body[end + 3..];
+            return Ok(Some(Tag::Other));
+        }
+        // 属性值里不会有未转义的 '>'，之外的第一个 '>' 就是标签结尾
+        let end = find_tag_end(rest).ok_or("unterminated tag")?;
+        let inner = &rest[..end];
+        self.rest = &rest[end + 1..];
+        if inner.starts_with('?') || inner.starts_with('!') {
+            return Ok(Some(Tag::Other));
+        }
+        if let Some(name) = inner.strip_prefix('/') {
+            return Ok(Some(Tag::Close(name.trim())));
+        }
+        let (inner, self_closing) = match inner.strip_suffix('/') {
+            Some(inner) => (inner, true),
+            None => (inner, false),
+        };
+        let name_end = inner
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(inner.len());
+        Ok(Some(Tag::Open {
+            name: &inner[..name_end],
+            attrs: parse_attrs(&inner[name_end..])?,
+            self_closing,
+        }))
+    }
+}
+
+/// 跳过引号内的内容找 '>'
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_attrs(mut s: &str) -> Result<Vec<(&str, String)>, String> {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            return Ok(attrs);
+        }
+        let eq = s
+            .find('=')
+            .ok_or_else(|| format!("invalid attribute `{}`", s))?;
+        let key = s[..eq].trim();
+        let value = s[eq + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("unquoted attribute `{}`", key))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or_else(|| format!("unterminated attribute `{}`", key))?;
+        attrs.push((key, unescape(&value[1..1 + end])));
+        s = &value[end + 2..];
+    }
+}
+
+/// XML 实体: &amp; &lt; &gt; &quot; &apos; &#10; &#x4e2d;
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let entity = &rest[1..semi];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            c.map(|c| (c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            // 不认识的实体原样保留
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::ui::logical_bounds, core::unmap_with_scale, ui::selector::*};
+
+    /// 真机 (1080x2400) 上 uiautomator dump 的原始输出 (协议弹窗)
+    const DUMP: &str = include_str!("../../tests/fixtures/ui/uiautomator_dump.xml");
+
+    fn find<'a>(root: &'a UiNode, criterion: Criterion) -> &'a UiNode {
+        Selector {
+            criteria: vec![criterion],
+        }
+        .find_one(root)
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_real_dump() {
+        let root = parse_dump(DUMP).unwrap().unwrap();
+        // 只有一个窗口时不包根节点
+        assert_eq!(root.class_name, "android.widget.FrameLayout");
+        assert_eq!(root.bounds, Bounds::from([0, 0, 1080, 2400]));
+        assert_eq!(root.descendants().len(), 10);
+
+        let ok = find(&root, Criterion::Text("同意".into()));
+        assert_eq!(ok.id.as_deref(), Some("com.example.app:id/ok"));
+        assert_eq!(ok.short_class_name(), "Button");
+        assert_eq!(ok.package_name, "com.example.app");
+        assert_eq!(ok.bounds, Bounds::from([570, 2160, 1020, 2320]));
+        assert!(ok.clickable && ok.enabled && ok.selected && !ok.checked);
+
+        let cancel = find(&root, Criterion::Id("cancel".into()));
+        assert!(!cancel.enabled);
+        let agree = find(&root, Criterion::ClassName("CheckBox".into()));
+        assert!(agree.checked);
+        let scroll = find(&root, Criterion::Scrollable(true));
+        assert!(scroll.focused);
+        assert_eq!(scroll.children.len(), 2);
+        // 空字符串属性视为没有
+        assert_eq!(root.text, None);
+        assert_eq!(root.id, None);
+        assert_eq!(root.desc, None);
+    }
+
+    #[test]
+    fn unescapes_entities() {
+        let root = parse_dump(DUMP).unwrap().unwrap();
+        let back = find(&root, Criterion::Id("back".into()));
+        assert_eq!(back.desc.as_deref(), Some("<返回>"));
+        let title = find(&root, Criterion::Id("title".into()));
+        assert_eq!(title.text.as_deref(), Some("Terms & Conditions"));
+        let body = find(&root, Criterion::Id("body".into()));
+        // 不认识的实体原样保留
+        assert_eq!(
+            body.text.as_deref(),
+            Some("第一行\n第二行 \"重要\" 中文 &nbsp;")
+        );
+
+        assert_eq!(unescape("a &amp;&amp; b"), "a && b");
+        assert_eq!(unescape("&apos;x&apos;"), "'x'");
+        assert_eq!(unescape("&#128512;"), "😀");
+        assert_eq!(unescape("AT&T"), "AT&T");
+        assert_eq!(unescape("&#xD800;"), "&#xD800;");
+        assert_eq!(unescape("&amp"), "&amp");
+    }
+
+    #[test]
+    fn idle_state_error_is_no_snapshot() {
+        // 界面一直在动时 dump 失败，文件没生成，cat 不到内容
+        assert_eq!(parse_dump("").unwrap().map(|n| n.class_name), None);
+        assert_eq!(
+            parse_dump("ERROR: could not get idle state.\n")
+                .unwrap()
+                .map(|n| n.class_name),
+            None
+        );
+        assert_eq!(
+            parse_dump("<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><hierarchy rotation=\"1\"></hierarchy>")
+                .unwrap()
+                .map(|n| n.class_name),
+            None
+        );
+    }
+
+    #[test]
+    fn wraps_multiple_windows() {
+        let xml = r#"<hierarchy rotation="0"><node class="android.widget.FrameLayout" package="com.android.systemui" bounds="[0,0][1080,84]" /><node class="android.widget.FrameLayout" package="com.example.app" bounds="[0,84][1080,2400]"><node text="OK" class="android.widget.Button" package="com.example.app" bounds="[10,100][200,180]" /></node></hierarchy>"#;
+        let root = parse_dump(xml).unwrap().unwrap();
+        assert_eq!(root.class_name, "hierarchy");
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.bounds, Bounds::from([0, 0, 1080, 2400]));
+        assert_eq!(root.package_name, "com.android.systemui");
+        assert_eq!(
+            find(&root, Criterion::Text("OK".into())).bounds.center(),
+            (105, 140)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_dumps() {
+        for bounds in [
+            "[0,0][1080]",
+            "[0,0][1080,2400][1,1]",
+            "[a,b][c,d]",
+            "0,0,1080,2400x",
+        ] {
+            let xml = format!(
+                r#"<hierarchy><node class="View" bounds="{}" /></hierarchy>"#,
+                bounds
+            );
+            let err = parse_dump(&xml).unwrap_err();
+            assert!(err.contains("invalid bounds"), "{}: {}", bounds, err);
+        }
+        assert_eq!(
+            parse_bounds("[-5,0][10,20]"),
+            Some(Bounds::from([-5, 0, 10, 20]))
+        );
+
+        for xml in [
+            r#"<hierarchy><node class="View" bounds="[0,0][1,1]"></hierarchy>"#,
+            r#"<hierarchy></node></hierarchy>"#,
+            r#"<hierarchy><node class="View" bounds="[0,0][1,1]"#,
+            r#"<hierarchy><node class=View bounds="[0,0][1,1]" /></hierarchy>"#,
+            r#"<hierarchy><node class /></hierarchy>"#,
+            r#"<hierarchy><!-- truncated"#,
+        ] {
+            assert!(parse_dump(xml).is_err(), "{}", xml);
+        }
+    }
+
+    #[test]
+    fn maps_bounds_back_to_logical_coordinates() {
+        let root = parse_dump(DUMP).unwrap().unwrap();
+        let ok = find(&root, Criterion::Text("同意".into()));
+        // 画面按 1.5 倍缩放: 脚本的 720x1600 对应屏幕 1080x2400
+        let unmap = |x, y| unmap_with_scale(x, y, 1.5);
+        assert_eq!(logical_bounds(ok, unmap), vec![380, 1440, 300, 106]);
+        assert_eq!(logical_bounds(&root, unmap), vec![0, 0, 720, 1600]);
+        let (x, y) = ok.bounds.center();
+        assert_eq!(unmap(x, y), (530, 1493));
+        // 还没有画面 (scale 为 0) 时原样返回
+        assert_eq!(
+            logical_bounds(ok, |x, y| unmap_with_scale(x, y, 0.0)),
+            vec![570, 2160, 450, 160]
+        );
+    }
+}
@@ -1,18 +1,33 @@
 use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Mutex,
-    },
+    path::Path,
+    sync::{Arc, Mutex},
     thread,
 };
 
 use log::info;
 
 use crate::{
-    input::{AccessibilityStrategy, InputController, RootStrategy},
-    js_engine::{self, CURRENT_SCRIPT_TASK},
+    bundle::{BundleError, ScriptBundle},
+    config::{ConfigError, ConfigValue, CONFIG},
+    constants::ENGINE_API_VERSION,
+    core::SCREEN_BUFFER,
+    data_dir,
+    debugger::{self, DebugError},
+    events,
+    hot_reload::{self, HotReloadError},
+    input::{
+        arbiter::{self, InputPolicy},
+        humanize::{HumanizeConfig, HumanizeError, HumanizedController},
+        recorder::{ActionLog, RecordedAction, RecordingController},
+        AccessibilityStrategy, InputController, RootStrategy,
+    },
+    js_engine::{self, RuntimeLimits, ScriptControl, ScriptSource},
+    lifecycle::{self, ScriptErrorKind, ScriptEvent, ScriptStatus},
     logger::{self, init_logger},
-    types::{AccessibilityService, PlatformLogger},
+    manifest::{NetworkConfig, RuntimeEnv, ScriptManifest},
+    registry::{self, ScriptId, ScriptInfo, ScriptOptions, ScriptRole},
+    storage::{self, StorageEntry, StorageError},
+    types::{AccessibilityService, PlatformLogger, ScriptListener},
 };
 
 // ⚠️ UniFFI 的 callback 是 Box<dyn ...>，它是唯一的。
@@ -35,11 +50,28 @@ uniffi::setup_scaffolding!();
 // 1. 全局状态存储
 // ==========================================
 
-pub static IS_PAUSED: AtomicBool = AtomicBool::new(false);
-
 lazy_static::lazy_static! {
     // 硬件控制器 (Root/无障碍)
     pub static ref CONTROLLER: Mutex<Option<Box<dyn InputController>>> = Mutex::new(None);
+    // 拟人化配置 (None = 关闭)，安装控制器时生效
+    pub static ref HUMANIZE_CONFIG: Mutex<Option<HumanizeConfig>> = Mutex::new(None);
+    // 录制控制器的记录句柄 (安装了 RecordingController 时才有)
+    pub static ref RECORDED_ACTIONS: Mutex<Option<ActionLog>> = Mutex::new(None);
+    // 宿主的日志回调 (init_service 传入)，热重载等需要让用户看到的消息走这里
+    pub static ref PLATFORM_LOGGER: Mutex<Option<Box<dyn PlatformLogger>>> = Mutex::new(None);
+}
+
+/// 安装控制器，如果开启了拟人化则自动包一层
+pub fn install_controller(ctrl: Box<dyn InputController>) {
+    let ctrl: Box<dyn InputController> = match HUMANIZE_CONFIG.lock().unwrap().clone() {
+        Some(config) => {
+            info!("Humanize enabled: {:?}", config);
+            Box::new(HumanizedController::new(ctrl, config))
+        }
+        None => ctrl,
+    };
+    let mut guard = CONTROLLER.lock().unwrap();
+    *guard = Some(ctrl);
 }
 
 /// 桌面端专用初始化 (Desktop / JVM)
@@ -59,108 +91,487 @@ pub fn init_desktop() {
 // ==========================================
 
 /// 初始化服务 (App 启动时调用)
-#[uniffi::export]
+/// host_package: 宿主的安装包名 (context.packageName)，Root 模式定位剪贴板接收器用，不传按默认 applicationId
+#[uniffi::export(default(host_package = None))]
 pub fn init_service(
     use_root: bool,
     logger: Box<dyn PlatformLogger>,
     service: Option<Box<dyn AccessibilityService>>,
+    host_package: Option<String>,
 ) {
     init_logger();
 
     let ctrl: Box<dyn InputController> = if use_root {
         info!("Initializing Root Strategy");
-        Box::new(RootStrategy)
+        match host_package {
+            Some(package) => Box::new(RootStrategy::with_host_package(&package)),
+            None => Box::new(RootStrategy::new()),
+        }
     } else {
         info!("Initializing Accessibility Strategy");
         if let Some(s) = service {
             Box::new(AccessibilityStrategy::new(s))
         } else {
             logger.log("Error: Accessibility Service is required for non-root mode".into());
+            *PLATFORM_LOGGER.lock().unwrap() = Some(logger);
             return;
         }
     };
 
-    let mut guard = CONTROLLER.lock().unwrap();
-    *guard = Some(ctrl);
+    *RECORDED_ACTIONS.lock().unwrap() = None;
+    install_controller(ctrl);
     logger.log(format!(
         "Service Initialized. Mode: {}",
         if use_root { "Root" } else { "Accessibility" }
     ));
+    *PLATFORM_LOGGER.lock().unwrap() = Some(logger);
+}
+
+/// 安装录制控制器 (桌面端 dry run / 测试用)，替换当前控制器，并清空旧记录
+#[uniffi::export]
+pub fn install_recording_controller() {
+    let recorder = RecordingController::new();
+    *RECORDED_ACTIONS.lock().unwrap() = Some(recorder.log_handle());
+    install_controller(Box::new(recorder));
+    info!("Recording controller installed");
+}
+
+/// 读取录制到的动作序列 (未安装录制控制器时返回空)
+#[uniffi::export]
+pub fn recorded_actions() -> Vec<RecordedAction> {
+    match RECORDED_ACTIONS.lock().unwrap().as_ref() {
+        Some(log) => log.lock().unwrap().clone(),
+        None => vec![],
+    }
 }
 
-/// 运行 JS 脚本 (点击开始按钮调用)
+/// 清空录制记录
 #[uniffi::export]
-pub fn run_js_script(script_content: String) {
-    // 1. 先停止旧脚本
-    stop_script();
+pub fn clear_recorded_actions() {
+    if let Some(log) = RECORDED_ACTIONS.lock().unwrap().as_ref() {
+        log.lock().unwrap().clear();
+    }
+}
 
-    // 2. 启动新线程
-    thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .unwrap();
+/// 设置拟人化参数 (传 None 关闭)
+/// 需在 init_service 之前调用，或设置后重新 init_service
+/// 参数不合法 (NaN / 无穷 / 概率超出 0~1) 时拒绝，保留原配置
+#[uniffi::export]
+pub fn set_humanize_config(config: Option<HumanizeConfig>) -> Result<(), HumanizeError> {
+    if let Some(config) = &config {
+        config.validate()?;
+    }
+    info!("Humanize config updated: {:?}", config);
+    *HUMANIZE_CONFIG.lock().unwrap() = config;
+    Ok(())
+}
 
-        rt.block_on(async move {
-            // 3. 启动任务
-            let handle = tokio::spawn(async move {
-                // 🔥 每次运行前，强制重置为非暂停状态
-                IS_PAUSED.store(false, Ordering::Relaxed);
+/// 当前引擎 API 版本 (对应 manifest 的 minApiVersion)
+#[uniffi::export]
+pub fn engine_api_version() -> u32 {
+    ENGINE_API_VERSION
+}
 
-                match js_engine::run_script_async(script_content).await {
-                    Ok(_) => info!("✅ Script finished successfully"),
-                    Err(e) => info!("❌ Script error: {}", e),
-                }
-            });
+/// 运行 JS 脚本 (点击开始按钮调用)，返回脚本 id
+/// 没有清单，入口按 main/start/run 猜测
+/// limits: 运行限制，不传使用默认值 (RuntimeLimits::default)
+/// options: 不传则作为主脚本运行 (会停掉旧的主脚本)
+/// source_map: 同时打包出的 script.js.map 内容 (调试器断点 / 调用栈映射回 TS)
+#[uniffi::export(default(limits = None, options = None, source_map = None))]
+pub fn run_js_script(
+    script_content: String,
+    limits: Option<RuntimeLimits>,
+    options: Option<ScriptOptions>,
+    source_map: Option<String>,
+) -> ScriptId {
+    let options = options.unwrap_or_default();
+    if options.role == ScriptRole::Main {
+        CONFIG.lock().unwrap().apply_manifest(&[]);
+    }
+    spawn_script(
+        ScriptSource::Legacy {
+            script: script_content,
+            entry: None,
+            source_map,
+            name: None,
+            network: NetworkConfig::default(),
+        },
+        String::new(),
+        limits.unwrap_or_default(),
+        options,
+    )
+}
+
+/// 运行 JS 脚本 + 清单 (manifest.json 内容)
+/// 入口取清单声明的 entry，校验不通过直接返回错误，不会启动脚本
+#[uniffi::export(default(limits = None, options = None, source_map = None))]
+pub fn run_js_script_with_manifest(
+    script_content: String,
+    manifest_json: String,
+    limits: Option<RuntimeLimits>,
+    options: Option<ScriptOptions>,
+    source_map: Option<String>,
+) -> Result<ScriptId, BundleError> {
+    let options = options.unwrap_or_default();
+    let manifest = ScriptManifest::parse(manifest_json.as_bytes())?;
+    manifest.validate(&runtime_env())?;
+    // 配置是全局共享的，类型声明和默认值以主脚本的清单为准
+    if options.role == ScriptRole::Main {
+        CONFIG.lock().unwrap().apply_manifest(&manifest.config);
+    }
+    Ok(spawn_script(
+        ScriptSource::Legacy {
+            script: script_content,
+            entry: Some(manifest.entry),
+            source_map,
+            name: Some(manifest.name.clone()),
+            network: manifest.network,
+        },
+        manifest.name,
+        limits.unwrap_or_default(),
+        options,
+    ))
+}
+
+/// 运行脚本包 (目录或 .zip，内含 manifest.json)
+/// 包打开/解析/校验失败会直接返回错误，脚本本身的运行错误走日志
+#[uniffi::export(default(limits = None, options = None))]
+pub fn run_script_bundle(
+    path: String,
+    limits: Option<RuntimeLimits>,
+    options: Option<ScriptOptions>,
+) -> Result<ScriptId, BundleError> {
+    run_bundle(ScriptBundle::open(Path::new(&path))?, limits, options)
+}
+
+/// 运行已打开的脚本包 (磁盘 / 内存)
+pub(crate) fn run_bundle(
+    bundle: ScriptBundle,
+    limits: Option<RuntimeLimits>,
+    options: Option<ScriptOptions>,
+) -> Result<ScriptId, BundleError> {
+    let options = options.unwrap_or_default();
+    bundle.manifest().validate(&runtime_env())?;
+    if options.role == ScriptRole::Main {
+        CONFIG
+            .lock()
+            .unwrap()
+            .apply_manifest(&bundle.manifest().config);
+    }
+    let name = bundle.manifest().name.clone();
+    Ok(spawn_script(
+        ScriptSource::Bundle(Arc::new(bundle)),
+        name,
+        limits.unwrap_or_default(),
+        options,
+    ))
+}
+
+/// 设置一个配置项 (脚本运行前后都可以调用，运行中修改会通知 Config.onChange)
+/// 清单声明了类型的键会校验类型
+#[uniffi::export]
+pub fn set_config_value(key: String, value: ConfigValue) -> Result<(), ConfigError> {
+    let value = value.to_json()?;
+    CONFIG.lock().unwrap().set(&key, value)
+}
+
+/// 批量设置配置 (JSON 对象，例如 WebView 配置页保存的整份配置)
+#[uniffi::export]
+pub fn set_config_json(json: String) -> Result<(), ConfigError> {
+    CONFIG.lock().unwrap().merge_json(&json)
+}
+
+/// 读取当前全部配置 (JSON 对象，包含清单默认值)
+#[uniffi::export]
+pub fn config_json() -> String {
+    CONFIG.lock().unwrap().snapshot().to_string()
+}
+
+#[uniffi::export]
+pub fn remove_config_value(key: String) {
+    CONFIG.lock().unwrap().remove(&key);
+}
+
+#[uniffi::export]
+pub fn clear_config() {
+    CONFIG.lock().unwrap().clear();
+}
+
+/// 设置 App 数据目录 (Android 传 Context.filesDir)，脚本的 Storage 等数据保存在下面
+/// 不设置时这些数据只在内存里
+#[uniffi::export]
+pub fn set_data_dir(path: String) {
+    info!("Data dir: {}", path);
+    data_dir::set(Path::new(&path));
+    storage::reset_cache();
+}
+
+/// 有 Storage 数据的命名空间 (清单名 / "script-<哈希>")
+#[uniffi::export]
+pub fn storage_namespaces() -> Vec<String> {
+    storage::namespaces()
+}
+
+/// 查看某个脚本的 Storage (不含已过期的条目)
+#[uniffi::export]
+pub fn storage_entries(namespace: String) -> Vec<StorageEntry> {
+    storage::entries(&namespace)
+}
+
+/// 删除某个脚本的一个 Storage 键
+#[uniffi::export]
+pub fn remove_storage_value(namespace: String, key: String) -> Result<(), StorageError> {
+    storage::remove(&namespace, &key).map(|_| ())
+}
+
+/// 清空 Storage: 传命名空间只清该脚本，None 清空全部
+#[uniffi::export(default(namespace = None))]
+pub fn clear_storage(namespace: Option<String>) -> Result<(), StorageError> {
+    match namespace {
+        Some(namespace) => storage::clear(&namespace),
+        None => storage::clear_all(),
+    }
+}
+
+/// 当前运行环境快照 (控制器模式 + 截图尺寸)，用于清单校验
+fn runtime_env() -> RuntimeEnv {
+    let mode = CONTROLLER.lock().unwrap().as_ref().map(|c| c.mode());
+    let screen = {
+        let guard = SCREEN_BUFFER.lock().unwrap();
+        let (_, w, h, _, _) = &*guard;
+        (*w > 0 && *h > 0).then_some((*w as u32, *h as u32))
+    };
+    RuntimeEnv { mode, screen }
+}
+
+/// 设置脚本生命周期监听 (传 None 取消)
+#[uniffi::export]
+pub fn set_script_listener(listener: Option<Box<dyn ScriptListener>>) {
+    lifecycle::set_listener(listener);
+}
+
+/// 查询主脚本当前状态 (没有运行过则 Idle)
+#[uniffi::export]
+pub fn script_status() -> ScriptStatus {
+    registry::main_status()
+}
+
+/// 查询指定脚本的状态 (id 不存在或已被清理返回 None)
+#[uniffi::export]
+pub fn script_status_by_id(script_id: ScriptId) -> Option<ScriptStatus> {
+    registry::status(script_id)
+}
+
+/// 所有运行中 + 最近结束的脚本
+#[uniffi::export]
+pub fn list_scripts() -> Vec<ScriptInfo> {
+    registry::list()
+}
+
+/// 开启脚本调试服务 (只监听 127.0.0.1，电脑端先 adb forward tcp:PORT tcp:PORT)
+/// port = 0 随机分配，返回实际端口；之后启动的第一个主脚本会被附加调试
+/// token: 每次开启随机生成 (至少 16 个字符)，客户端握手时出示，防止本机其它应用连上来执行脚本
+/// suspend_on_start: 脚本在第一条语句前暂停，等调试客户端 continue
+#[uniffi::export(default(suspend_on_start = false))]
+pub fn start_debug_server(
+    port: u16,
+    token: String,
+    suspend_on_start: bool,
+) -> Result<u16, DebugError> {
+    debugger::start_server(port, suspend_on_start, token)
+}
+
+#[uniffi::export]
+pub fn stop_debug_server() {
+    debugger::stop_server();
+}
+
+/// 调试服务的端口，没开启返回 None
+#[uniffi::export]
+pub fn debug_server_port() -> Option<u16> {
+    debugger::server_port()
+}
+
+/// 开启开发模式热重载，返回推送端口 (没开推送返回 None)
+/// watch_path: 监听 script.js / 脚本包目录 / .zip，变化后重启主脚本
+/// push_port: 接收 tools/dev.js 推送的本地端口 (0 随机，电脑端 adb forward tcp:PORT tcp:PORT)
+/// token: 开推送时必填，推送需带上 (和调试服务用同一个开发者模式令牌)
+/// preserve_config: 重载时保留当前配置 (开发期间改过的值)，false 则恢复到开启时的配置
+#[uniffi::export(default(
+    watch_path = None,
+    push_port = None,
+    token = None,
+    preserve_config = true,
+    limits = None
+))]
+pub fn start_hot_reload(
+    watch_path: Option<String>,
+    push_port: Option<u16>,
+    token: Option<String>,
+    preserve_config: bool,
+    limits: Option<RuntimeLimits>,
+) -> Result<Option<u16>, HotReloadError> {
+    hot_reload::start(watch_path, push_port, token, preserve_config, limits)
+}
 
-            // 4. 获取 AbortHandle 并存入全局变量
-            // 🔥 关键修改：使用 handle.abort_handle()
-            let abort_handle = handle.abort_handle();
+/// 关闭热重载 (正在运行的脚本不受影响)
+#[uniffi::export]
+pub fn stop_hot_reload() {
+    hot_reload::stop();
+}
+
+/// 热重载是否开着
+#[uniffi::export]
+pub fn is_hot_reload_enabled() -> bool {
+    hot_reload::is_enabled()
+}
+
+/// 设置多脚本的输入仲裁策略
+#[uniffi::export]
+pub fn set_input_policy(policy: InputPolicy) {
+    info!("Input policy: {:?}", policy);
+    arbiter::set_policy(policy);
+}
 
-            {
-                let task_mutex = CURRENT_SCRIPT_TASK.get_or_init(|| std::sync::Mutex::new(None));
-                if let Ok(mut guard) = task_mutex.lock() {
-                    *guard = Some(abort_handle); // AbortHandle 可以被 Clone (虽然这里是 Move 进去，但也支持 clone)
+#[uniffi::export]
+pub fn input_policy() -> InputPolicy {
+    arbiter::policy()
+}
+
+fn spawn_script(
+    source: ScriptSource,
+    name: String,
+    limits: RuntimeLimits,
+    options: ScriptOptions,
+) -> ScriptId {
+    // 1. 主脚本同时只有一个，先停止旧的
+    if options.role == ScriptRole::Main {
+        registry::stop_all(Some(ScriptRole::Main));
+    }
+    let name = match options.name.clone() {
+        Some(name) => name,
+        None if !name.is_empty() => name,
+        None => format!("{:?}", options.role).to_lowercase(),
+    };
+    // 限制不合法时登记后直接按失败结束，不启动线程
+    if let Err(message) = limits.validate() {
+        let (id, _) = registry::register(name, &options, None);
+        registry::start_failed(id, format!("Invalid runtime limits: {}", message));
+        return id;
+    }
+    let (id, control) = registry::register(name, &options, limits.timeout());
+
+    // 2. 启动新线程 (每个脚本独立线程 + 运行时，栈要容得下 JS 栈上限)
+    let spawned = thread::Builder::new()
+        .name(format!("touch-script-{}", id))
+        .stack_size(limits.thread_stack_size())
+        .spawn(move || run_script_thread(id, source, control, limits));
+
+    match spawned {
+        Ok(handle) => registry::attach_thread(id, handle),
+        Err(e) => registry::start_failed(id, format!("Failed to spawn script thread: {}", e)),
+    }
+    id
+}
+
+fn run_script_thread(
+    id: ScriptId,
+    source: ScriptSource,
+    control: ScriptControl,
+    limits: RuntimeLimits,
+) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    rt.block_on(async move {
+        // 3. 启动任务
+        let task_control = control.clone();
+        let handle = tokio::spawn(async move {
+            match js_engine::run_script_async(source, task_control.clone(), limits).await {
+                // 被中断回调打断时 JS 抛的是 "interrupted"，按中止处理
+                Err(_) if task_control.is_cancelled() => {
+                    info!("🛑 Script #{} interrupted", id);
+                    registry::end(id, ScriptEvent::Aborted);
+                }
+                Ok(_) => {
+                    info!("✅ Script #{} finished successfully", id);
+                    registry::end(id, ScriptEvent::Finished);
+                }
+                Err(e) => {
+                    info!("❌ Script #{} error: {}", id, e);
+                    registry::end(
+                        id,
+                        ScriptEvent::Failed {
+                            kind: e.kind,
+                            message: e.message,
+                            stack: e.stack,
+                        },
+                    );
                 }
             }
+        });
 
-            // 5. 等待任务结束
-            // 无论是自然结束，还是被外部 abort()，这里都会返回
-            // 如果是被 abort 的，result 会是一个 Cancelled Error
-            let _ = handle.await;
+        // 4. 登记 AbortHandle，stop 时用它打断 await
+        registry::attach_task(id, handle.abort_handle());
 
-            // 6. 清理全局变量
-            {
-                let task_mutex = CURRENT_SCRIPT_TASK.get_or_init(|| std::sync::Mutex::new(None));
-                if let Ok(mut guard) = task_mutex.lock() {
-                    *guard = None;
-                }
+        // 5. 等待任务结束
+        // 无论是自然结束，还是被外部 abort()，这里都会返回
+        // 如果是被 abort 的，result 会是一个 Cancelled Error (Aborted 事件已在 stop 时发出)
+        // panic 则当作失败上报
+        if let Err(e) = handle.await {
+            if e.is_panic() {
+                registry::end(
+                    id,
+                    ScriptEvent::Failed {
+                        kind: ScriptErrorKind::Exception,
+                        message: "Script task panicked".into(),
+                        stack: None,
+                    },
+                );
             }
+        }
 
-            info!("👋 Script Task Ended, Runtime shutting down.");
-        });
+        info!("👋 Script #{} Task Ended, Runtime shutting down.", id);
     });
 }
 
+/// 上报前台应用切换 (AccessibilityEvent TYPE_WINDOW_STATE_CHANGED)，脚本收到 "foreground" 事件
+/// 和上次相同时忽略，宿主不用自己去重
+#[uniffi::export(default(activity = None))]
+pub fn report_foreground_app(package_name: String, activity: Option<String>) {
+    events::foreground_changed(package_name, activity);
+}
+
+/// 上报新通知 (宿主的 NotificationMonitorService.onNotificationPosted)，脚本收到 "notification" 事件
+#[uniffi::export]
+pub fn report_notification(package_name: String, title: String, text: String) {
+    events::notification_posted(package_name, title, text);
+}
+
+/// 停止所有脚本 (主脚本 + 辅助脚本)
 #[uniffi::export]
 pub fn stop_script() {
-    let task_mutex = CURRENT_SCRIPT_TASK.get_or_init(|| std::sync::Mutex::new(None));
-
-    if let Ok(mut guard) = task_mutex.lock() {
-        // 取出 AbortHandle
-        if let Some(abort_handle) = guard.take() {
-            info!("🛑 Stopping script task...");
-            abort_handle.abort(); // 🔥 使用 abort_handle 停止任务
-            info!("✅ Script task aborted signal sent.");
-        } else {
-            info!("⚠️ No running script to stop.");
-        }
-    }
+    info!("🛑 Stopping all scripts...");
+    registry::stop_all(None);
+}
+
+/// 停止指定脚本，返回是否找到运行中的脚本
+#[uniffi::export]
+pub fn stop_script_by_id(script_id: ScriptId) -> bool {
+    registry::stop(script_id)
 }
 
+/// 暂停 / 恢复所有脚本
 #[uniffi::export]
 pub fn set_paused(paused: bool) {
     info!("Script Paused State: {}", paused);
-    // Relaxed 顺序对于这种简单的标志位已经足够了
-    IS_PAUSED.store(paused, Ordering::Relaxed);
+    registry::set_paused_all(paused);
+}
+
+/// 暂停 / 恢复指定脚本，返回是否找到运行中的脚本
+#[uniffi::export]
+pub fn set_script_paused(script_id: ScriptId, paused: bool) -> bool {
+    registry::set_paused(script_id, paused)
 }
@@ -1,6 +1,6 @@
 use crate::vision::colors;
 use crate::vision::types::{ColorRule, Rect};
-use image::{DynamicImage};
+use image::DynamicImage;
 
 /// 扫描连通区域
 /// 返回一组 Rect，代表每个连通块的包围盒
@@ -0,0 +1,177 @@
+// ==========================================================
+// 🖼️ 图片编码 (PNG / JPEG)，附带截图的缩放比例
+// 截图是逻辑分辨率 (缓冲区) 下的图，scale = 真实屏幕 / 截图。
+// 写进文件元数据，工具端取模板 / 换算坐标时不用再猜:
+//   PNG:  tEXt 块 "Scale" = "2.5"
+//   JPEG: COM 段 "Scale=2.5"
+// ==========================================================
+
+use std::io::Cursor;
+
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder},
+    DynamicImage, ImageEncoder,
+};
+
+use super::types::VisionError;
+
+/// JPEG 默认质量
+pub const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    Png,
+    Jpeg,
+}
+
+impl SaveFormat {
+    /// "png" / "jpg" / "jpeg" (不区分大小写)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            _ => None,
+        }
+    }
+}
+
+pub fn encode(img: &DynamicImage, format: SaveFormat, scale: f32) -> Result<Vec<u8>, VisionError> {
+    let mut data = Vec::new();
+    let encode_error = |e: image::ImageError| VisionError::EncodeError(e.to_string());
+    match format {
+        SaveFormat::Png => {
+            let rgba = img.to_rgba8();
+            PngEncoder::new(&mut Cursor::new(&mut data))
+                .write_image(
+                    rgba.as_raw(),
+                    rgba.width(),
+                    rgba.height(),
+                    image::ExtendedColorType::Rgba8,
+                )
+                .map_err(encode_error)?;
+            insert_png_text(&mut data, "Scale", &scale.to_string());
+        }
+        SaveFormat::Jpeg => {
+            // JPEG 没有透明通道
+            let rgb = img.to_rgb8();
+            JpegEncoder::new_with_quality(&mut Cursor::new(&mut data), DEFAULT_JPEG_QUALITY)
+                .write_image(
+                    rgb.as_raw(),
+                    rgb.width(),
+                    rgb.height(),
+                    image::ExtendedColorType::Rgb8,
+                )
+                .map_err(encode_error)?;
+            insert_jpeg_comment(&mut data, &format!("Scale={}", scale));
+        }
+    }
+    Ok(data)
+}
+
+/// 读取 encode 写入的缩放比例 (没有则返回 None)
+pub fn read_scale(data: &[u8]) -> Option<f32> {
+    let value = if data.starts_with(PNG_SIGNATURE) {
+        png_text(data, "Scale")?
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_comment(data)?.strip_prefix("Scale=")?
+    } else {
+        return None;
+    };
+    value.parse().ok().filter(|s: &f32| *s > 0.0)
+}
+
+// ==========================================
+// PNG tEXt 块
+// ==========================================
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// 签名 (8) + IHDR 块 (4 长度 + 4 类型 + 13 数据 + 4 CRC)
+const PNG_IHDR_END: usize = 8 + 25;
+
+fn insert_png_text(png: &mut Vec<u8>, keyword: &str, text: &str) {
+    if png.len() < PNG_IHDR_END {
+        return;
+    }
+    let mut body = b"tEXt".to_vec();
+    body.extend_from_slice(keyword.as_bytes());
+    body.push(0);
+    body.extend_from_slice(text.as_bytes());
+    let mut chunk = ((body.len() - 4) as u32).to_be_bytes().to_vec();
+    chunk.extend_from_slice(&body);
+    chunk.extend_from_slice(&crc32(&body).to_be_bytes());
+    png.splice(PNG_IHDR_END..PNG_IHDR_END, chunk);
+}
+
+fn png_text<'a>(png: &'a [u8], keyword: &str) -> Option<&'a str> {
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 8 <= png.len() {
+        let len = u32::from_be_bytes(png[pos..pos + 4].try_into().ok()?) as usize;
+        let kind = &png[pos + 4..pos + 8];
+        let data = png.get(pos + 8..pos + 8 + len)?;
+        if kind == b"tEXt" {
+            if let Some(text) = data
+                .strip_prefix(keyword.as_bytes())
+                .and_then(|rest| rest.strip_prefix(&[0]))
+            {
+                return std::str::from_utf8(text).ok();
+            }
+        }
+        if kind == b"IDAT" {
+            return None;
+        }
+        pos += 12 + len;
+    }
+    None
+}
+
+/// PNG 块用的 CRC-32 (IEEE)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// ==========================================
+// JPEG COM 段 (SOI / JFIF APP0 之后)
+// ==========================================
+
+fn insert_jpeg_comment(jpeg: &mut Vec<u8>, text: &str) {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return;
+    }
+    // JFIF 要求 APP0 紧跟 SOI，注释放在它后面
+    let at = match jpeg.get(2..6) {
+        Some([0xFF, 0xE0, hi, lo]) => 4 + u16::from_be_bytes([*hi, *lo]) as usize,
+        _ => 2,
+    };
+    if at > jpeg.len() {
+        return;
+    }
+    // 长度包含自身的 2 字节
+    let mut segment = vec![0xFF, 0xFE];
+    segment.extend_from_slice(&((text.len() + 2) as u16).to_be_bytes());
+    segment.extend_from_slice(text.as_bytes());
+    jpeg.splice(at..at, segment);
+}
+
+fn jpeg_comment(jpeg: &[u8]) -> Option<&str> {
+    let mut pos = 2;
+    // 只看图像数据 (SOS) 之前的段
+    while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF && jpeg[pos + 1] != 0xDA {
+        let len = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+        if jpeg[pos + 1] == 0xFE {
+            return std::str::from_utf8(jpeg.get(pos + 4..pos + 2 + len)?).ok();
+        }
+        pos += 2 + len;
+    }
+    None
+}
@@ -5,6 +5,113 @@ use imageproc::filter::median_filter;
 use imageproc::morphology::{dilate, erode};
 
 use super::skeleton;
+use super::types::{BlackWhiteFilterType, ColorFilterType, ImageFilter, VisionError};
+
+/// 按 ImageFilter 处理 (FreeTools 的 apply_filter 和脚本的 Image.applyFilter 共用)
+/// 还没实现的滤镜返回错误，不会原样返回图片让调用方以为处理过了
+pub fn apply(
+    img: DynamicImage,
+    filter: ImageFilter,
+    param1: Option<i32>,
+    param2: Option<i32>,
+) -> Result<DynamicImage, VisionError> {
+    let unsupported =
+        || VisionError::ProcessError(format!("Filter {:?} is not supported yet", filter));
+    Ok(match filter {
+        ImageFilter::Color(cf) => match cf {
+            ColorFilterType::Binarization => {
+                let min = param1.unwrap_or(0).clamp(0, 255) as u8;
+                let max = param2.unwrap_or(255).clamp(0, 255) as u8;
+                binarize_rgb_avg(&img, min, max)
+            }
+            ColorFilterType::Grayscale => grayscale(&img),
+            ColorFilterType::ColorPick | ColorFilterType::Posterize => return Err(unsupported()),
+        },
+        ImageFilter::BlackWhite(bw) => match bw {
+            BlackWhiteFilterType::Denoise => denoise(&img, 1),
+            BlackWhiteFilterType::Invert => invert(&img),
+            BlackWhiteFilterType::Skeleton => skeleton(&img),
+            _ => return Err(unsupported()),
+        },
+        ImageFilter::Common(_) => return Err(unsupported()),
+        ImageFilter::View => img, // 浏览模式，不做处理
+    })
+}
+
+/// 脚本里按名字调用的滤镜
+/// "grayscale" / "binarize"(min, max) / "threshold"(value) / "invert" / "denoise"(radius)
+/// "dilate" / "erode" / "skeleton" / "keepColor"(目标色, 偏色)
+pub enum NamedFilter {
+    Shared(ImageFilter, Option<i32>, Option<i32>),
+    Threshold(u8),
+    Denoise(u32),
+    Dilate,
+    Erode,
+    KeepColor(String, String),
+}
+
+impl NamedFilter {
+    pub fn parse(name: &str, params: &[String]) -> Result<Self, VisionError> {
+        let int = |i: usize| -> Result<Option<i32>, VisionError> {
+            params
+                .get(i)
+                .map(|p| {
+                    p.parse::<f64>().map(|v| v as i32).map_err(|_| {
+                        VisionError::ProcessError(format!("`{}`: `{}` is not a number", name, p))
+                    })
+                })
+                .transpose()
+        };
+        Ok(match name {
+            "grayscale" => Self::Shared(ImageFilter::Color(ColorFilterType::Grayscale), None, None),
+            "binarize" => Self::Shared(
+                ImageFilter::Color(ColorFilterType::Binarization),
+                int(0)?,
+                int(1)?,
+            ),
+            "invert" => Self::Shared(
+                ImageFilter::BlackWhite(BlackWhiteFilterType::Invert),
+                None,
+                None,
+            ),
+            "skeleton" => Self::Shared(
+                ImageFilter::BlackWhite(BlackWhiteFilterType::Skeleton),
+                None,
+                None,
+            ),
+            "threshold" => Self::Threshold(int(0)?.unwrap_or(128).clamp(0, 255) as u8),
+            "denoise" => Self::Denoise(int(0)?.unwrap_or(1).max(1) as u32),
+            "dilate" => Self::Dilate,
+            "erode" => Self::Erode,
+            "keepColor" => match params {
+                [target, bias, ..] => Self::KeepColor(target.clone(), bias.clone()),
+                [target] => Self::KeepColor(target.clone(), "#000000".into()),
+                [] => {
+                    return Err(VisionError::ProcessError(
+                        "`keepColor` needs a target color".into(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(VisionError::ProcessError(format!(
+                    "Unknown filter `{}`",
+                    name
+                )))
+            }
+        })
+    }
+
+    pub fn apply(&self, img: DynamicImage) -> Result<DynamicImage, VisionError> {
+        Ok(match self {
+            Self::Shared(filter, p1, p2) => return apply(img, *filter, *p1, *p2),
+            Self::Threshold(value) => binarize(&img, *value),
+            Self::Denoise(radius) => denoise(&img, *radius),
+            Self::Dilate => dilate_filter(&img),
+            Self::Erode => erode_filter(&img),
+            Self::KeepColor(target, bias) => keep_color(&img, target, bias),
+        })
+    }
+}
 
 /// 1. 二值化 (固定阈值)
 /// 将图片转为灰度，然后根据阈值转为纯黑白
@@ -135,3 +242,38 @@ fn is_color_match(c: [u8; 3], t: [u8; 3], b: [u8; 3]) -> bool {
 
     r_diff <= b[0] && g_diff <= b[1] && b_diff <= b[2]
 }
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+
+    use super::*;
+    use crate::vision::types::CommonFilterType;
+
+    #[test]
+    fn binarize_params_are_clamped() {
+        // 300 不能绕回 44，否则灰色 (128) 会被当成范围内
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(1, 1, Rgb([128, 128, 128])));
+        let filter = ImageFilter::Color(ColorFilterType::Binarization);
+        let out = apply(img.clone(), filter, Some(300), Some(400)).unwrap();
+        assert_eq!(out.to_luma8().get_pixel(0, 0).0, [0]);
+        let out = apply(img, filter, Some(-5), Some(300)).unwrap();
+        assert_eq!(out.to_luma8().get_pixel(0, 0).0, [255]);
+    }
+
+    #[test]
+    fn unsupported_filters_are_errors() {
+        let img = DynamicImage::ImageRgb8(RgbImage::new(1, 1));
+        for filter in [
+            ImageFilter::Color(ColorFilterType::Posterize),
+            ImageFilter::BlackWhite(BlackWhiteFilterType::Deskew),
+            ImageFilter::Common(CommonFilterType::MedianBlur),
+        ] {
+            assert!(matches!(
+                apply(img.clone(), filter, None, None),
+                Err(VisionError::ProcessError(_))
+            ));
+        }
+        assert!(apply(img, ImageFilter::View, None, None).is_ok());
+    }
+}
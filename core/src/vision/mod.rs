@@ -1,5 +1,8 @@
 pub mod analysis;
 pub mod colors;
+pub mod encode;
 pub mod filters;
+pub mod ocr;
+pub mod search;
 pub mod skeleton;
 pub mod types;
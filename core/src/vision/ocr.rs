@@ -0,0 +1,225 @@
+// ==========================================================
+// 🔤 点阵字库识别 (OCR)
+// 字库 JSON (FreeTools 字库映射导出的格式):
+//   { "glyphs": [ { "char": "3", "rows": ["0110", "1001", "0010", ...] } ] }
+//   每个字符一张二值点阵，"1" 为笔画；char 也可以是词
+// 识别前先把图片处理成白字黑底 (二值化 / keepColor)，亮度 >= 128 视为笔画
+// 每个字形在图上滑动匹配，得分为笔画的交并比 (0 ~ 1)；
+// 重叠的结果保留得分高的，再按行、从左到右拼成文本
+// ==========================================================
+
+use image::DynamicImage;
+use serde::Deserialize;
+
+use crate::vision::types::VisionError;
+
+/// 亮度不低于它视为笔画
+const FOREGROUND_LUMA: u8 = 128;
+/// 两个结果重叠超过较小者面积的这个比例，视为同一个字
+const MAX_OVERLAP: f32 = 0.5;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FontFile {
+    Glyphs { glyphs: Vec<GlyphDef> },
+    List(Vec<GlyphDef>),
+}
+
+#[derive(Deserialize)]
+struct GlyphDef {
+    #[serde(rename = "char")]
+    text: String,
+    rows: Vec<String>,
+}
+
+struct Glyph {
+    text: String,
+    width: usize,
+    height: usize,
+    /// 笔画点的坐标
+    strokes: Vec<(usize, usize)>,
+}
+
+pub struct Font {
+    glyphs: Vec<Glyph>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OcrMatch {
+    pub text: String,
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+    pub score: f32,
+}
+
+impl Font {
+    pub fn parse(json: &[u8]) -> Result<Self, VisionError> {
+        let file: FontFile = serde_json::from_slice(json)
+            .map_err(|e| VisionError::LoadError(format!("invalid font: {}", e)))?;
+        let defs = match file {
+            FontFile::Glyphs { glyphs } | FontFile::List(glyphs) => glyphs,
+        };
+        let glyphs = defs
+            .into_iter()
+            .map(Glyph::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if glyphs.is_empty() {
+            return Err(VisionError::LoadError("font has no glyphs".into()));
+        }
+        Ok(Self { glyphs })
+    }
+}
+
+impl Glyph {
+    fn parse(def: GlyphDef) -> Result<Self, VisionError> {
+        let invalid =
+            |why: &str| VisionError::LoadError(format!("invalid glyph `{}`: {}", def.text, why));
+        let width = def.rows.first().map_or(0, |r| r.len());
+        if width == 0 || def.rows.iter().any(|r| r.len() != width) {
+            return Err(invalid("rows must be non-empty and the same length"));
+        }
+        let mut strokes = Vec::new();
+        for (y, row) in def.rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '1' => strokes.push((x, y)),
+                    '0' => {}
+                    _ => return Err(invalid("rows may only contain 0 and 1")),
+                }
+            }
+        }
+        if strokes.is_empty() {
+            return Err(invalid("glyph has no strokes"));
+        }
+        Ok(Self {
+            width,
+            height: def.rows.len(),
+            strokes,
+            text: def.text,
+        })
+    }
+}
+
+/// 二值图 + 笔画计数的积分图
+struct Binary {
+    w: usize,
+    h: usize,
+    on: Vec<bool>,
+    integral: Vec<u32>,
+}
+
+impl Binary {
+    fn new(img: &DynamicImage) -> Self {
+        let luma = img.to_luma8();
+        let (w, h) = (luma.width() as usize, luma.height() as usize);
+        let on: Vec<bool> = luma.pixels().map(|p| p.0[0] >= FOREGROUND_LUMA).collect();
+        let stride = w + 1;
+        let mut integral = vec![0; stride * (h + 1)];
+        for y in 0..h {
+            let mut row = 0;
+            for x in 0..w {
+                row += on[y * w + x] as u32;
+                integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row;
+            }
+        }
+        Self { w, h, on, integral }
+    }
+
+    fn count(&self, x: usize, y: usize, w: usize, h: usize) -> u32 {
+        let stride = self.w + 1;
+        let (x1, y1) = (x + w, y + h);
+        self.integral[y1 * stride + x1] + self.integral[y * stride + x]
+            - self.integral[y * stride + x1]
+            - self.integral[y1 * stride + x]
+    }
+}
+
+/// 识别图中的所有字符，按行返回 (行从上到下，行内从左到右)
+pub fn recognize(img: &DynamicImage, font: &Font, threshold: f32) -> Vec<Vec<OcrMatch>> {
+    let binary = Binary::new(img);
+    let mut found = Vec::new();
+    for glyph in &font.glyphs {
+        if glyph.width > binary.w || glyph.height > binary.h {
+            continue;
+        }
+        let strokes = glyph.strokes.len() as f32;
+        for y in 0..=binary.h - glyph.height {
+            for x in 0..=binary.w - glyph.width {
+                // 交并比不超过 min / max，笔画数差太多的窗口直接跳过
+                let on = binary.count(x, y, glyph.width, glyph.height) as f32;
+                if on < strokes * threshold || on * threshold > strokes {
+                    continue;
+                }
+                let hit = glyph
+                    .strokes
+                    .iter()
+                    .filter(|(gx, gy)| binary.on[(y + gy) * binary.w + x + gx])
+                    .count() as f32;
+                let score = hit / (strokes + on - hit);
+                if score >= threshold {
+                    found.push(OcrMatch {
+                        text: glyph.text.clone(),
+                        left: x as u32,
+                        top: y as u32,
+                        width: glyph.width as u32,
+                        height: glyph.height as u32,
+                        score,
+                    });
+                }
+            }
+        }
+    }
+    reading_order(suppress_overlaps(found))
+}
+
+/// 得分高的优先，和已保留的结果重叠太多的丢掉 (同分时大字形优先)
+fn suppress_overlaps(mut found: Vec<OcrMatch>) -> Vec<OcrMatch> {
+    found.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then((b.width * b.height).cmp(&(a.width * a.height)))
+    });
+    let mut kept: Vec<OcrMatch> = Vec::new();
+    for m in found {
+        let overlaps = kept.iter().any(|k| {
+            let w = (m.left + m.width).min(k.left + k.width) as i64 - m.left.max(k.left) as i64;
+            let h = (m.top + m.height).min(k.top + k.height) as i64 - m.top.max(k.top) as i64;
+            let smaller = (m.width * m.height).min(k.width * k.height) as f32;
+            w > 0 && h > 0 && (w * h) as f32 > smaller * MAX_OVERLAP
+        });
+        if !overlaps {
+            kept.push(m);
+        }
+    }
+    kept
+}
+
+/// 按行分组 (竖直中心落在同一行的范围内)，行内从左到右
+fn reading_order(mut found: Vec<OcrMatch>) -> Vec<Vec<OcrMatch>> {
+    found.sort_by_key(|m| m.top * 2 + m.height);
+    let mut lines: Vec<(u32, u32, Vec<OcrMatch>)> = Vec::new();
+    for m in found {
+        let center = m.top + m.height / 2;
+        match lines
+            .iter_mut()
+            .find(|(top, bottom, _)| (*top..*bottom).contains(&center))
+        {
+            Some((top, bottom, line)) => {
+                *top = (*top).min(m.top);
+                *bottom = (*bottom).max(m.top + m.height);
+                line.push(m);
+            }
+            None => lines.push((m.top, m.top + m.height, vec![m])),
+        }
+    }
+    lines.sort_by_key(|(top, _, _)| *top);
+    lines
+        .into_iter()
+        .map(|(_, _, mut line)| {
+            line.sort_by_key(|m| m.left);
+            line
+        })
+        .collect()
+}
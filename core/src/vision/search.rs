@@ -0,0 +1,274 @@
+// ==========================================================
+// 🔍 图内查找 (找色 / 找图)
+// 坐标都是图片坐标；rect 限定查找区域，超出图片的部分会被裁掉
+// 找图用零均值归一化互相关 (ZNCC)，得分 -1 ~ 1，对整体亮度变化不敏感；
+// 模板较大时先在缩小的图上粗找，再回到原图在候选点附近精确匹配
+// ==========================================================
+
+use image::DynamicImage;
+
+use crate::core::is_color_match;
+use crate::vision::types::Rect;
+
+/// 粗找时最多保留的候选点
+const COARSE_CANDIDATES: usize = 5;
+/// 粗找的得分比阈值放宽多少 (缩小后细节丢失，得分偏低)
+const COARSE_MARGIN: f32 = 0.25;
+/// 缩小后模板的最小边长
+const MIN_COARSE_SIDE: usize = 8;
+/// 最大缩小倍数
+const MAX_COARSE_FACTOR: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImageMatch {
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+    pub score: f32,
+}
+
+/// 把区域裁到图片范围内，返回 (left, top, right, bottom)；没有交集返回 None
+pub fn clip_rect(rect: Option<Rect>, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let (w, h) = (width as i64, height as i64);
+    let (left, top, right, bottom) = match rect {
+        Some(r) => (
+            (r.left as i64).max(0),
+            (r.top as i64).max(0),
+            (r.left as i64 + r.width as i64).min(w),
+            (r.top as i64 + r.height as i64).min(h),
+        ),
+        None => (0, 0, w, h),
+    };
+    (left < right && top < bottom).then_some((left as u32, top as u32, right as u32, bottom as u32))
+}
+
+/// 找第一个颜色匹配的点 (逐行扫描)，容差为 RGB 空间的欧氏距离
+pub fn find_color(
+    img: &DynamicImage,
+    target: (u8, u8, u8),
+    tolerance: u8,
+    rect: Option<Rect>,
+) -> Option<(u32, u32)> {
+    let rgb = img.to_rgb8();
+    let (left, top, right, bottom) = clip_rect(rect, rgb.width(), rgb.height())?;
+    let (tr, tg, tb) = target;
+    for y in top..bottom {
+        for x in left..right {
+            let [r, g, b] = rgb.get_pixel(x, y).0;
+            if is_color_match(r, g, b, tr, tg, tb, tolerance) {
+                return Some((x, y));
+            }
+        }
+    }
+    None
+}
+
+/// 在图中找模板，返回得分最高且不低于 threshold 的位置
+pub fn find_image(
+    img: &DynamicImage,
+    template: &DynamicImage,
+    threshold: f32,
+    rect: Option<Rect>,
+) -> Option<ImageMatch> {
+    let (left, top, right, bottom) = clip_rect(rect, img.width(), img.height())?;
+    let area = Gray::from_image(&img.crop_imm(left, top, right - left, bottom - top));
+    let tpl = Gray::from_image(template);
+    if tpl.w == 0 || tpl.h == 0 || tpl.w > area.w || tpl.h > area.h {
+        return None;
+    }
+
+    let mut factor = 1;
+    while factor < MAX_COARSE_FACTOR
+        && tpl.w / (factor * 2) >= MIN_COARSE_SIDE
+        && tpl.h / (factor * 2) >= MIN_COARSE_SIDE
+    {
+        factor *= 2;
+    }
+
+    let full = Searcher::new(&area, &tpl);
+    let (x, y, score) = if factor == 1 {
+        full.best_in(0, 0, area.w - tpl.w, area.h - tpl.h)?
+    } else {
+        let coarse_area = area.downscale(factor);
+        let coarse_tpl = tpl.downscale(factor);
+        let coarse = Searcher::new(&coarse_area, &coarse_tpl);
+        coarse
+            .candidates(threshold - COARSE_MARGIN)
+            .into_iter()
+            .filter_map(|(cx, cy)| {
+                // 回到原图，在粗找位置附近精确匹配
+                let x0 = (cx * factor).saturating_sub(factor);
+                let y0 = (cy * factor).saturating_sub(factor);
+                let x1 = (cx * factor + factor).min(area.w - tpl.w);
+                let y1 = (cy * factor + factor).min(area.h - tpl.h);
+                full.best_in(x0, y0, x1, y1)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))?
+    };
+    (score >= threshold).then_some(ImageMatch {
+        left: left + x as u32,
+        top: top + y as u32,
+        width: tpl.w as u32,
+        height: tpl.h as u32,
+        score,
+    })
+}
+
+/// 灰度图 (f32)
+struct Gray {
+    w: usize,
+    h: usize,
+    data: Vec<f32>,
+}
+
+impl Gray {
+    fn from_image(img: &DynamicImage) -> Self {
+        let luma = img.to_luma8();
+        Self {
+            w: luma.width() as usize,
+            h: luma.height() as usize,
+            data: luma.into_raw().into_iter().map(f32::from).collect(),
+        }
+    }
+
+    /// 按 factor 做块平均缩小
+    fn downscale(&self, factor: usize) -> Self {
+        let (w, h) = (self.w / factor, self.h / factor);
+        let mut data = Vec::with_capacity(w * h);
+        let n = (factor * factor) as f32;
+        for y in 0..h {
+            for x in 0..w {
+                let mut sum = 0.0;
+                for dy in 0..factor {
+                    let row = (y * factor + dy) * self.w + x * factor;
+                    sum += self.data[row..row + factor].iter().sum::<f32>();
+                }
+                data.push(sum / n);
+            }
+        }
+        Self { w, h, data }
+    }
+}
+
+/// 对一张图 + 一个模板的 ZNCC 计算 (预先算好积分图和零均值模板)
+struct Searcher<'a> {
+    img: &'a Gray,
+    tpl_w: usize,
+    tpl_h: usize,
+    /// 模板减去均值后的像素
+    tpl: Vec<f32>,
+    tpl_mean: f64,
+    /// Σ (t - mean)²
+    tpl_var: f64,
+    /// 积分图 (w+1)*(h+1): 像素和 / 平方和
+    sum: Vec<f64>,
+    sq: Vec<f64>,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(img: &'a Gray, tpl: &Gray) -> Self {
+        let n = (tpl.w * tpl.h) as f64;
+        let tpl_mean = tpl.data.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let centered: Vec<f32> = tpl.data.iter().map(|&v| v - tpl_mean as f32).collect();
+        let tpl_var = centered.iter().map(|&v| (v as f64).powi(2)).sum();
+
+        let stride = img.w + 1;
+        let mut sum = vec![0.0; stride * (img.h + 1)];
+        let mut sq = vec![0.0; stride * (img.h + 1)];
+        for y in 0..img.h {
+            let (mut row_sum, mut row_sq) = (0.0, 0.0);
+            for x in 0..img.w {
+                let v = img.data[y * img.w + x] as f64;
+                row_sum += v;
+                row_sq += v * v;
+                sum[(y + 1) * stride + x + 1] = sum[y * stride + x + 1] + row_sum;
+                sq[(y + 1) * stride + x + 1] = sq[y * stride + x + 1] + row_sq;
+            }
+        }
+        Self {
+            img,
+            tpl_w: tpl.w,
+            tpl_h: tpl.h,
+            tpl: centered,
+            tpl_mean,
+            tpl_var,
+            sum,
+            sq,
+        }
+    }
+
+    fn window(&self, table: &[f64], x: usize, y: usize) -> f64 {
+        let stride = self.img.w + 1;
+        let (x1, y1) = (x + self.tpl_w, y + self.tpl_h);
+        table[y1 * stride + x1] - table[y * stride + x1] - table[y1 * stride + x]
+            + table[y * stride + x]
+    }
+
+    fn score(&self, x: usize, y: usize) -> f32 {
+        let n = (self.tpl_w * self.tpl_h) as f64;
+        let sum = self.window(&self.sum, x, y);
+        let var = (self.window(&self.sq, x, y) - sum * sum / n).max(0.0);
+        const EPS: f64 = 1e-6;
+        if self.tpl_var < EPS {
+            // 纯色模板: 比较窗口的均值和起伏
+            let diff = (sum / n - self.tpl_mean).abs() + (var / n).sqrt();
+            return (1.0 - diff / 255.0) as f32;
+        }
+        if var < EPS {
+            return 0.0;
+        }
+        let mut cross = 0.0f64;
+        for ty in 0..self.tpl_h {
+            let row = &self.img.data[(y + ty) * self.img.w + x..][..self.tpl_w];
+            let tpl_row = &self.tpl[ty * self.tpl_w..][..self.tpl_w];
+            cross += row
+                .iter()
+                .zip(tpl_row)
+                .map(|(&a, &b)| (a * b) as f64)
+                .sum::<f64>();
+        }
+        (cross / (var * self.tpl_var).sqrt()) as f32
+    }
+
+    /// [x0, x1] × [y0, y1] 范围内得分最高的位置
+    fn best_in(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> Option<(usize, usize, f32)> {
+        let mut best: Option<(usize, usize, f32)> = None;
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let score = self.score(x, y);
+                if best.is_none_or(|b| score > b.2) {
+                    best = Some((x, y, score));
+                }
+            }
+        }
+        best
+    }
+
+    /// 得分不低于 min_score 的前几个位置 (彼此不相邻)
+    fn candidates(&self, min_score: f32) -> Vec<(usize, usize)> {
+        let mut all = Vec::new();
+        for y in 0..=self.img.h - self.tpl_h {
+            for x in 0..=self.img.w - self.tpl_w {
+                let score = self.score(x, y);
+                if score >= min_score {
+                    all.push((x, y, score));
+                }
+            }
+        }
+        all.sort_by(|a, b| b.2.total_cmp(&a.2));
+        let mut picked: Vec<(usize, usize)> = Vec::new();
+        for (x, y, _) in all {
+            if picked
+                .iter()
+                .all(|&(px, py)| px.abs_diff(x) > 1 || py.abs_diff(y) > 1)
+            {
+                picked.push((x, y));
+                if picked.len() == COARSE_CANDIDATES {
+                    break;
+                }
+            }
+        }
+        picked
+    }
+}
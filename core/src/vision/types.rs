@@ -101,4 +101,10 @@ pub enum VisionError {
 
     #[error("Encoding failed: {0}")]
     EncodeError(String),
+
+    #[error("No screen frame available yet")]
+    NoFrame,
+
+    #[error("Invalid region: {0}")]
+    InvalidRegion(String),
 }
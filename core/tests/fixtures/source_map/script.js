@@ -0,0 +1,15 @@
+"use strict";
+(() => {
+  // src/utils.ts
+  function clamp(value, min, max) {
+    return Math.min(Math.max(value, min), max);
+  }
+
+  // src/index.ts
+  var GameScript = {
+    main: async () => {
+      const x = clamp(5, 0, 3);
+      throw new Error("boom " + x);
+    }
+  };
+})();
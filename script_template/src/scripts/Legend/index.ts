@@ -1,11 +1,10 @@
 import { fightLoop } from "./tasks";
 import { type GameConfig, DEFAULT_CONFIG } from "./config";
 
-declare const GameSettings: GameConfig;
-
 //脚本主流程
 export async function main() {
-  const config = (typeof GameSettings !== 'undefined') ? GameSettings : DEFAULT_CONFIG;
+  // 宿主下发的配置覆盖默认值
+  const config: GameConfig = { ...DEFAULT_CONFIG, ...Config.all() };
   log(`🚀 脚本启动...`);
   while (true) {
     await fightLoop(config);
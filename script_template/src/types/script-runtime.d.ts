@@ -4,11 +4,103 @@ declare global {
   /** 全局日志函数 */
   function log(msg: string): void;
 
+  // --- 定时器 ---
+  // 脚本暂停期间不触发；入口函数返回后脚本会等到所有定时器结束 (setInterval 要 clearInterval) 才结束，
+  // 出错 / 被停止 / 超时时自动清除所有定时器
+  /** delay 毫秒后调用一次 callback，多余参数原样传给 callback */
+  function setTimeout<A extends any[]>(callback: (...args: A) => void, delay?: number, ...args: A): number;
+  /** 每隔 delay 毫秒调用一次 callback */
+  function setInterval<A extends any[]>(callback: (...args: A) => void, delay?: number, ...args: A): number;
+  /** 取消定时器，id 无效时什么都不做 */
+  function clearTimeout(id: number | undefined): void;
+  function clearInterval(id: number | undefined): void;
+
+  // --- 网络请求 (精简版 fetch) ---
+  // 只支持 http://，主机必须在 manifest.json 的 network.allowedHosts 里声明；
+  // 不跟随重定向，超时 / 响应体上限见 network.timeoutMs / network.maxResponseBytes
+  interface FetchOptions {
+    /** 默认 GET */
+    method?: string;
+    headers?: Record<string, string | number>;
+    body?: string | ArrayBuffer | Uint8Array;
+    /** 本次请求的超时，不能超过清单里的 timeoutMs */
+    timeoutMs?: number;
+  }
+  interface FetchResponse {
+    readonly status: number;
+    readonly statusText: string;
+    /** status 在 200-299 之间 */
+    readonly ok: boolean;
+    readonly url: string;
+    /** 响应头，名字为小写 */
+    readonly headers: Record<string, string>;
+    /** 读取单个响应头 (不区分大小写)，不存在返回 undefined */
+    header(name: string): string | undefined;
+    text(): Promise<string>;
+    json<T = any>(): Promise<T>;
+    arrayBuffer(): Promise<ArrayBuffer>;
+  }
+  /** 发送 HTTP 请求；网络错误、超时、不在白名单时 reject (HTTP 错误码不会) */
+  function fetch(url: string, options?: FetchOptions): Promise<FetchResponse>;
+
+  /** 输入动作失败时抛出的异常 (Device 的所有方法都可能抛出) */
+  interface InputError extends Error {
+    name: "InputError";
+    code: "NOT_INITIALIZED" | "PERMISSION_DENIED" | "COMMAND_FAILED" | "UNSUPPORTED";
+  }
+
+  /** 键名 (BACK / HOME / ENTER / VOLUME_UP / A / F1 ...，可带 KEYCODE_ 前缀) 或 Android 键值 */
+  type KeyName = string | number;
+
+  interface KeyOptions {
+    /** 长按 */
+    longPress?: boolean;
+    /** 连按次数，默认 1 */
+    repeat?: number;
+    /** 同时按住的修饰键，如 ["CTRL"] (仅 Root，需 Android 13+) */
+    meta?: KeyName[];
+  }
+
   // --- Device 单例 ---
   interface DeviceInstance {
     click(x: number, y: number): void;
+    /** 在矩形区域内点击 (开启拟人化时区域内随机取点) */
+    clickRect(x: number, y: number, w: number, h: number): void;
+    /** 长按 duration 毫秒 */
+    press(x: number, y: number, duration: number): void;
     swipe(x1: number, y1: number, x2: number, y2: number, duration: number): void;
+    /**
+     * 输入文字
+     * - auto (默认): 纯 ASCII 用 keyboard，否则用 clipboard
+     * - keyboard: `input text`，仅 ASCII
+     * - clipboard: 写剪贴板后粘贴，支持中文
+     * - ime: ADB Keyboard 输入法广播 (仅 Root，需启用该输入法)
+     */
+    inputText(text: string, method?: "auto" | "keyboard" | "clipboard" | "ime"): void;
+    /**
+     * 按键。无障碍模式仅支持 BACK / HOME / RECENTS / NOTIFICATION / POWER / SCREENSHOT
+     */
+    key(key: KeyName, options?: KeyOptions): void;
+    /** 只按下 (仅 Root)，按住期间的其它按键会组合成组合键 */
+    keyDown(key: KeyName): void;
+    /** 抬起 keyDown 按下的键 */
+    keyUp(key: KeyName): void;
     shell(cmd: string): string;
+    /** 前台应用的包名，识别不出 (锁屏等) 返回 undefined */
+    currentPackage(): string | undefined;
+    /** 前台 Activity 的完整类名，前台是对话框等非 Activity 时返回 undefined */
+    currentActivity(): string | undefined;
+    /** 启动应用 (已在运行则切回前台) */
+    launchApp(pkg: string): void;
+    /** 强制停止应用 (仅 Root) */
+    forceStop(pkg: string): void;
+    /**
+     * 多脚本并行时独占设备，其它低优先级脚本的动作会等待，直到 release 或超过 ms
+     * (ms 不传则一直持有；脚本结束时自动释放)
+     */
+    acquire(ms?: number): void;
+    /** 释放 acquire 持有的设备 */
+    release(): void;
   }
   /** 全局设备对象 (直接使用，无需 new) */
   var Device: DeviceInstance;
@@ -22,13 +114,251 @@ declare global {
   var Colors: ColorsInstance;
 
   // --- Config 单例 ---
+  type ConfigJson = string | number | boolean | null | ConfigJson[] | { [key: string]: ConfigJson };
+
+  /**
+   * 配置由宿主下发，manifest.json 的 config 声明提供类型和默认值。
+   * 带类型的 getter: 值类型不符抛 TypeError，不存在且没传 defaultValue 抛 ReferenceError
+   */
   interface ConfigInstance {
-    get(key: string): string;
-    getInt(key: string): number;
+    /** 不存在时返回 defaultValue (默认 undefined) */
+    get<T = ConfigJson>(key: string, defaultValue?: T): T;
+    getString(key: string, defaultValue?: string): string;
+    getInt(key: string, defaultValue?: number): number;
+    getFloat(key: string, defaultValue?: number): number;
+    getBool(key: string, defaultValue?: boolean): boolean;
+    getList<T = ConfigJson>(key: string, defaultValue?: T[]): T[];
+    has(key: string): boolean;
+    keys(): string[];
+    /** 全部配置的拷贝 */
+    all(): Record<string, any>;
+    /** 脚本运行中宿主修改配置时回调 (键被删除时 value 为 undefined) */
+    onChange(listener: (key: string, value: ConfigJson | undefined, oldValue: ConfigJson | undefined) => void): void;
   }
   /** 全局配置对象 (直接使用，无需 new) */
   var Config: ConfigInstance;
 
+  // --- Assets 单例 (仅脚本包模式) ---
+  interface AssetsInstance {
+    /** 读取包内文本文件，路径相对包根目录 */
+    readText(path: string): string;
+    /** 读取包内二进制文件 */
+    readBytes(path: string): ArrayBuffer;
+    exists(path: string): boolean;
+  }
+  /** 脚本包内的只读资源 (模板图、字库、数据表...) */
+  var Assets: AssetsInstance;
+
+  // --- Files 单例 ---
+  /**
+   * 脚本的文件沙箱:
+   * - 不带前缀 / "data://" 是脚本自己的数据目录 (读写，按清单名隔离，没有清单时按脚本内容隔离)
+   * - "bundle://" 是脚本包内的文件 (只读，仅脚本包模式)
+   * 绝对路径和越过根目录的 ".." 会抛错
+   */
+  interface FilesInstance {
+    readText(path: string): string;
+    readBytes(path: string): ArrayBuffer;
+    /** 覆盖写入，自动创建父目录 */
+    writeText(path: string, text: string): void;
+    writeBytes(path: string, data: ArrayBuffer | Uint8Array): void;
+    /** 追加文本，文件不存在时创建 */
+    appendText(path: string, text: string): void;
+    exists(path: string): boolean;
+    /** 列出目录 (默认数据目录根)，子目录以 "/" 结尾 */
+    list(dir?: string): string[];
+    /** 删除文件或目录，返回是否存在 */
+    remove(path: string): boolean;
+  }
+  /** 全局文件对象 (直接使用，无需 new) */
+  var Files: FilesInstance;
+
+  // --- Storage 单例 ---
+  /**
+   * 跨运行保存的键值数据 (按清单名隔离，没有清单时按脚本内容隔离，保存在 App 数据目录)。
+   * 值可以是任意能 JSON 序列化的数据，读出来的是拷贝
+   */
+  interface StorageInstance {
+    /** 不存在或已过期时返回 defaultValue (默认 undefined) */
+    get<T = any>(key: string, defaultValue?: T): T;
+    /** ttlMs: 多少毫秒后过期，不传则一直保存 */
+    set(key: string, value: any, ttlMs?: number): void;
+    /** 返回是否删除了存在的键 */
+    remove(key: string): boolean;
+    has(key: string): boolean;
+    keys(): string[];
+    /** 剩余有效毫秒数，不存在或不过期返回 undefined */
+    ttl(key: string): number | undefined;
+    clear(): void;
+  }
+  /** 全局存储对象 (直接使用，无需 new) */
+  var Storage: StorageInstance;
+
+  // --- 图片 / 截图 ---
+  /** 区域 [x, y, 宽, 高]，脚本逻辑坐标 (即截图缓冲区坐标) */
+  type Region = [number, number, number, number];
+  type ImageFormat = "png" | "jpg" | "jpeg";
+
+  /**
+   * 滤镜步骤，和 FreeTools 的滤镜一致:
+   * "grayscale" | ["binarize", min, max] | ["threshold", value] | "invert" | ["denoise", radius]
+   * | "dilate" | "erode" | "skeleton" | ["keepColor", "#目标色", "#偏色"]
+   */
+  type FilterStep = string | [string, ...(string | number)[]];
+
+  interface ImageMatch {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+    score: number;
+  }
+
+  /** 点阵字库: 每个字符一张点阵，"1" 为笔画 */
+  interface OcrFont {
+    glyphs: { char: string; rows: string[] }[];
+  }
+
+  interface OcrResult {
+    /** 按行拼接，行之间用 "\n" 分隔 */
+    text: string;
+    chars: (ImageMatch & { char: string })[];
+  }
+
+  /**
+   * 脚本持有的图片，用完可以 release 立即释放内存。
+   * 处理类方法都返回新图片；结果坐标都是图片坐标
+   */
+  interface Image {
+    readonly width: number;
+    readonly height: number;
+    /** 真实屏幕坐标 = 图片坐标 * scale (保存时写进文件元数据) */
+    readonly scale: number;
+    crop(region: Region): Image;
+    /** 缩放后 scale 按宽度比例换算 */
+    resize(width: number, height: number): Image;
+    /** "#RRGGBB" */
+    getPixel(x: number, y: number): string;
+    applyFilter(name: string, ...params: (string | number)[]): Image;
+    pipeline(steps: FilterStep[]): Image;
+    /** tolerance 默认 10 */
+    findColor(color: string, tolerance?: number, region?: Region | null): [number, number] | undefined;
+    /** template 为 Image 或图片路径；threshold 为相关系数 (默认 0.9) */
+    findImage(template: Image | string, threshold?: number, region?: Region | null): ImageMatch | undefined;
+    /** 图片需先处理成白字黑底；font 为字库 JSON 路径或对象，threshold 默认 0.85 */
+    ocr(font: string | OcrFont, options?: { threshold?: number; region?: Region | null }): OcrResult;
+    /** 编码为图片字节 (默认 PNG) */
+    encode(format?: ImageFormat): ArrayBuffer;
+    /** 保存到文件沙箱 (路径规则同 Files)，format 不传按扩展名判断，没有扩展名为 PNG */
+    save(path: string, format?: ImageFormat): void;
+    release(): void;
+  }
+  interface ImageStatic {
+    /** 从文件沙箱加载 PNG / JPEG，scale 取文件元数据 (没有则为 1) */
+    load(path: string): Image;
+    /** 同 Screen.capture */
+    fromScreen(region?: Region | null): Image;
+  }
+  var Image: ImageStatic;
+
+  // --- Screen 单例 ---
+  interface ScreenInstance {
+    /** 截取当前画面 (或其中一块区域) */
+    capture(region?: Region | null): Image;
+    /** 截图并保存，例如出错时留证据: Screen.save(`fail/${Date.now()}.jpg`) */
+    save(path: string, region?: Region | null, format?: ImageFormat): void;
+  }
+  /** 当前画面 (直接使用，无需 new) */
+  var Screen: ScreenInstance;
+
+  // --- Events 单例 ---
+  interface FrameEvent {
+    /** 帧序号 (递增) */
+    seq: number;
+    width: number;
+    height: number;
+    scale: number;
+  }
+  interface ForegroundEvent {
+    package: string;
+    activity: string | undefined;
+  }
+  interface NotificationEvent {
+    package: string;
+    title: string;
+    text: string;
+  }
+  interface EventMap {
+    /** 新画面 (回调处理慢时中间的帧会被合并) */
+    frame: FrameEvent;
+    /** 前台应用 / 页面切换 */
+    foreground: ForegroundEvent;
+    /** 其它应用的新通知 (需要在 App 设置里开启通知监听) */
+    notification: NotificationEvent;
+  }
+  /**
+   * 宿主事件订阅。回调在脚本线程上调用，暂停期间的事件丢弃；
+   * 脚本结束 (入口函数返回且定时器都结束，或脚本停止) 后自动取消所有订阅；订阅本身不会让脚本继续运行
+   */
+  interface EventsInstance {
+    on<K extends keyof EventMap>(event: K, callback: (e: EventMap[K]) => void): void;
+    /** 不传 callback 则移除该事件的所有回调，返回是否移除了 */
+    off<K extends keyof EventMap>(event: K, callback?: (e: EventMap[K]) => void): boolean;
+  }
+  /** 全局事件对象 (直接使用，无需 new) */
+  var Events: EventsInstance;
+
+  // --- 控件查找 ---
+  /** 节点树里的一个控件 (抓取时的快照)，坐标为脚本逻辑坐标 */
+  interface UiObject {
+    readonly text: string | undefined;
+    readonly desc: string | undefined;
+    /** 资源 id，如 "com.app:id/ok" */
+    readonly id: string | undefined;
+    readonly className: string;
+    readonly packageName: string;
+    /** [x, y, 宽, 高] */
+    readonly bounds: Region;
+    readonly center: [number, number];
+    readonly clickable: boolean;
+    readonly enabled: boolean;
+    readonly checked: boolean;
+    readonly selected: boolean;
+    readonly focused: boolean;
+    readonly scrollable: boolean;
+    children(): UiObject[];
+    /** 在这个控件的子树里查找 */
+    findOne(selector: UiSelector): UiObject | undefined;
+    click(): void;
+  }
+  /**
+   * 控件选择器，条件链式组合，全部满足才算匹配:
+   * new UiSelector().text("同意").click()
+   */
+  class UiSelector {
+    constructor();
+    text(text: string): UiSelector;
+    textContains(text: string): UiSelector;
+    desc(desc: string): UiSelector;
+    descContains(desc: string): UiSelector;
+    /** 全名 "com.app:id/ok" 或只写 "ok" */
+    id(id: string): UiSelector;
+    /** 全名 "android.widget.Button" 或只写 "Button" */
+    className(name: string): UiSelector;
+    packageName(name: string): UiSelector;
+    clickable(value?: boolean): UiSelector;
+    enabled(value?: boolean): UiSelector;
+    checked(value?: boolean): UiSelector;
+    selected(value?: boolean): UiSelector;
+    scrollable(value?: boolean): UiSelector;
+    /** timeoutMs 内反复查找，不传则只找一次 */
+    findOne(timeoutMs?: number): UiObject | undefined;
+    findAll(): UiObject[];
+    exists(): boolean;
+    /** 找到后点击，返回是否找到 */
+    click(timeoutMs?: number): boolean;
+  }
+
   // --- Thread 单例 ---
   interface ThreadInstance {
     sleep(ms: number): Promise<void>;
@@ -1,12 +1,15 @@
 // 定义接口类型
 interface AndroidTouchHelper {
   saveScript(script: string): void;
+  /** 保存配置 (JSON 对象)，脚本通过 Config 读取 */
+  saveConfig(json: string): void;
   log(msg: string): void;
 }
 
 // 1. Mock 实现 (用于浏览器调试)
 const MockBridge: AndroidTouchHelper = {
   saveScript: (s) => console.log(`[Mock] Saved script (${s.length} chars)`),
+  saveConfig: (json) => console.log(`[Mock] Saved config ${json}`),
   log: (msg) => console.log(`[Mock Log] ${msg}`)
 };
 
@@ -0,0 +1,126 @@
+import net from 'net';
+import readline from 'readline';
+import { readDevToken } from './dev-token.js';
+
+// ==========================================
+// 🐞 脚本调试客户端
+// 用法 (先在 App 设置里打开开发者模式，再点一次开始):
+//   adb forward tcp:9229 tcp:9229
+//   node tools/debug.js [port] [token]
+// 令牌不传时通过 adb run-as 从 App 私有目录读取
+// ==========================================
+const PORT = Number(process.argv[2] || 9229);
+const TOKEN = process.argv[3] || readDevToken();
+
+const HELP = `
+  b <file> <line>   在源文件 (如 Legend/tasks.ts) 的某行设置断点，不带参数清空
+  c                 继续运行        p     暂停
+  s                 单步进入        n     单步跳过      o   单步跳出
+  e <expr>          在当前作用域求值
+  vars              当前作用域的变量
+  props <expr>      对象的属性
+  bt                调用栈
+  ex <none|uncaught|all>  遇到异常时暂停
+  status / help / quit
+`;
+
+let nextId = 1;
+const breakpoints = new Map(); // source -> lines
+
+if (!TOKEN) {
+  console.error('❌ 读取调试令牌失败: 请确认 App 已打开开发者模式，或手动传入令牌');
+  process.exit(1);
+}
+
+const socket = net.connect(PORT, '127.0.0.1', () => {
+  send('authenticate', { token: TOKEN });
+  console.log(`🔌 已连接到 127.0.0.1:${PORT}，输入 help 查看命令`);
+  rl.prompt();
+});
+
+const rl = readline.createInterface({ input: process.stdin, output: process.stdout, prompt: 'debug> ' });
+
+function send(command, args) {
+  const request = { id: nextId++, command };
+  if (args !== undefined) request.arguments = args;
+  socket.write(JSON.stringify(request) + '\n');
+}
+
+function formatLocation(loc) {
+  if (!loc) return '(脚本已退出)';
+  const o = loc.original;
+  return o ? `${o.source}:${o.line}:${o.column} (script.js:${loc.line})` : `script.js:${loc.line}`;
+}
+
+function printMessage(msg) {
+  if (msg.event === 'paused') {
+    console.log(`⏸️  暂停 [${msg.body.reason}] ${formatLocation(msg.body.location)}`);
+    if (msg.body.exception) console.log(`   ${msg.body.exception.value}`);
+  } else if (msg.event) {
+    console.log(`📣 ${msg.event} ${JSON.stringify(msg.body)}`);
+  } else if (!msg.success) {
+    console.log(`❌ ${msg.message}`);
+  } else if (msg.body.result) {
+    console.log(`= (${msg.body.result.type}) ${msg.body.result.value}`);
+  } else if (msg.body.variables || msg.body.properties) {
+    for (const v of msg.body.variables || msg.body.properties) console.log(`  ${v.name}: (${v.type}) ${v.value}`);
+  } else if (msg.body.frames) {
+    msg.body.frames.forEach((f, i) => console.log(`  #${i} ${f.function} ${formatLocation(f)}`));
+  } else if (Object.keys(msg.body).length > 0) {
+    console.log(JSON.stringify(msg.body));
+  }
+}
+
+readline.createInterface({ input: socket }).on('line', line => {
+  try {
+    printMessage(JSON.parse(line));
+  } catch {
+    console.log(line);
+  }
+  rl.prompt(true);
+});
+
+socket.on('close', () => {
+  console.log('🔌 连接已断开');
+  process.exit(0);
+});
+socket.on('error', e => {
+  console.error(`❌ 连接失败: ${e.message} (是否已执行 adb forward 并开启调试服务?)`);
+  process.exit(1);
+});
+
+rl.on('line', input => {
+  const [cmd, ...rest] = input.trim().split(/\s+/);
+  const arg = input.trim().slice(cmd.length).trim();
+  switch (cmd) {
+    case 'b': {
+      if (rest.length < 2) {
+        for (const source of breakpoints.keys()) send('setBreakpoints', { source, lines: [] });
+        breakpoints.clear();
+        break;
+      }
+      const [source, line] = rest;
+      const lines = [...(breakpoints.get(source) || []), Number(line)];
+      breakpoints.set(source, lines);
+      send('setBreakpoints', { source, lines });
+      break;
+    }
+    case 'c': send('continue'); break;
+    case 'p': send('pause'); break;
+    case 's': send('stepIn'); break;
+    case 'n': send('stepOver'); break;
+    case 'o': send('stepOut'); break;
+    case 'e': send('evaluate', { expression: arg }); break;
+    case 'vars': send('variables'); break;
+    case 'props': send('properties', { expression: arg }); break;
+    case 'bt': send('stackTrace'); break;
+    case 'ex': send('setPauseOnExceptions', { mode: rest[0] || 'uncaught' }); break;
+    case 'status': send('status'); break;
+    case 'quit': socket.end(); return;
+    case '':
+      break;
+    default:
+      console.log(HELP);
+  }
+  rl.prompt();
+});
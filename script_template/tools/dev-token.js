@@ -0,0 +1,18 @@
+import { execSync } from 'child_process';
+
+// App 的安装包名 (applicationId)
+export const PACKAGE_NAME = 'org.eu.freex.touchhelper';
+
+// 开发服务 (调试 / 热重载推送) 的令牌: 环境变量 TOUCH_DEV_TOKEN 优先，
+// 否则从 App 私有目录读取 (App 设置里打开开发者模式后生成，run-as 只对可调试的包有效)
+export function readDevToken() {
+  if (process.env.TOUCH_DEV_TOKEN) return process.env.TOUCH_DEV_TOKEN.trim();
+  try {
+    return execSync(`adb shell run-as ${PACKAGE_NAME} cat files/dev_token`, {
+      encoding: 'utf8',
+      stdio: ['ignore', 'pipe', 'ignore'],
+    }).trim();
+  } catch {
+    return '';
+  }
+}
@@ -1,8 +1,10 @@
 import * as esbuild from 'esbuild';
 import { spawn, exec } from 'child_process';
 import fs from 'fs';
+import net from 'net';
 import path from 'path';
 import { fileURLToPath } from 'url';
+import { readDevToken } from './dev-token.js';
 
 // ==========================================
 // 1. 配置区域
@@ -16,7 +18,8 @@ const OUT_FILE = path.join(__dirname, '../public/script.js');
 
 // ADB 与 Vite 配置
 const PORT = 5173;
-const PACKAGE_NAME = "org.eu.freex.app";
+// 脚本热重载推送端口 (App 设置里打开开发者模式后开启，对应 startHotReload 的 pushPort)
+const PUSH_PORT = 9230;
 
 // ==========================================
 // 2. 核心逻辑
@@ -52,6 +55,7 @@ async function startDev() {
               console.error(`❌ 脚本编译失败`);
             } else {
               console.log(`⚡ 脚本更新成功: public/script.js`);
+              pushScript();
             }
           });
         },
@@ -92,6 +96,11 @@ async function startDev() {
         wakeUpApp();
       }
     });
+
+    exec(`adb forward tcp:${PUSH_PORT} tcp:${PUSH_PORT}`, (err) => {
+      if (err) console.error(`❌ ADB Forward Failed: ${err.message}`);
+      else console.log(`✅ 热重载端口映射成功: PC:${PUSH_PORT} -> Phone:${PUSH_PORT}`);
+    });
   }, 2000);
 
   // 退出清理
@@ -102,6 +111,41 @@ async function startDev() {
   });
 }
 
+// 把最新的 script.js (+ map) 推给 App，App 停掉当前脚本并重新运行
+// App 端需要打开开发者模式并点一次开始 (开启热重载)，没连上时静默跳过
+// 令牌每次 App 重启都会变，读取失败时下次推送再试
+let devToken = '';
+function pushScript() {
+  devToken = devToken || readDevToken();
+  if (!devToken) {
+    console.log('⚠️ 没有读取到开发者模式令牌，跳过推送 (App 设置里打开开发者模式)');
+    return;
+  }
+  const files = { 'script.js': fs.readFileSync(OUT_FILE, 'utf8') };
+  if (fs.existsSync(`${OUT_FILE}.map`)) {
+    files['script.js.map'] = fs.readFileSync(`${OUT_FILE}.map`, 'utf8');
+  }
+  const socket = net.connect(PUSH_PORT, '127.0.0.1', () => {
+    socket.write(JSON.stringify({ token: devToken, files }) + '\n');
+  });
+  socket.setEncoding('utf8');
+  socket.on('data', data => {
+    try {
+      const res = JSON.parse(data.split('\n')[0]);
+      if (res.success) console.log(`🔥 已热重载: 脚本 #${res.scriptId} (${res.reloadMs} ms)`);
+      else {
+        console.error(`❌ 热重载失败: ${res.message}`);
+        // App 重启后令牌会变，下次推送重新读取
+        if (res.message === 'Invalid token') devToken = '';
+      }
+    } catch {
+      // 忽略
+    }
+    socket.end();
+  });
+  socket.on('error', () => {});
+}
+
 function wakeUpApp() {
   const url = `http://localhost:${PORT}`;
   const cmd = `adb shell am broadcast -a org.eu.freex.LOAD_UI --es path "${url}"`;
@@ -0,0 +1,47 @@
+import http from 'http';
+
+// ==========================================
+// 🧪 本地 HTTP 替身 (调试脚本里的 fetch)
+// 用法:
+//   node tools/mock-server.js [port]
+//   adb reverse tcp:8787 tcp:8787     # 手机上的 127.0.0.1:8787 转到电脑
+// manifest.json 里声明: "network": { "allowedHosts": ["127.0.0.1:8787"] }
+//
+//   GET  /config      返回 CONFIG (脚本拉取配置)
+//   POST /progress    打印脚本上报的进度
+//   其它              原样回显请求 (方法 / 路径 / 请求头 / 请求体)
+// ==========================================
+const PORT = Number(process.argv[2] || 8787);
+
+const CONFIG = { loops: 3, notice: 'hello from mock server' };
+
+function reply(res, status, body) {
+  const text = JSON.stringify(body);
+  res.writeHead(status, { 'Content-Type': 'application/json', 'Content-Length': Buffer.byteLength(text) });
+  res.end(text);
+}
+
+http.createServer((req, res) => {
+  const chunks = [];
+  req.on('data', (chunk) => chunks.push(chunk));
+  req.on('end', () => {
+    const body = Buffer.concat(chunks).toString();
+    const time = new Date().toLocaleTimeString();
+    console.log(`[${time}] ${req.method} ${req.url}${body ? ` ${body}` : ''}`);
+
+    if (req.method === 'GET' && req.url === '/config') {
+      return reply(res, 200, CONFIG);
+    }
+    if (req.method === 'POST' && req.url === '/progress') {
+      try {
+        console.log('📈 进度:', JSON.parse(body));
+      } catch {
+        return reply(res, 400, { error: 'body is not JSON' });
+      }
+      return reply(res, 200, { ok: true });
+    }
+    reply(res, 200, { method: req.method, url: req.url, headers: req.headers, body });
+  });
+}).listen(PORT, () => {
+  console.log(`🧪 Mock server listening on http://127.0.0.1:${PORT}`);
+});